@@ -0,0 +1,164 @@
+//! counts the heap allocations made while filling each map with small
+//! `Copy` values, and times the insertions
+//!
+//! values are stored inline, so the binary trees should make one allocation
+//! per entry (the node), and the B-tree well under one
+//! the unsized-value path of `BsTreeMap` still boxes every value, and is
+//! printed next to the inline one for comparison
+//! `ArenaBsTreeMap` keeps its nodes in one growing `Vec`, so it should
+//! only allocate when that doubles
+//!
+//! run with `cargo bench --bench allocations`
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use terees::avl_tree::map::AvlTreeMap;
+use terees::b_tree::map::BTreeMap;
+use terees::binary_search_tree::arena::map::ArenaBsTreeMap;
+use terees::binary_search_tree::map::BsTreeMap;
+use terees::red_black_tree::map::RbTreeMap;
+
+/// wraps the system allocator, counting every allocation
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+// SAFETY: every call is forwarded to `System` unchanged
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const ENTRIES: u64 = 100_000;
+const ROUNDS: u32 = 10;
+
+/// shuffles `0..ENTRIES` with a fixed linear congruential generator,
+/// so the unbalanced tree stays shallow and every run sees the same keys
+fn keys() -> Vec<u64> {
+    let mut keys: Vec<u64> = (0..ENTRIES).collect();
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    for i in (1..keys.len()).rev() {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        keys.swap(i, (state >> 33) as usize % (i + 1));
+    }
+    keys
+}
+
+/// the allocations and time per entry of a single round
+struct Cost {
+    allocations: f64,
+    nanos: f64,
+}
+
+/// runs `fill` `ROUNDS` times, and measures the allocations and time
+/// per entry of a single round
+fn measure<M>(keys: &[u64], mut fill: impl FnMut(&[u64]) -> M) -> Cost {
+    let mut allocations = 0;
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..ROUNDS {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        let map = black_box(fill(keys));
+        elapsed += start.elapsed();
+        allocations += ALLOCATIONS.load(Ordering::Relaxed) - before;
+        drop(map);
+    }
+    let per_round = f64::from(ROUNDS) * keys.len() as f64;
+    Cost {
+        allocations: allocations as f64 / per_round,
+        nanos: elapsed.as_nanos() as f64 / per_round,
+    }
+}
+
+/// prints a row of the table, with the boxed columns left empty for maps
+/// that store every value inline
+fn report(name: &str, inline: Cost, boxed: Option<Cost>) {
+    let (allocations, nanos) = match boxed {
+        Some(boxed) => (
+            format!("{:.3}", boxed.allocations),
+            format!("{:.1}", boxed.nanos),
+        ),
+        None => ("-".to_string(), "-".to_string()),
+    };
+    println!(
+        "{name:<24} {:>14.3} {allocations:>14} {:>14.1} {nanos:>14}",
+        inline.allocations, inline.nanos,
+    );
+}
+
+fn main() {
+    let keys = keys();
+    println!(
+        "{:<24} {:>14} {:>14} {:>14} {:>14}",
+        "per entry", "inline allocs", "boxed allocs", "inline ns", "boxed ns",
+    );
+    let inline = measure(&keys, |keys| {
+        let mut map = BsTreeMap::new();
+        for &key in keys {
+            map.insert(key, key);
+        }
+        map
+    });
+    let boxed = measure(&keys, |keys| {
+        let mut map: BsTreeMap<u64, [u64]> = BsTreeMap::new();
+        for &key in keys {
+            map.insert_boxed(key, Box::new([key]));
+        }
+        map
+    });
+    report("BsTreeMap", inline, Some(boxed));
+    let inline = measure(&keys, |keys| {
+        let mut map = ArenaBsTreeMap::new();
+        for &key in keys {
+            map.insert(key, key);
+        }
+        map
+    });
+    report("ArenaBsTreeMap", inline, None);
+    let inline = measure(&keys, |keys| {
+        let mut map = AvlTreeMap::new();
+        for &key in keys {
+            map.insert(key, key);
+        }
+        map
+    });
+    report("AvlTreeMap", inline, None);
+    let inline = measure(&keys, |keys| {
+        let mut map = RbTreeMap::new();
+        for &key in keys {
+            map.insert(key, key);
+        }
+        map
+    });
+    report("RbTreeMap", inline, None);
+    let inline = measure(&keys, |keys| {
+        let mut map = BTreeMap::new();
+        for &key in keys {
+            map.insert(key, key);
+        }
+        map
+    });
+    report("BTreeMap", inline, None);
+}
@@ -0,0 +1,170 @@
+//! the allocators that `LinkedList`, `BsTreeMap` and `BTreeMap` get their
+//! nodes from
+//!
+//! with the `allocator_api` feature, which needs a nightly compiler, these
+//! are the standard `Allocator`, `Global` and `AllocError`, so any allocator
+//! written for the standard library works as is
+//! otherwise, `Allocator` is a stand-in with the same required methods,
+//! which can be implemented on stable and moved over to the standard trait
+//! by only changing the import
+//!
+//! an allocator for a bump arena, used through a reference to it:
+//!
+//! ```
+//! # #![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+//! use std::alloc::Layout;
+//! use std::cell::Cell;
+//! use std::ptr::NonNull;
+//! use terees::alloc::{AllocError, Allocator};
+//! use terees::linked_list::LinkedList;
+//!
+//! struct Bump {
+//!     memory: Box<[Cell<u64>]>,
+//!     used: Cell<usize>,
+//! }
+//!
+//! unsafe impl Allocator for Bump {
+//!     fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+//!         let words = layout.size().div_ceil(8);
+//!         let start = self.used.get();
+//!         if layout.align() > 8 || start + words > self.memory.len() {
+//!             return Err(AllocError);
+//!         }
+//!         self.used.set(start + words);
+//!         let ptr = self.memory[start..].as_ptr().cast_mut().cast::<u8>();
+//!         let ptr = NonNull::new(ptr).ok_or(AllocError)?;
+//!         Ok(NonNull::slice_from_raw_parts(ptr, words * 8))
+//!     }
+//!
+//!     unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+//! }
+//!
+//! let bump = Bump {
+//!     memory: (0..64).map(|_| Cell::new(0)).collect(),
+//!     used: Cell::new(0),
+//! };
+//! let mut list = LinkedList::new_in(&bump);
+//! list.extend([1, 2, 3]);
+//! assert_eq!(list.iter().sum::<i32>(), 6);
+//! assert!(bump.used.get() > 0);
+//! ```
+
+#[cfg(feature = "allocator_api")]
+pub use alloc_crate::alloc::{AllocError, Allocator, Global};
+
+#[cfg(not(feature = "allocator_api"))]
+pub use shim::{AllocError, Allocator, Global};
+
+#[cfg(not(feature = "allocator_api"))]
+mod shim {
+    use alloc_crate::alloc::{self, Layout};
+    use core::error::Error;
+    use core::fmt;
+    use core::ptr::{self, NonNull};
+
+    /// the error returned when an allocator cannot hand out memory
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct AllocError;
+
+    impl fmt::Display for AllocError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("memory allocation failed")
+        }
+    }
+
+    impl Error for AllocError {}
+
+    /// a source of memory, like the standard `Allocator`
+    ///
+    /// # Safety
+    /// a block returned by `allocate` must stay valid until it is passed to
+    /// `deallocate` on the same allocator, a clone of it, or a reference to
+    /// either, and must fit `layout`
+    pub unsafe trait Allocator {
+        /// hands out a block of memory that fits `layout`
+        fn allocate(&self, layout: Layout)
+            -> Result<NonNull<[u8]>, AllocError>;
+
+        /// gives back a block of memory
+        ///
+        /// # Safety
+        /// `ptr` must come from `allocate` on this allocator, with the same
+        /// `layout`, and must not be used afterwards
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+    }
+
+    /// the global allocator, which every container uses by default
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct Global;
+
+    // SAFETY: blocks come straight from the global allocator and go back to
+    // it, and zero-sized ones are never allocated at all
+    unsafe impl Allocator for Global {
+        fn allocate(
+            &self,
+            layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            let ptr = match layout.size() {
+                0 => ptr::without_provenance_mut(layout.align()),
+                // SAFETY: `layout` has a non-zero size
+                _ => unsafe { alloc::alloc(layout) },
+            };
+            let ptr = NonNull::new(ptr).ok_or(AllocError)?;
+            Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            if layout.size() != 0 {
+                alloc::dealloc(ptr.as_ptr(), layout);
+            }
+        }
+    }
+
+    // SAFETY: every call is forwarded to `A` unchanged
+    unsafe impl<A> Allocator for &A
+    where
+        A: Allocator + ?Sized,
+    {
+        fn allocate(
+            &self,
+            layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            (**self).allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            (**self).deallocate(ptr, layout)
+        }
+    }
+}
+
+/// an allocator for tests that forwards to `Global`, and counts the blocks
+/// it has handed out and the ones still in use
+#[cfg(test)]
+#[derive(Default)]
+pub struct Counting {
+    pub allocated: core::cell::Cell<usize>,
+    pub live: core::cell::Cell<usize>,
+}
+
+// SAFETY: every call is forwarded to `Global` unchanged
+#[cfg(test)]
+unsafe impl Allocator for Counting {
+    fn allocate(
+        &self,
+        layout: core::alloc::Layout,
+    ) -> Result<core::ptr::NonNull<[u8]>, AllocError> {
+        self.allocated.set(self.allocated.get() + 1);
+        self.live.set(self.live.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(
+        &self,
+        ptr: core::ptr::NonNull<u8>,
+        layout: core::alloc::Layout,
+    ) {
+        self.live.set(self.live.get() - 1);
+        Global.deallocate(ptr, layout)
+    }
+}
@@ -0,0 +1,669 @@
+//! a self-balancing binary search tree with keys and values
+//! after every insertion and removal, the heights of the two subtrees of
+//! any node differ by at most one, so lookups stay logarithmic
+
+use super::node::Node;
+use crate::entry::{Entry, Value};
+use crate::merge::{merge_sorted, KeepRight};
+use alloc_crate::boxed::Box;
+use alloc_crate::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::mem;
+use core::ops::RangeBounds;
+
+pub use super::iter::{
+    IntoIter, Iter, IterMut, Keys, Range, RangeMut, Values, ValuesMut,
+};
+
+pub struct AvlTreeMap<K, V>
+where
+    K: Ord,
+    V: Value + ?Sized,
+{
+    root: Option<Box<Node<K, V>>>,
+    size: usize,
+}
+
+impl<K, V> AvlTreeMap<K, V>
+where
+    K: Ord,
+{
+    /// creates an empty `AvlTreeMap`
+    #[inline]
+    pub const fn new() -> AvlTreeMap<K, V> {
+        AvlTreeMap {
+            root: None,
+            size: 0,
+        }
+    }
+
+    /// returns the number of entries in `self`
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// returns `true` if `self` contains an entry with key `key`
+    /// otherwise returns `false`
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.root {
+            Some(ref node) => node.contains(key),
+            None => false,
+        }
+    }
+
+    /// sets the value of the key with key `key` to `value`
+    /// if `key` already exists, the value is overridden
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) {
+        if Node::insert(&mut self.root, key, value) {
+            self.size += 1;
+        }
+    }
+
+    /// inserts a new entry with key `key` and value `value`
+    /// if `key` already exists, the old value is kept
+    #[inline]
+    pub fn try_insert(&mut self, key: K, value: V) {
+        if Node::try_insert(&mut self.root, key, value) {
+            self.size += 1;
+        }
+    }
+
+    /// moves all entries of `other` into `self`, leaving `other` empty
+    /// if both contain an entry with the same key, the value from `other`
+    /// is kept
+    ///
+    /// runs in linear time
+    pub fn append(&mut self, other: &mut AvlTreeMap<K, V>) {
+        let this = mem::take(self).into_iter();
+        let merged =
+            merge_sorted(this, mem::take(other).into_iter(), KeepRight);
+        *self = AvlTreeMap::from_sorted(merged);
+    }
+
+    /// moves every entry with a key of at least `key` into a new map
+    /// and returns it
+    pub fn split_off<Q>(&mut self, key: &Q) -> AvlTreeMap<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut entries: Vec<(K, V)> = mem::take(self).into_iter().collect();
+        let at = entries.partition_point(|(left, _)| left.borrow() < key);
+        let right = entries.split_off(at);
+        *self = AvlTreeMap::from_sorted(entries);
+        AvlTreeMap::from_sorted(right)
+    }
+
+    /// builds a balanced tree out of `entries`, which must be sorted by key
+    /// and free of duplicates
+    fn from_sorted(entries: Vec<(K, V)>) -> AvlTreeMap<K, V> {
+        AvlTreeMap {
+            size: entries.len(),
+            root: Node::from_sorted(entries.len(), &mut entries.into_iter()),
+        }
+    }
+
+    /// removes the entry with key `key` and returns it
+    /// returns `None` if no such entry exists
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let entry = Node::remove(&mut self.root, key)?;
+        self.size -= 1;
+        Some(entry)
+    }
+
+    /// returns an optional reference to the `value` with key `key`
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.root {
+            Some(ref node) => node.get(key),
+            None => None,
+        }
+    }
+
+    /// returns an optional mutable reference to the `value` with key `key`
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.root {
+            Some(ref mut node) => node.get_mut(key),
+            None => None,
+        }
+    }
+
+    /// returns an optional reference to the smallest entry
+    pub fn smallest(&self) -> Option<&Entry<K, V>> {
+        self.root.as_ref().map(|node| node.smallest())
+    }
+
+    /// returns an optional mutable reference to the smallest entry
+    pub fn smallest_mut(&mut self) -> Option<&mut Entry<K, V>> {
+        self.root.as_mut().map(|node| node.smallest_mut())
+    }
+
+    /// returns an optional reference to the largest entry
+    pub fn largest(&self) -> Option<&Entry<K, V>> {
+        self.root.as_ref().map(|node| node.largest())
+    }
+
+    /// returns an optional mutable reference to the largest entry
+    pub fn largest_mut(&mut self) -> Option<&mut Entry<K, V>> {
+        self.root.as_mut().map(|node| node.largest_mut())
+    }
+
+    /// returns the entry with `index` smaller entries,
+    /// or `None` if `index` is out of bounds
+    /// takes time proportional to the height of `self`
+    pub fn nth(&self, index: usize) -> Option<(&K, &V)> {
+        self.root.as_ref()?.nth(index).map(Entry::split)
+    }
+
+    /// returns the entry with `index` smaller entries,
+    /// with a mutable reference to its value,
+    /// or `None` if `index` is out of bounds
+    pub fn nth_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.root.as_mut()?.nth_mut(index).map(Entry::split_mut)
+    }
+
+    /// returns the number of entries with keys smaller than `key`,
+    /// whether or not `self` contains `key`
+    /// takes time proportional to the height of `self`
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.root.as_ref().map_or(0, |node| node.rank(key))
+    }
+
+    /// returns the key that has `rank` smaller keys,
+    /// the inverse of `rank` for keys in `self`
+    pub fn select(&self, rank: usize) -> Option<&K> {
+        self.nth(rank).map(|(key, _)| key)
+    }
+
+    /// returns the number of nodes on the longest path from the root
+    /// of `self` down to a leaf
+    pub fn height(&self) -> usize {
+        Node::height(&self.root)
+    }
+
+    /// returns an iterator over the entries of `self`, in order of their keys
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(self.root.as_deref(), self.size)
+    }
+
+    /// returns an iterator over the entries of `self`, in order of their keys,
+    /// with mutable references to the values
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut::new(self.root.as_deref_mut(), self.size)
+    }
+
+    /// returns an iterator over the keys of `self`, in order
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys::new(self.iter())
+    }
+
+    /// returns an iterator over the values of `self`, in order of their keys
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values::new(self.iter())
+    }
+
+    /// returns an iterator over mutable references to the values of `self`,
+    /// in order of their keys
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut::new(self.iter_mut())
+    }
+
+    /// returns an iterator over the entries of `self` whose keys lie within
+    /// `range`, in order of their keys
+    /// if the start of `range` is after its end, the iterator is empty
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Range::new(self.root.as_deref(), &range)
+    }
+
+    /// returns an iterator over the entries of `self` whose keys lie within
+    /// `range`, in order of their keys, with mutable references to the values
+    /// if the start of `range` is after its end, the iterator is empty
+    pub fn range_mut<Q, R>(&mut self, range: R) -> RangeMut<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        RangeMut::new(self.root.as_deref_mut(), &range)
+    }
+}
+
+impl<K, V> IntoIterator for AvlTreeMap<K, V>
+where
+    K: Ord,
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self.root, self.size)
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a AvlTreeMap<K, V>
+where
+    K: Ord,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut AvlTreeMap<K, V>
+where
+    K: Ord,
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> Default for AvlTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for AvlTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = AvlTreeMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V> Extend<(K, V)> for AvlTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> Extend<(&'a K, &'a V)> for AvlTreeMap<K, V>
+where
+    K: Ord + Copy,
+    V: Copy,
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(&key, &value)| (key, value)));
+    }
+}
+
+impl<K, V, const N: usize> From<[(K, V); N]> for AvlTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from(array: [(K, V); N]) -> Self {
+        Self::from_iter(array)
+    }
+}
+
+impl<K, V> From<Vec<(K, V)>> for AvlTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from(vec: Vec<(K, V)>) -> Self {
+        Self::from_iter(vec)
+    }
+}
+
+impl<K, V> Clone for AvlTreeMap<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        self.iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+impl<K, V> fmt::Debug for AvlTreeMap<K, V>
+where
+    K: Ord + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K, V> PartialEq for AvlTreeMap<K, V>
+where
+    K: Ord,
+    V: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.size() == other.size() && self.iter().eq(other)
+    }
+}
+
+impl<K, V> Eq for AvlTreeMap<K, V>
+where
+    K: Ord,
+    V: Eq,
+{
+}
+
+impl<K, V> Hash for AvlTreeMap<K, V>
+where
+    K: Ord + Hash,
+    V: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.size());
+        for entry in self {
+            entry.hash(state);
+        }
+    }
+}
+
+impl<K, V> PartialOrd for AvlTreeMap<K, V>
+where
+    K: Ord,
+    V: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<K, V> Ord for AvlTreeMap<K, V>
+where
+    K: Ord,
+    V: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc_crate::{format, vec};
+
+    // set up a tree to apply tests to
+    fn basic_tree() -> AvlTreeMap<usize, &'static str> {
+        let mut tree = AvlTreeMap::new();
+        tree.insert(5, " , ");
+        tree.insert(3, "hello");
+        tree.insert(6, "world");
+        tree.insert(7, "this is the largest entry");
+        tree.insert(1, "this is the smallest entry");
+        tree.insert(4, "hmmm");
+
+        tree
+    }
+
+    // checks the ordering, heights and balance of every node,
+    // returning the height of the subtree
+    fn check_node(
+        link: &Option<Box<Node<usize, usize>>>,
+        low: Option<usize>,
+        high: Option<usize>,
+    ) -> usize {
+        let Some(node) = link else {
+            return 0;
+        };
+        let key = *node.key();
+        assert!(low.is_none_or(|low| low < key));
+        assert!(high.is_none_or(|high| key < high));
+        let left = check_node(&node.left, low, Some(key));
+        let right = check_node(&node.right, Some(key), high);
+        assert!(left.abs_diff(right) <= 1, "unbalanced at {key}");
+        assert_eq!(Node::height(link), 1 + left.max(right));
+        assert_eq!(
+            node.size(),
+            1 + Node::size_of(&node.left) + Node::size_of(&node.right)
+        );
+        1 + left.max(right)
+    }
+
+    fn check(tree: &AvlTreeMap<usize, usize>) {
+        check_node(&tree.root, None, None);
+        assert_eq!(tree.iter().count(), tree.size());
+        assert_eq!(Node::size_of(&tree.root), tree.size());
+    }
+
+    #[test]
+    fn insert() {
+        let mut tree = AvlTreeMap::new();
+        tree.insert(5, " , ");
+        tree.insert(3, "hello");
+        tree.insert(7, "haha get replaced");
+        tree.insert(6, "world");
+        tree.insert(7, "hii");
+
+        assert_eq!(tree.size(), 4);
+        assert_eq!(tree.get(&7), Some(&"hii"));
+        assert_eq!(tree.get(&8), None);
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut tree = basic_tree();
+        tree.try_insert(7, "already exists!");
+        assert_eq!(tree.size(), 6);
+        assert_eq!(tree.get(&7), Some(&"this is the largest entry"));
+        tree.try_insert(9, "does not exist yet");
+        assert_eq!(tree.size(), 7);
+    }
+
+    #[test]
+    fn sorted_insert_stays_balanced() {
+        let mut tree = AvlTreeMap::new();
+        for key in 0..1000 {
+            tree.insert(key, key);
+            check(&tree);
+        }
+        // 1.44 * log2(1000) is just under 15
+        assert!(tree.height() <= 14);
+        for key in (0..1000).rev() {
+            tree.insert(key + 1000, key);
+        }
+        check(&tree);
+        assert_eq!(tree.size(), 2000);
+    }
+
+    #[test]
+    fn remove() {
+        let mut tree = basic_tree();
+        assert_eq!(tree.remove(&5), Some(Entry::new(5, " , ")));
+        assert!(!tree.contains(&5));
+        assert_eq!(tree.size(), 5);
+
+        assert_eq!(tree.remove(&3), Some(Entry::new(3, "hello")));
+        assert_eq!(tree.remove(&3), None);
+        assert_eq!(tree.size(), 4);
+
+        let keys: Vec<&usize> = tree.keys().collect();
+        assert_eq!(keys, [&1, &4, &6, &7]);
+    }
+
+    #[test]
+    fn remove_stays_balanced() {
+        let mut tree = AvlTreeMap::new();
+        for key in 0..500 {
+            tree.insert((key * 7919) % 500, key);
+        }
+        for key in (0..500).step_by(3) {
+            assert!(tree.remove(&key).is_some());
+            check(&tree);
+        }
+        for key in 0..500 {
+            assert_eq!(tree.contains(&key), key % 3 != 0);
+        }
+        while let Some(&key) = tree.keys().next() {
+            tree.remove(&key);
+            check(&tree);
+        }
+        assert_eq!(tree.size(), 0);
+        assert!(tree.root.is_none());
+    }
+
+    #[test]
+    fn contains() {
+        let tree = basic_tree();
+        assert!(tree.contains(&5)); // check root node
+        assert!(tree.contains(&4));
+        assert!(!tree.contains(&0));
+    }
+
+    #[test]
+    fn iter() {
+        let mut tree = basic_tree();
+        let keys: Vec<usize> = tree.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys, [1, 3, 4, 5, 6, 7]);
+
+        for value in tree.values_mut().rev().take(1) {
+            *value = "replaced";
+        }
+        assert_eq!(tree.get(&7), Some(&"replaced"));
+        let range: Vec<&usize> = tree.range(2..6).map(|(key, _)| key).collect();
+        assert_eq!(range, [&3, &4, &5]);
+
+        let mut iter = tree.into_iter();
+        assert_eq!(iter.len(), 6);
+        assert_eq!(iter.next(), Some((1, "this is the smallest entry")));
+        assert_eq!(iter.next_back(), Some((7, "replaced")));
+    }
+
+    #[test]
+    fn smallest() {
+        let tree = basic_tree();
+        assert_eq!(
+            tree.smallest(),
+            Some(&Entry::new(1, "this is the smallest entry"))
+        );
+    }
+
+    #[test]
+    fn largest() {
+        let tree = basic_tree();
+        assert_eq!(
+            tree.largest(),
+            Some(&Entry::new(7, "this is the largest entry"))
+        );
+    }
+
+    #[test]
+    fn order_statistics() {
+        let mut tree = AvlTreeMap::new();
+        for key in 0..200 {
+            tree.insert((key * 7919) % 200, key);
+        }
+        for key in (0..200).step_by(3) {
+            tree.remove(&key);
+        }
+        let keys: Vec<usize> = tree.keys().copied().collect();
+        for (index, key) in keys.iter().enumerate() {
+            assert_eq!(tree.nth(index).map(|(key, _)| key), Some(key));
+            assert_eq!(tree.select(index), Some(key));
+            assert_eq!(tree.rank(key), index);
+            assert_eq!(tree.rank(&(key + 1)), index + 1);
+        }
+        assert_eq!(tree.nth(keys.len()), None);
+        assert_eq!(tree.rank(&1000), keys.len());
+        if let Some((_, value)) = tree.nth_mut(0) {
+            *value = 1000;
+        }
+        assert_eq!(tree.get(&keys[0]), Some(&1000));
+        assert_eq!(AvlTreeMap::<usize, usize>::new().nth(0), None);
+    }
+
+    #[test]
+    fn split_off_and_append() {
+        let mut tree: AvlTreeMap<usize, usize> =
+            (0..100).map(|key| (key, key)).collect();
+        for at in [50, 0, 100] {
+            let mut right = tree.split_off(&at);
+            assert!(tree.keys().copied().eq(0..at));
+            assert!(right.keys().copied().eq(at..100));
+            assert_eq!(tree.size() + right.size(), 100);
+            check(&tree);
+            check(&right);
+            tree.append(&mut right);
+            assert_eq!(right.size(), 0);
+            assert_eq!(tree.size(), 100);
+        }
+        let mut other: AvlTreeMap<_, _> =
+            (90..110).map(|key| (key, 0)).collect();
+        tree.append(&mut other);
+        assert_eq!(tree.size(), 110);
+        assert_eq!(tree.get(&95), Some(&0));
+        assert_eq!(tree.get(&5), Some(&5));
+        assert!(tree.split_off(&200).iter().next().is_none());
+        assert_eq!(tree.size(), 110);
+        check(&tree);
+    }
+
+    #[test]
+    fn std_traits() {
+        let tree: AvlTreeMap<usize, usize> =
+            (0..100).rev().map(|key| (key, key)).collect();
+        assert_eq!(tree.size(), 100);
+        assert!(tree.keys().copied().eq(0..100));
+        check(&tree);
+        let mut other = tree.clone();
+        assert_eq!(other, tree);
+        other.extend([(100, 100), (0, 1)]);
+        other.extend([(&101, &101)]);
+        assert_eq!(other.size(), 102);
+        assert_eq!(other.get(&0), Some(&1));
+        assert!(tree < other);
+
+        let small = AvlTreeMap::from([(2, "b"), (1, "a"), (2, "c")]);
+        assert_eq!(format!("{small:?}"), r#"{1: "a", 2: "c"}"#);
+        assert_eq!(small, AvlTreeMap::from(vec![(1, "a"), (2, "c")]));
+        assert_ne!(small, AvlTreeMap::from([(1, "a")]));
+    }
+}
@@ -0,0 +1,4 @@
+mod iter;
+pub mod map;
+mod node;
+pub mod set;
@@ -0,0 +1,465 @@
+//! a node in `AvlTreeMap`
+use crate::entry::{Entry, Value};
+use crate::traversal::{Expand, Split, Step};
+use alloc_crate::boxed::Box;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+
+/// `left` represents entries that have smaller `key`s than `self.key`
+/// `right` represents entries that have greater `key`s than `self.key`
+/// `height` is the number of nodes on the longest path down from `self`,
+/// including `self`
+/// `size` is the number of entries in the subtree rooted at `self`,
+/// including `self`
+pub struct Node<K, V>
+where
+    K: Ord,
+    V: Value + ?Sized,
+{
+    entry: Entry<K, V>,
+    height: usize,
+    size: usize,
+    pub left: Option<Box<Node<K, V>>>,
+    pub right: Option<Box<Node<K, V>>>,
+}
+
+impl<K, V> Node<K, V>
+where
+    K: Ord,
+{
+    /// creates a single `Entry` with key `key` and value `value`
+    #[inline]
+    pub fn new(key: K, value: V) -> Node<K, V> {
+        Node {
+            entry: Entry::new(key, value),
+            height: 1,
+            size: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    /// returns an optional reference to the `value` with key `key`
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match key.cmp(self.key().borrow()) {
+            Ordering::Less => self.left.as_ref()?.get(key),
+            Ordering::Greater => self.right.as_ref()?.get(key),
+            Ordering::Equal => Some(&self.entry.value),
+        }
+    }
+
+    /// returns an optional mutable reference to the `value` with key `key`
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match key.cmp(self.key().borrow()) {
+            Ordering::Less => self.left.as_mut()?.get_mut(key),
+            Ordering::Greater => self.right.as_mut()?.get_mut(key),
+            Ordering::Equal => Some(&mut self.entry.value),
+        }
+    }
+
+    /// returns a reference to the key
+    pub fn key(&self) -> &K {
+        self.entry.key()
+    }
+
+    /// returns `true` if the subtree rooted at `self` contains `key`
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// sets the value of the entry with key `key` to `value`
+    /// in the subtree rooted at `link`, then rebalances it
+    /// if `key` already exists, the value is overwritten
+    /// otherwise, a new entry is added
+    /// returns `true` if a *new* entry is added (not overwritten)
+    /// else returns `false`
+    pub fn insert(
+        link: &mut Option<Box<Node<K, V>>>,
+        key: K,
+        value: V,
+    ) -> bool {
+        Node::put(link, key, value, true)
+    }
+
+    /// inserts a new entry with key and value `key`, `value`
+    /// in the subtree rooted at `link`, then rebalances it
+    /// if an entry with `key` already exists, does not overwrite old value
+    /// returns `true` if a new entry is created
+    /// else returns false
+    pub fn try_insert(
+        link: &mut Option<Box<Node<K, V>>>,
+        key: K,
+        value: V,
+    ) -> bool {
+        Node::put(link, key, value, false)
+    }
+
+    fn put(
+        link: &mut Option<Box<Node<K, V>>>,
+        key: K,
+        value: V,
+        overwrite: bool,
+    ) -> bool {
+        let Some(node) = link else {
+            *link = Some(Box::from(Node::new(key, value)));
+            return true;
+        };
+        let inserted = match key.cmp(node.key()) {
+            Ordering::Less => Node::put(&mut node.left, key, value, overwrite),
+            Ordering::Greater => {
+                Node::put(&mut node.right, key, value, overwrite)
+            }
+            Ordering::Equal => {
+                if overwrite {
+                    node.entry.value = value;
+                }
+                false
+            }
+        };
+        if inserted {
+            Node::rebalance(link);
+        }
+        inserted
+    }
+
+    /// removes the entry with key `key` from the subtree rooted at `link`,
+    /// then rebalances it
+    /// returns the removed entry, or `None` if no such entry exists
+    ///
+    /// a node with two children is replaced by its in-order successor
+    pub fn remove<Q>(
+        link: &mut Option<Box<Node<K, V>>>,
+        key: &Q,
+    ) -> Option<Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = link.as_mut()?;
+        let removed = match key.cmp(node.key().borrow()) {
+            Ordering::Less => Node::remove(&mut node.left, key),
+            Ordering::Greater => Node::remove(&mut node.right, key),
+            Ordering::Equal => {
+                let mut node = link.take()?;
+                *link = match (node.left.take(), node.right.take()) {
+                    (None, None) => None,
+                    (Some(child), None) | (None, Some(child)) => Some(child),
+                    (Some(left), Some(right)) => {
+                        let mut right = Some(right);
+                        let mut successor = Node::take_smallest(&mut right)?;
+                        successor.left = Some(left);
+                        successor.right = right;
+                        Some(successor)
+                    }
+                };
+                Some(node.entry)
+            }
+        };
+        if removed.is_some() {
+            Node::rebalance(link);
+        }
+        removed
+    }
+
+    /// detaches the node with the smallest key from the subtree rooted at
+    /// `link`, putting its right child in its place and rebalancing
+    fn take_smallest(
+        link: &mut Option<Box<Node<K, V>>>,
+    ) -> Option<Box<Node<K, V>>> {
+        match link {
+            Some(node) if node.left.is_some() => {
+                let smallest = Node::take_smallest(&mut node.left);
+                Node::rebalance(link);
+                smallest
+            }
+            _ => {
+                let mut node = link.take()?;
+                *link = node.right.take();
+                Some(node)
+            }
+        }
+    }
+
+    /// builds a balanced tree out of the first `len` entries of `entries`,
+    /// which must be sorted by key
+    pub fn from_sorted(
+        len: usize,
+        entries: &mut impl Iterator<Item = (K, V)>,
+    ) -> Option<Box<Node<K, V>>> {
+        if len == 0 {
+            return None;
+        }
+        let left = Node::from_sorted(len / 2, entries);
+        let (key, value) = entries.next()?;
+        let right = Node::from_sorted(len - len / 2 - 1, entries);
+        let mut node = Box::new(Node::new(key, value));
+        node.left = left;
+        node.right = right;
+        node.update();
+        Some(node)
+    }
+
+    /// returns a reference to the smallest entry
+    pub fn smallest(&self) -> &Entry<K, V> {
+        match self.left {
+            Some(ref node) => node.smallest(),
+            None => &self.entry,
+        }
+    }
+
+    /// returns a mutable reference to the smallest entry
+    pub fn smallest_mut(&mut self) -> &mut Entry<K, V> {
+        match self.left {
+            Some(ref mut node) => node.smallest_mut(),
+            None => &mut self.entry,
+        }
+    }
+
+    /// returns a reference to the largest entry
+    pub fn largest(&self) -> &Entry<K, V> {
+        match self.right {
+            Some(ref node) => node.largest(),
+            None => &self.entry,
+        }
+    }
+
+    /// returns a mutable reference to the largest entry
+    pub fn largest_mut(&mut self) -> &mut Entry<K, V> {
+        match self.right {
+            Some(ref mut node) => node.largest_mut(),
+            None => &mut self.entry,
+        }
+    }
+
+    /// returns the number of entries in the subtree rooted at `self`
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// returns the number of entries in the subtree rooted at `link`
+    pub fn size_of(link: &Option<Box<Node<K, V>>>) -> usize {
+        link.as_ref().map_or(0, |node| node.size)
+    }
+
+    /// returns the entry with `index` smaller entries
+    /// in the subtree rooted at `self`
+    pub fn nth(&self, index: usize) -> Option<&Entry<K, V>> {
+        let left = Node::size_of(&self.left);
+        match index.cmp(&left) {
+            Ordering::Less => self.left.as_ref()?.nth(index),
+            Ordering::Equal => Some(&self.entry),
+            Ordering::Greater => self.right.as_ref()?.nth(index - left - 1),
+        }
+    }
+
+    /// returns a mutable reference to the entry with `index` smaller entries
+    /// in the subtree rooted at `self`
+    pub fn nth_mut(&mut self, index: usize) -> Option<&mut Entry<K, V>> {
+        let left = Node::size_of(&self.left);
+        match index.cmp(&left) {
+            Ordering::Less => self.left.as_mut()?.nth_mut(index),
+            Ordering::Equal => Some(&mut self.entry),
+            Ordering::Greater => self.right.as_mut()?.nth_mut(index - left - 1),
+        }
+    }
+
+    /// returns the number of entries in the subtree rooted at `self`
+    /// with keys smaller than `key`
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let left = Node::size_of(&self.left);
+        match key.cmp(self.key().borrow()) {
+            Ordering::Less => {
+                self.left.as_ref().map_or(0, |node| node.rank(key))
+            }
+            Ordering::Equal => left,
+            Ordering::Greater => {
+                left + 1 + self.right.as_ref().map_or(0, |node| node.rank(key))
+            }
+        }
+    }
+
+    /// returns the height of the subtree rooted at `link`
+    pub fn height(link: &Option<Box<Node<K, V>>>) -> usize {
+        link.as_ref().map_or(0, |node| node.height)
+    }
+
+    /// recalculates `self.height` and `self.size` from its children
+    fn update(&mut self) {
+        self.height =
+            1 + Node::height(&self.left).max(Node::height(&self.right));
+        self.size = 1 + Node::size_of(&self.left) + Node::size_of(&self.right);
+    }
+
+    /// returns how much taller the left subtree is than the right subtree
+    fn balance_factor(&self) -> isize {
+        Node::height(&self.left) as isize - Node::height(&self.right) as isize
+    }
+
+    /// restores the AVL property at the root of `link`,
+    /// assuming both of its subtrees are balanced
+    fn rebalance(link: &mut Option<Box<Node<K, V>>>) {
+        let Some(mut node) = link.take() else {
+            return;
+        };
+        node.update();
+        let balance_factor = node.balance_factor();
+        if balance_factor > 1 {
+            if let Some(left) = node.left.take() {
+                node.left = Some(match left.balance_factor() < 0 {
+                    true => Node::rotate_left(left),
+                    false => left,
+                });
+            }
+            node = Node::rotate_right(node);
+        } else if balance_factor < -1 {
+            if let Some(right) = node.right.take() {
+                node.right = Some(match right.balance_factor() > 0 {
+                    true => Node::rotate_right(right),
+                    false => right,
+                });
+            }
+            node = Node::rotate_left(node);
+        }
+        *link = Some(node);
+    }
+
+    /// makes the left child of `node` the root of its subtree
+    fn rotate_right(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let Some(mut left) = node.left.take() else {
+            return node;
+        };
+        node.left = left.right.take();
+        node.update();
+        left.right = Some(node);
+        left.update();
+        left
+    }
+
+    /// makes the right child of `node` the root of its subtree
+    fn rotate_left(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let Some(mut right) = node.right.take() else {
+            return node;
+        };
+        node.right = right.left.take();
+        node.update();
+        right.left = Some(node);
+        right.update();
+        right
+    }
+}
+
+impl<'a, K, V> Expand for &'a Node<K, V>
+where
+    K: Ord,
+{
+    type Entry = (&'a K, &'a V);
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let (left, entry, right) = self.split();
+        [
+            left.map(Step::Node),
+            Some(Step::Entry(entry)),
+            right.map(Step::Node),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl<K, V> Split for &Node<K, V>
+where
+    K: Ord,
+{
+    type Key = K;
+
+    fn split(self) -> (Option<Self>, Self::Entry, Option<Self>) {
+        (
+            self.left.as_deref(),
+            self.entry.split(),
+            self.right.as_deref(),
+        )
+    }
+
+    fn key(entry: &Self::Entry) -> &K {
+        entry.0
+    }
+}
+
+impl<'a, K, V> Expand for &'a mut Node<K, V>
+where
+    K: Ord,
+{
+    type Entry = (&'a K, &'a mut V);
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let (left, entry, right) = self.split();
+        [
+            left.map(Step::Node),
+            Some(Step::Entry(entry)),
+            right.map(Step::Node),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl<K, V> Split for &mut Node<K, V>
+where
+    K: Ord,
+{
+    type Key = K;
+
+    fn split(self) -> (Option<Self>, Self::Entry, Option<Self>) {
+        (
+            self.left.as_deref_mut(),
+            self.entry.split_mut(),
+            self.right.as_deref_mut(),
+        )
+    }
+
+    fn key(entry: &Self::Entry) -> &K {
+        entry.0
+    }
+}
+
+impl<K, V> Expand for Box<Node<K, V>>
+where
+    K: Ord,
+{
+    type Entry = (K, V);
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let Node {
+            entry, left, right, ..
+        } = *self;
+        [
+            left.map(Step::Node),
+            Some(Step::Entry(entry.into_pair())),
+            right.map(Step::Node),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
@@ -0,0 +1,207 @@
+//! a self-balancing binary search tree with keys but no associated value
+//! a degenerate form of `AvlTreeMap`
+
+use super::map::{self, AvlTreeMap};
+use crate::entry::Entry;
+use crate::iter::Keys;
+use alloc_crate::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::ops::RangeBounds;
+
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AvlTreeSet<T>
+where
+    T: Ord,
+{
+    map: AvlTreeMap<T, ()>,
+}
+
+impl<K> AvlTreeSet<K>
+where
+    K: Ord,
+{
+    /// creates an empty `AvlTreeSet`
+    pub const fn new() -> AvlTreeSet<K> {
+        AvlTreeSet {
+            map: AvlTreeMap::new(),
+        }
+    }
+
+    /// inserts a new entry with key `key`
+    /// if such an entry already exists, nothing happens
+    pub fn insert(&mut self, key: K) {
+        self.map.try_insert(key, ());
+    }
+
+    /// removes the entry with key `key`
+    /// returns `true` if such an entry existed
+    pub fn remove<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.remove(key).is_some()
+    }
+
+    /// returns the number of entries in `self`
+    pub fn size(&self) -> usize {
+        self.map.size()
+    }
+
+    /// returns true if `self` contains an entry `key`
+    /// otherwise returns false
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.contains(key)
+    }
+
+    pub fn smallest(&self) -> Option<&Entry<K, ()>> {
+        self.map.smallest()
+    }
+
+    pub fn smallest_mut(&mut self) -> Option<&mut Entry<K, ()>> {
+        self.map.smallest_mut()
+    }
+
+    pub fn largest(&self) -> Option<&Entry<K, ()>> {
+        self.map.largest()
+    }
+
+    pub fn largest_mut(&mut self) -> Option<&mut Entry<K, ()>> {
+        self.map.largest_mut()
+    }
+
+    /// returns the key with `index` smaller keys,
+    /// or `None` if `index` is out of bounds
+    pub fn nth(&self, index: usize) -> Option<&K> {
+        self.map.select(index)
+    }
+
+    /// returns the number of keys smaller than `key`
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.rank(key)
+    }
+
+    /// returns an iterator over the keys of `self`, in order
+    pub fn iter(&self) -> Iter<'_, K> {
+        Keys::new(self.map.iter())
+    }
+
+    /// returns an iterator over the keys of `self` that lie within `range`,
+    /// in order
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Keys::new(self.map.range(range))
+    }
+}
+
+impl<T> IntoIterator for AvlTreeSet<T>
+where
+    T: Ord,
+{
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Keys::new(self.map.into_iter())
+    }
+}
+
+impl<'a, T> IntoIterator for &'a AvlTreeSet<T>
+where
+    T: Ord,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Default for AvlTreeSet<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for AvlTreeSet<T>
+where
+    T: Ord + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self).finish()
+    }
+}
+
+impl<T> FromIterator<T> for AvlTreeSet<T>
+where
+    T: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        AvlTreeSet {
+            map: iter.into_iter().map(|key| (key, ())).collect(),
+        }
+    }
+}
+
+impl<T> Extend<T> for AvlTreeSet<T>
+where
+    T: Ord,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|key| (key, ())));
+    }
+}
+
+impl<'a, T> Extend<&'a T> for AvlTreeSet<T>
+where
+    T: Ord + Copy,
+{
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for AvlTreeSet<T>
+where
+    T: Ord,
+{
+    fn from(array: [T; N]) -> Self {
+        Self::from_iter(array)
+    }
+}
+
+impl<T> From<Vec<T>> for AvlTreeSet<T>
+where
+    T: Ord,
+{
+    fn from(vec: Vec<T>) -> Self {
+        Self::from_iter(vec)
+    }
+}
+
+/// an iterator over the keys of an `AvlTreeSet`, in order
+pub type Iter<'a, T> = Keys<map::Iter<'a, T, ()>>;
+
+/// an owning iterator over the keys of an `AvlTreeSet`, in order
+pub type IntoIter<T> = Keys<map::IntoIter<T, ()>>;
+
+/// an iterator over the keys of an `AvlTreeSet` that lie within a range,
+/// in order
+pub type Range<'a, T> = Keys<map::Range<'a, T, ()>>;
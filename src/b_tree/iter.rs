@@ -0,0 +1,151 @@
+//! in-order iterators over `BTreeMap`
+use super::map::{BTreeMap, DEFAULT_B};
+use super::node::{Node, Slots};
+use crate::alloc::{Allocator, Global};
+use crate::boxed::AllocBox;
+use crate::entry::Entry;
+use crate::iter;
+use crate::traversal::Traversal;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+/// an iterator over the entries of a `BTreeMap`, in order of their keys
+pub type Iter<'a, K, V, const B: usize = DEFAULT_B, A = Global> =
+    iter::Iter<&'a Node<K, V, B, A>, &'a A>;
+
+/// a mutable iterator over the entries of a `BTreeMap`,
+/// in order of their keys
+pub type IterMut<'a, K, V, const B: usize = DEFAULT_B, A = Global> =
+    iter::Iter<&'a mut Node<K, V, B, A>, &'a A>;
+
+/// an owning iterator over the entries of a `BTreeMap`,
+/// in order of their keys
+pub type IntoIter<K, V, const B: usize = DEFAULT_B, A = Global> =
+    iter::IntoIter<AllocBox<Node<K, V, B, A>, A>, A>;
+
+/// an iterator over the keys of a `BTreeMap`, in order
+pub type Keys<'a, K, V, const B: usize = DEFAULT_B, A = Global> =
+    iter::Keys<Iter<'a, K, V, B, A>>;
+
+/// an iterator over the values of a `BTreeMap`, in order of their keys
+pub type Values<'a, K, V, const B: usize = DEFAULT_B, A = Global> =
+    iter::Values<Iter<'a, K, V, B, A>>;
+
+/// a mutable iterator over the values of a `BTreeMap`,
+/// in order of their keys
+pub type ValuesMut<'a, K, V, const B: usize = DEFAULT_B, A = Global> =
+    iter::Values<IterMut<'a, K, V, B, A>>;
+
+/// an iterator that removes the entries of a `BTreeMap` matching a
+/// predicate as it goes, and returns them, in order of their keys
+/// entries it has not reached when dropped stay in the map
+pub struct ExtractIf<'a, K, V, F, const B: usize = DEFAULT_B, A = Global>
+where
+    K: Ord,
+    A: Allocator + Clone,
+{
+    extraction: Extraction<'a, K, V, B, A>,
+    pred: F,
+}
+
+impl<'a, K, V, F, const B: usize, A> ExtractIf<'a, K, V, F, B, A>
+where
+    K: Ord,
+    A: Allocator + Clone,
+{
+    pub(super) fn new(
+        map: &'a mut BTreeMap<K, V, B, A>,
+        pred: F,
+    ) -> ExtractIf<'a, K, V, F, B, A> {
+        ExtractIf {
+            extraction: Extraction::new(map),
+            pred,
+        }
+    }
+}
+
+impl<K, V, F, const B: usize, A> Iterator for ExtractIf<'_, K, V, F, B, A>
+where
+    K: Ord,
+    F: FnMut(&K, &mut V) -> bool,
+    A: Allocator + Clone,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.extraction.next(&mut self.pred)
+    }
+}
+
+/// the walk behind `extract_if` on a `BTreeMap` or a `BTreeSet`
+/// extracted entries are taken out of their slots, and once this is
+/// dropped, the tree is rebuilt around the gaps if there are any
+///
+/// the walk borrows the nodes for `'a`, but the map is needed again when
+/// this is dropped, so the map itself is only held as a pointer
+pub struct Extraction<'a, K, V, const B: usize, A>
+where
+    K: Ord,
+    A: Allocator + Clone,
+{
+    map: NonNull<BTreeMap<K, V, B, A>>,
+    slots: Traversal<Slots<'a, K, V, B, A>, A>,
+    removed: usize,
+    marker: PhantomData<&'a mut BTreeMap<K, V, B, A>>,
+}
+
+impl<'a, K, V, const B: usize, A> Extraction<'a, K, V, B, A>
+where
+    K: Ord,
+    A: Allocator + Clone,
+{
+    pub fn new(
+        map: &'a mut BTreeMap<K, V, B, A>,
+    ) -> Extraction<'a, K, V, B, A> {
+        let alloc = map.allocator().clone();
+        let mut map = NonNull::from(map);
+        // SAFETY: the map is borrowed mutably for `'a`, and only used again
+        // once `slots`, which borrows its nodes, is gone
+        let root = unsafe { map.as_mut() }.root_mut();
+        Extraction {
+            map,
+            slots: Traversal::new_in(root.map(Slots::new), alloc),
+            removed: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// takes out the next entry for which `pred` returns `true`,
+    /// and returns it
+    /// `pred` only ever sees entries that are still in the map
+    pub fn next<F>(&mut self, mut pred: F) -> Option<(K, V)>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        for slot in &mut self.slots {
+            let Some(entry) = slot else {
+                continue;
+            };
+            let (key, value) = entry.split_mut();
+            if pred(key, value) {
+                self.removed += 1;
+                return slot.take().map(Entry::into_pair);
+            }
+        }
+        None
+    }
+}
+
+impl<K, V, const B: usize, A> Drop for Extraction<'_, K, V, B, A>
+where
+    K: Ord,
+    A: Allocator + Clone,
+{
+    fn drop(&mut self) {
+        self.slots.clear();
+        // SAFETY: the borrows of the nodes ended along with the steps of
+        // `slots`
+        let map = unsafe { self.map.as_mut() };
+        map.close_gaps(self.removed);
+    }
+}
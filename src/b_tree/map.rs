@@ -1,8 +1,915 @@
-use super::node::Node;
-pub struct BTreeMap<K, V>
+//! a B-tree with keys and values
+//! every node holds several sorted entries, and all leaves are at the same
+//! depth, which keeps the tree shallow and its entries close together
+//!
+//! the branching factor `B` is the maximum number of children of a node,
+//! so each node holds up to `B - 1` entries
+//! larger nodes mean fewer allocations and a shallower tree,
+//! at the cost of more comparisons and moves within each node
+
+use super::node::{Insertion, Node};
+use crate::alloc::{Allocator, Global};
+use crate::boxed::AllocBox;
+use crate::entry::Entry;
+use crate::merge::{count_common, KeepRight, MergeSorted};
+use alloc_crate::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::mem;
+
+pub use super::iter::{
+    ExtractIf, IntoIter, Iter, IterMut, Keys, Values, ValuesMut,
+};
+
+/// the branching factor of a `BTreeMap` when none is given
+pub const DEFAULT_B: usize = 7;
+
+/// every node is allocated from `alloc`
+pub struct BTreeMap<K, V, const B: usize = DEFAULT_B, A = Global>
 where
     K: Ord,
+    A: Allocator,
 {
     size: usize,
-    root: Option<Box<Node<K, V>>>,
+    root: Option<AllocBox<Node<K, V, B, A>, A>>,
+    alloc: A,
+}
+
+impl<K, V> BTreeMap<K, V>
+where
+    K: Ord,
+{
+    /// creates an empty `BTreeMap` with the default branching factor
+    #[inline]
+    pub const fn new() -> BTreeMap<K, V> {
+        BTreeMap::with_branching_factor()
+    }
+}
+
+impl<K, V, A> BTreeMap<K, V, DEFAULT_B, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    /// creates an empty `BTreeMap` with the default branching factor,
+    /// whose nodes come from `alloc`
+    #[inline]
+    pub const fn new_in(alloc: A) -> BTreeMap<K, V, DEFAULT_B, A> {
+        BTreeMap::with_branching_factor_in(alloc)
+    }
+}
+
+impl<K, V, const B: usize> BTreeMap<K, V, B>
+where
+    K: Ord,
+{
+    /// creates an empty `BTreeMap` whose nodes have up to `B` children
+    /// `B` must be at least 3, smaller values are rejected at compile time
+    ///
+    /// ```compile_fail
+    /// use terees::b_tree::map::BTreeMap;
+    /// let tree = BTreeMap::<u32, u32, 2>::with_branching_factor();
+    /// ```
+    #[inline]
+    pub const fn with_branching_factor() -> BTreeMap<K, V, B> {
+        BTreeMap::with_branching_factor_in(Global)
+    }
+}
+
+impl<K, V, const B: usize, A> BTreeMap<K, V, B, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    /// creates an empty `BTreeMap` whose nodes have up to `B` children,
+    /// and come from `alloc`
+    #[inline]
+    pub const fn with_branching_factor_in(alloc: A) -> BTreeMap<K, V, B, A> {
+        const { assert!(B >= 3, "a B-tree needs a branching factor of at least 3") };
+        BTreeMap {
+            size: 0,
+            root: None,
+            alloc,
+        }
+    }
+
+    /// returns a reference to the allocator of `self`
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// returns the number of entries in `self`
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// returns `true` if `self` contains an entry with key `key`
+    /// otherwise returns `false`
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_entry(key).is_some()
+    }
+
+    /// sets the value of the key with key `key` to `value`
+    /// if `key` already exists, the value is overridden
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V)
+    where
+        A: Clone,
+    {
+        self.put(key, value, true);
+    }
+
+    /// inserts a new entry with key `key` and value `value`
+    /// if `key` already exists, the old value is kept
+    #[inline]
+    pub fn try_insert(&mut self, key: K, value: V)
+    where
+        A: Clone,
+    {
+        self.put(key, value, false);
+    }
+
+    fn put(&mut self, key: K, value: V, overwrite: bool)
+    where
+        A: Clone,
+    {
+        let alloc = &self.alloc;
+        let root = self.root.get_or_insert_with(|| {
+            AllocBox::new_in(Node::new(), alloc.clone())
+        });
+        match root.insert(key, value, overwrite, alloc) {
+            Insertion::Existing => return,
+            Insertion::Added => {}
+            Insertion::Split(entry, right) => {
+                if let Some(left) = self.root.take() {
+                    let root = Node::with_children(left, entry, right);
+                    self.root = Some(AllocBox::new_in(root, alloc.clone()));
+                }
+            }
+        }
+        self.size += 1;
+    }
+
+    /// moves every entry with a key of at least `key` into a new map
+    /// and returns it
+    ///
+    /// both halves are built from scratch out of the sorted entries,
+    /// which takes linear time
+    pub fn split_off<Q>(&mut self, key: &Q) -> BTreeMap<K, V, B, A>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        A: Clone,
+    {
+        let below = self.keys().take_while(|left| (*left).borrow() < key);
+        let len = below.count();
+        let size = self.size;
+        let mut entries = self.take().into_iter();
+        *self = self.build(len, &mut entries);
+        self.build(size - len, &mut entries)
+    }
+
+    /// moves all entries of `other` into `self`, leaving `other` empty
+    /// if both contain an entry with the same key, the value from `other`
+    /// is kept
+    ///
+    /// `self` is built from scratch out of the merged entries,
+    /// which takes linear time
+    pub fn append(&mut self, other: &mut BTreeMap<K, V, B, A>)
+    where
+        A: Clone,
+    {
+        let common = count_common(self.keys(), other.keys());
+        let len = self.size + other.size - common;
+        let left = self.take().into_iter();
+        let right = other.take().into_iter();
+        let mut merged = MergeSorted::new(left, right, KeepRight);
+        *self = self.build(len, &mut merged);
+    }
+
+    /// removes all entries from `self`
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.size = 0;
+    }
+
+    /// keeps only the entries for which `f` returns `true`
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+        A: Clone,
+    {
+        self.extract_if(|key, value| !f(key, value)).for_each(drop);
+    }
+
+    /// returns an iterator that removes the entries for which `pred`
+    /// returns `true`, in order of their keys, and yields them
+    /// if the iterator is dropped early, the remaining entries are kept,
+    /// and if `pred` panics, every entry not yet yielded is kept
+    ///
+    /// the entries are taken out of their nodes in place
+    /// if any are, the tree is rebuilt in linear time once the iterator
+    /// is dropped, otherwise it is left as it was
+    /// if the iterator is leaked instead, the map is left in an
+    /// unspecified state
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, F, B, A>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+        A: Clone,
+    {
+        ExtractIf::new(self, pred)
+    }
+
+    /// returns a mutable reference to the root of `self`
+    pub(super) fn root_mut(&mut self) -> Option<&mut Node<K, V, B, A>> {
+        self.root.as_deref_mut()
+    }
+
+    /// closes the gaps left in the nodes by taking `removed` entries out,
+    /// by rebuilding the tree out of the remaining entries
+    pub(super) fn close_gaps(&mut self, removed: usize)
+    where
+        A: Clone,
+    {
+        if removed == 0 {
+            return;
+        }
+        self.size -= removed;
+        let len = self.size;
+        let mut entries = self.take().into_iter();
+        *self = self.build(len, &mut entries);
+    }
+
+    /// returns an empty map with the same allocator as `self`
+    fn empty(&self) -> BTreeMap<K, V, B, A>
+    where
+        A: Clone,
+    {
+        BTreeMap::with_branching_factor_in(self.alloc.clone())
+    }
+
+    /// builds a map with the same allocator as `self` out of the first `len`
+    /// entries of `entries`, which must be sorted by key
+    fn build(
+        &self,
+        len: usize,
+        entries: &mut impl Iterator<Item = (K, V)>,
+    ) -> BTreeMap<K, V, B, A>
+    where
+        A: Clone,
+    {
+        BTreeMap {
+            size: len,
+            root: Node::from_sorted(len, entries, &self.alloc),
+            alloc: self.alloc.clone(),
+        }
+    }
+
+    /// replaces `self` with an empty map with the same allocator,
+    /// and returns the old one
+    fn take(&mut self) -> BTreeMap<K, V, B, A>
+    where
+        A: Clone,
+    {
+        mem::replace(self, self.empty())
+    }
+
+    /// removes the entry with key `key` and returns it
+    /// returns `None` if no such entry exists
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let root = self.root.as_mut()?;
+        let entry = root.remove(key)?;
+        if root.len() == 0 {
+            self.root = root.take_first_child();
+        }
+        self.size -= 1;
+        Some(entry)
+    }
+
+    /// returns an optional reference to the `value` with key `key`
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_entry(key).map(|entry| &entry.value)
+    }
+
+    /// returns an optional mutable reference to the `value` with key `key`
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.root
+            .as_mut()?
+            .entry_mut(key)
+            .map(|entry| &mut entry.value)
+    }
+
+    /// returns an optional reference to the `Entry` with key `key`
+    pub fn get_entry<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.root.as_ref()?.entry(key)
+    }
+
+    /// returns an optional reference to the smallest entry
+    pub fn smallest(&self) -> Option<&Entry<K, V>> {
+        self.root.as_ref()?.smallest()
+    }
+
+    /// returns an optional reference to the largest entry
+    pub fn largest(&self) -> Option<&Entry<K, V>> {
+        self.root.as_ref()?.largest()
+    }
+
+    /// returns an iterator over the entries of `self`, in order of their keys
+    pub fn iter(&self) -> Iter<'_, K, V, B, A> {
+        Iter::new_in(self.root.as_deref(), self.size, &self.alloc)
+    }
+
+    /// returns an iterator over the entries of `self`, in order of their keys,
+    /// with mutable references to the values
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V, B, A> {
+        IterMut::new_in(self.root.as_deref_mut(), self.size, &self.alloc)
+    }
+
+    /// returns an iterator over the keys of `self`, in order
+    pub fn keys(&self) -> Keys<'_, K, V, B, A> {
+        Keys::new(self.iter())
+    }
+
+    /// returns an iterator over the values of `self`, in order of their keys
+    pub fn values(&self) -> Values<'_, K, V, B, A> {
+        Values::new(self.iter())
+    }
+
+    /// returns an iterator over mutable references to the values of `self`,
+    /// in order of their keys
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V, B, A> {
+        ValuesMut::new(self.iter_mut())
+    }
+}
+
+impl<K, V, const B: usize, A> IntoIterator for BTreeMap<K, V, B, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, B, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new_in(self.root, self.size, self.alloc)
+    }
+}
+
+impl<'a, K, V, const B: usize, A> IntoIterator for &'a BTreeMap<K, V, B, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, B, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, const B: usize, A> IntoIterator for &'a mut BTreeMap<K, V, B, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V, B, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, const B: usize> Default for BTreeMap<K, V, B>
+where
+    K: Ord,
+{
+    fn default() -> Self {
+        Self::with_branching_factor()
+    }
+}
+
+impl<K, V, const B: usize> FromIterator<(K, V)> for BTreeMap<K, V, B>
+where
+    K: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = BTreeMap::with_branching_factor();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, const B: usize, A> Extend<(K, V)> for BTreeMap<K, V, B, A>
+where
+    K: Ord,
+    A: Allocator + Clone,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V, const B: usize, A> Extend<(&'a K, &'a V)>
+    for BTreeMap<K, V, B, A>
+where
+    K: Ord + Copy,
+    V: Copy,
+    A: Allocator + Clone,
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(&key, &value)| (key, value)));
+    }
+}
+
+// like `new`, these only exist for the default branching factor, since
+// it could not be inferred otherwise
+impl<K, V, const N: usize> From<[(K, V); N]> for BTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from(array: [(K, V); N]) -> Self {
+        Self::from_iter(array)
+    }
+}
+
+impl<K, V> From<Vec<(K, V)>> for BTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from(vec: Vec<(K, V)>) -> Self {
+        Self::from_iter(vec)
+    }
+}
+
+impl<K, V, const B: usize, A> Clone for BTreeMap<K, V, B, A>
+where
+    K: Ord + Clone,
+    V: Clone,
+    A: Allocator + Clone,
+{
+    fn clone(&self) -> Self {
+        let mut map = self.empty();
+        map.extend(
+            self.iter().map(|(key, value)| (key.clone(), value.clone())),
+        );
+        map
+    }
+}
+
+impl<K, V, const B: usize, A> fmt::Debug for BTreeMap<K, V, B, A>
+where
+    K: Ord + fmt::Debug,
+    V: fmt::Debug,
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K, V, const B: usize, A> PartialEq for BTreeMap<K, V, B, A>
+where
+    K: Ord,
+    V: PartialEq,
+    A: Allocator,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.size() == other.size() && self.iter().eq(other)
+    }
+}
+
+impl<K, V, const B: usize, A> Eq for BTreeMap<K, V, B, A>
+where
+    K: Ord,
+    V: Eq,
+    A: Allocator,
+{
+}
+
+impl<K, V, const B: usize, A> Hash for BTreeMap<K, V, B, A>
+where
+    K: Ord + Hash,
+    V: Hash,
+    A: Allocator,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.size());
+        for entry in self {
+            entry.hash(state);
+        }
+    }
+}
+
+impl<K, V, const B: usize, A> PartialOrd for BTreeMap<K, V, B, A>
+where
+    K: Ord,
+    V: PartialOrd,
+    A: Allocator,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<K, V, const B: usize, A> Ord for BTreeMap<K, V, B, A>
+where
+    K: Ord,
+    V: Ord,
+    A: Allocator,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alloc::Counting;
+    use alloc_crate::string::ToString;
+    use alloc_crate::{format, vec};
+
+    // set up a tree to apply tests to
+    fn basic_tree() -> BTreeMap<usize, &'static str> {
+        let mut tree = BTreeMap::new();
+        tree.insert(5, " , ");
+        tree.insert(3, "hello");
+        tree.insert(6, "world");
+        tree.insert(7, "this is the largest entry");
+        tree.insert(1, "this is the smallest entry");
+        tree.insert(4, "hmmm");
+
+        tree
+    }
+
+    // a tree large enough to be several levels deep
+    fn big_tree() -> BTreeMap<usize, usize> {
+        let mut tree = BTreeMap::new();
+        for key in 0..1000 {
+            tree.insert((key * 7919) % 1000, key);
+        }
+        tree
+    }
+
+    fn check<V, const B: usize, A>(tree: &BTreeMap<usize, V, B, A>)
+    where
+        A: Allocator,
+    {
+        if let Some(ref root) = tree.root {
+            root.check(true);
+        }
+        assert_eq!(tree.iter().count(), tree.size());
+    }
+
+    #[test]
+    fn insert() {
+        let mut tree = BTreeMap::new();
+        tree.insert(5, " , ");
+        tree.insert(3, "hello");
+        tree.insert(7, "haha get replaced");
+        tree.insert(6, "world");
+        tree.insert(7, "hii");
+
+        assert_eq!(tree.size(), 4);
+        assert_eq!(tree.get(&7), Some(&"hii"));
+        assert_eq!(tree.get(&8), None);
+    }
+
+    #[test]
+    fn insert_splits() {
+        let mut tree = BTreeMap::new();
+        for key in 0..500 {
+            tree.insert(key, key);
+            check(&tree);
+        }
+        for key in (500..1000).rev() {
+            tree.insert(key, key);
+            check(&tree);
+        }
+        let tree = big_tree();
+        check(&tree);
+        assert_eq!(tree.size(), 1000);
+        assert!((0..1000).all(|key| tree.contains(&key)));
+        assert!(tree.keys().copied().eq(0..1000));
+    }
+
+    // inserts and removes keys in a tree with branching factor `B`
+    fn branching_factor<const B: usize>() {
+        let mut tree = BTreeMap::<usize, usize, B>::with_branching_factor();
+        for key in 0..500 {
+            tree.insert((key * 7919) % 500, key);
+            check(&tree);
+        }
+        assert!(tree.keys().copied().eq(0..500));
+        for key in (0..500).step_by(2) {
+            assert!(tree.remove(&key).is_some());
+            check(&tree);
+        }
+        assert!(tree.keys().copied().eq((1..500).step_by(2)));
+    }
+
+    #[test]
+    fn branching_factors() {
+        branching_factor::<3>();
+        branching_factor::<4>();
+        branching_factor::<5>();
+        branching_factor::<16>();
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut tree = basic_tree();
+        tree.try_insert(7, "already exists!");
+        assert_eq!(tree.size(), 6);
+        assert_eq!(tree.get(&7), Some(&"this is the largest entry"));
+        tree.try_insert(9, "does not exist yet");
+        assert_eq!(tree.size(), 7);
+    }
+
+    #[test]
+    fn remove() {
+        let mut tree = basic_tree();
+        assert_eq!(tree.remove(&5), Some(Entry::new(5, " , ")));
+        assert!(!tree.contains(&5));
+        assert_eq!(tree.remove(&5), None);
+        assert_eq!(tree.size(), 5);
+
+        let mut tree = big_tree();
+        for key in (0..1000).step_by(3) {
+            assert_eq!(tree.remove(&key).map(|entry| *entry.key()), Some(key));
+            check(&tree);
+        }
+        assert!((0..1000).all(|key| tree.contains(&key) == (key % 3 != 0)));
+        for key in (0..1000).rev() {
+            tree.remove(&key);
+            check(&tree);
+        }
+        assert_eq!(tree.size(), 0);
+        assert!(tree.root.is_none());
+    }
+
+    #[test]
+    fn borrowed_lookups() {
+        let mut tree = BTreeMap::new();
+        for key in 0..100 {
+            tree.insert(key.to_string(), key);
+        }
+        assert!(tree.contains("42"));
+        assert_eq!(tree.get("7"), Some(&7));
+        if let Some(value) = tree.get_mut("99") {
+            *value = 0;
+        }
+        assert_eq!(tree.get("99"), Some(&0));
+        assert_eq!(tree.remove("50").map(|entry| entry.value), Some(50));
+        assert!(!tree.contains("50"));
+        assert_eq!(tree.size(), 99);
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut tree = big_tree();
+        if let Some(value) = tree.get_mut(&500) {
+            *value = 0;
+        }
+        assert_eq!(tree.get(&500), Some(&0));
+        assert_eq!(tree.get_mut(&1000), None);
+    }
+
+    #[test]
+    fn iter() {
+        let mut tree = big_tree();
+        let mut iter = tree.iter();
+        assert_eq!(iter.len(), 1000);
+        assert_eq!(iter.next().map(|(key, _)| *key), Some(0));
+        assert_eq!(iter.next_back().map(|(key, _)| *key), Some(999));
+        assert!(iter.rev().map(|(key, _)| *key).eq((1..999).rev()));
+
+        for (key, value) in &mut tree {
+            *value = *key * 2;
+        }
+        assert!(tree.values().copied().eq((0..1000).map(|key| key * 2)));
+        let mut iter = tree.into_iter();
+        assert_eq!(iter.next_back(), Some((999, 1998)));
+        assert_eq!(iter.len(), 999);
+    }
+
+    #[test]
+    fn smallest() {
+        let tree = basic_tree();
+        assert_eq!(
+            tree.smallest(),
+            Some(&Entry::new(1, "this is the smallest entry"))
+        );
+    }
+
+    #[test]
+    fn largest() {
+        let tree = basic_tree();
+        assert_eq!(
+            tree.largest(),
+            Some(&Entry::new(7, "this is the largest entry"))
+        );
+    }
+
+    #[test]
+    fn split_off_and_append() {
+        let mut tree: BTreeMap<usize, usize> =
+            (0..100).map(|key| (key, key)).collect();
+        for at in [50, 0, 100] {
+            let mut right = tree.split_off(&at);
+            assert!(tree.keys().copied().eq(0..at));
+            assert!(right.keys().copied().eq(at..100));
+            assert_eq!(tree.size() + right.size(), 100);
+            check(&tree);
+            check(&right);
+            tree.append(&mut right);
+            assert_eq!(right.size(), 0);
+            assert_eq!(tree.size(), 100);
+        }
+        let mut other: BTreeMap<_, _> = (90..110).map(|key| (key, 0)).collect();
+        tree.append(&mut other);
+        assert_eq!(tree.size(), 110);
+        assert_eq!(tree.get(&95), Some(&0));
+        assert_eq!(tree.get(&5), Some(&5));
+        assert!(tree.split_off(&200).iter().next().is_none());
+        assert_eq!(tree.size(), 110);
+        check(&tree);
+    }
+
+    // splits and rejoins trees of every size up to several levels deep,
+    // which rebuilds them in every possible shape
+    fn split_off_and_append_with<const B: usize>() {
+        for len in 0..300 {
+            let mut tree = BTreeMap::<usize, usize, B>::with_branching_factor();
+            tree.extend((0..len).map(|key| (key, key)));
+            let mut right = tree.split_off(&(len / 3));
+            check(&tree);
+            check(&right);
+            assert!(tree.keys().copied().eq(0..len / 3));
+            assert!(right.keys().copied().eq(len / 3..len));
+            right.append(&mut tree);
+            check(&right);
+            assert!(right.keys().copied().eq(0..len));
+        }
+    }
+
+    #[test]
+    fn split_off_and_append_branching_factors() {
+        split_off_and_append_with::<3>();
+        split_off_and_append_with::<4>();
+        split_off_and_append_with::<5>();
+        split_off_and_append_with::<8>();
+    }
+
+    #[test]
+    fn retain_extract_and_clear() {
+        let mut tree: BTreeMap<usize, usize, 3> =
+            (0..100).map(|key| (key, key)).collect();
+        tree.retain(|key, value| {
+            *value += 1;
+            key % 3 != 0
+        });
+        assert_eq!(tree.size(), 66);
+        assert_eq!(tree.get(&1), Some(&2));
+        assert!(!tree.contains(&99));
+        check(&tree);
+
+        let extracted: Vec<_> = tree.extract_if(|key, _| *key < 10).collect();
+        assert_eq!(extracted, [(1, 2), (2, 3), (4, 5), (5, 6), (7, 8), (8, 9)]);
+        assert_eq!(tree.size(), 60);
+        assert_eq!(tree.smallest().map(|entry| *entry.key()), Some(10));
+        check(&tree);
+
+        // entries are only removed as the iterator reaches them
+        drop(tree.extract_if(|key, _| key % 2 == 0));
+        assert_eq!(tree.size(), 60);
+        let mut evens = tree.extract_if(|key, _| key % 2 == 0);
+        assert_eq!(evens.next(), Some((10, 11)));
+        assert_eq!(evens.next(), Some((14, 15)));
+        drop(evens);
+        assert_eq!(tree.size(), 58);
+        check(&tree);
+        assert_eq!(tree.extract_if(|key, _| key % 2 == 0).count(), 28);
+        assert!(tree.keys().all(|key| key % 2 == 1));
+        assert_eq!(tree.size(), 30);
+        check(&tree);
+
+        tree.clear();
+        assert_eq!(tree.size(), 0);
+        assert!(tree.iter().next().is_none());
+        tree.insert(1, 1);
+        assert_eq!(tree.get(&1), Some(&1));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn extract_if_survives_a_panicking_predicate() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut tree: BTreeMap<usize, usize, 3> =
+            (0..100).map(|key| (key, key)).collect();
+        let extract = catch_unwind(AssertUnwindSafe(|| {
+            let extracted = tree.extract_if(|key, _| {
+                assert_ne!(*key, 50);
+                key % 2 == 0
+            });
+            extracted.for_each(drop);
+        }));
+        assert!(extract.is_err());
+        check(&tree);
+        assert_eq!(tree.size(), 75);
+        assert!(tree.keys().all(|key| key % 2 == 1 || *key >= 50));
+        assert!(tree.contains(&50));
+    }
+
+    #[test]
+    fn std_traits() {
+        let tree: BTreeMap<usize, usize> =
+            (0..100).rev().map(|key| (key, key)).collect();
+        assert_eq!(tree.size(), 100);
+        assert!(tree.keys().copied().eq(0..100));
+        check(&tree);
+        let mut other = tree.clone();
+        assert_eq!(other, tree);
+        other.extend([(100, 100), (0, 1)]);
+        other.extend([(&101, &101)]);
+        assert_eq!(other.size(), 102);
+        assert_eq!(other.get(&0), Some(&1));
+        assert!(tree < other);
+
+        let small = BTreeMap::from([(2, "b"), (1, "a"), (2, "c")]);
+        assert_eq!(format!("{small:?}"), r#"{1: "a", 2: "c"}"#);
+        assert_eq!(small, BTreeMap::from(vec![(1, "a"), (2, "c")]));
+        assert_ne!(small, BTreeMap::from([(1, "a")]));
+    }
+
+    #[test]
+    fn custom_allocator() {
+        let counting = Counting::default();
+        let mut tree =
+            BTreeMap::<usize, usize, 3, _>::with_branching_factor_in(&counting);
+        tree.extend((0..100).map(|key| (key, key)));
+        let live = counting.live.get();
+        assert!(live > 1);
+        assert_eq!(counting.allocated.get(), live);
+        // iterators keep their pending steps in the same allocator
+        check(&tree);
+        assert!(counting.allocated.get() > live);
+        assert_eq!(counting.live.get(), live);
+
+        let mut right = tree.split_off(&50);
+        check(&tree);
+        check(&right);
+        tree.append(&mut right);
+        assert!(tree.keys().copied().eq(0..100));
+
+        // nothing is rebuilt unless something is removed
+        let root = tree.root.as_deref().map(|root| root as *const _);
+        tree.retain(|_, _| true);
+        assert_eq!(tree.root.as_deref().map(|root| root as *const _), root);
+
+        let copy = tree.clone();
+        tree.retain(|key, _| key % 2 == 0);
+        check(&tree);
+        drop(copy);
+        tree.clear();
+        drop(right);
+        assert_eq!(counting.live.get(), 0);
+
+        let mut small = BTreeMap::new_in(&counting);
+        small.insert(1, "one");
+        assert_eq!(counting.live.get(), 1);
+        assert_eq!(small.remove(&1).map(|entry| *entry.key()), Some(1));
+        assert_eq!(counting.live.get(), 0);
+    }
 }
@@ -1,15 +1,685 @@
+//! a node in `BTreeMap`
+use crate::alloc::{Allocator, Global};
+use crate::boxed::AllocBox;
 use crate::entry::Entry;
-const CAPACITY: usize = 6;
-const MIN: usize = CAPACITY / 2;
-const MAX_CHILDREN: usize = CAPACITY + 1;
+use crate::traversal::{Expand, Step};
+#[cfg(test)]
+use alloc_crate::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::iter;
 
-pub struct Node<K, V>
+/// an optional, owned child of a node
+type Child<K, V, const B: usize, A> = Option<AllocBox<Node<K, V, B, A>, A>>;
+
+/// a node with up to `B` children, and so up to `B - 1` entries
+/// `entries[..len]` are sorted by key, and the rest are `None`
+/// `children[i]` holds the entries smaller than `entries[i]`,
+/// and `children[i + 1]` the ones greater than it
+/// a leaf has no children at all, any other node has exactly `len + 1`
+///
+/// `entries` has room for `B` entries so that both arrays share a length,
+/// but the last slot is never used
+pub struct Node<K, V, const B: usize, A = Global>
 where
     K: Ord,
+    A: Allocator,
 {
     len: usize,
-    entries: [Option<Entry<K, V>>; CAPACITY],
-    children: [Option<Box<Node<K, V>>>; MAX_CHILDREN],
+    entries: [Option<Entry<K, V>>; B],
+    children: [Child<K, V, B, A>; B],
+}
+
+/// the result of inserting into a subtree
+pub enum Insertion<K, V, const B: usize, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    /// the key was already present
+    Existing,
+    /// a new entry was added
+    Added,
+    /// a new entry was added, but the node had to be split in two
+    /// the entry and the new right half must be added to the parent
+    Split(Entry<K, V>, AllocBox<Node<K, V, B, A>, A>),
+}
+
+impl<K, V, const B: usize, A> Node<K, V, B, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    /// the maximum number of entries in a node
+    const CAPACITY: usize = B - 1;
+    /// the minimum number of entries in any node but the root
+    const MIN: usize = Self::CAPACITY / 2;
+
+    /// creates a node without any entries
+    pub fn new() -> Node<K, V, B, A> {
+        Node {
+            len: 0,
+            entries: [const { None }; B],
+            children: [const { None }; B],
+        }
+    }
+
+    /// creates a root with a single entry between `left` and `right`
+    pub fn with_children(
+        left: AllocBox<Node<K, V, B, A>, A>,
+        entry: Entry<K, V>,
+        right: AllocBox<Node<K, V, B, A>, A>,
+    ) -> Node<K, V, B, A> {
+        let mut node = Node::new();
+        node.children[0] = Some(left);
+        node.push_pair(entry, Some(right));
+        node
+    }
+
+    /// builds a tree out of the first `len` entries of `entries`,
+    /// which must be sorted by key, with nodes from `alloc`
+    /// the tree is as shallow as it can be, and the entries are spread
+    /// evenly over the nodes of each level
+    pub fn from_sorted(
+        len: usize,
+        entries: &mut impl Iterator<Item = (K, V)>,
+        alloc: &A,
+    ) -> Child<K, V, B, A>
+    where
+        A: Clone,
+    {
+        if len == 0 {
+            return None;
+        }
+        let mut height = 1;
+        while Self::capacity(height) < len {
+            height += 1;
+        }
+        Some(Node::build(len, height, 2, entries, alloc))
+    }
+
+    /// the number of entries that fit in a subtree of height `height`
+    fn capacity(height: usize) -> usize {
+        (1..height).fold(Self::CAPACITY, |capacity, _| {
+            capacity.saturating_mul(B).saturating_add(Self::CAPACITY)
+        })
+    }
+
+    /// builds a subtree of height `height` out of the next `len` entries,
+    /// whose root has at least `min_children` children unless it is a leaf
+    /// every subtree below gets between `MIN + 1` and `B` children, which
+    /// is always possible as long as `len` fits the height
+    fn build(
+        len: usize,
+        height: usize,
+        min_children: usize,
+        entries: &mut impl Iterator<Item = (K, V)>,
+        alloc: &A,
+    ) -> AllocBox<Node<K, V, B, A>, A>
+    where
+        A: Clone,
+    {
+        let mut node = AllocBox::new_in(Node::new(), alloc.clone());
+        if height == 1 {
+            for (key, value) in entries.take(len) {
+                node.push_pair(Entry::new(key, value), None);
+            }
+            return node;
+        }
+        let child_capacity = Self::capacity(height - 1);
+        let children = (len + 1).div_ceil(child_capacity + 1).max(min_children);
+        // the entries that are not separators, spread over the children
+        let below = len + 1 - children;
+        for index in 0..children {
+            let separator = match index {
+                0 => None,
+                _ => entries.next(),
+            };
+            let len = below / children + usize::from(index < below % children);
+            let child =
+                Node::build(len, height - 1, Self::MIN + 1, entries, alloc);
+            if index == 0 {
+                node.children[0] = Some(child);
+            } else if let Some((key, value)) = separator {
+                node.push_pair(Entry::new(key, value), Some(child));
+            }
+        }
+        node
+    }
+
+    /// returns the number of entries directly in `self`
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// returns `true` if `self` has no children
+    pub fn is_leaf(&self) -> bool {
+        self.children[0].is_none()
+    }
+
+    /// takes out the only child of an empty node, which is left behind
+    /// when removing the last entry of the root
+    pub fn take_first_child(&mut self) -> Child<K, V, B, A> {
+        self.children[0].take()
+    }
+
+    /// binary searches for `key` among the entries of `self`
+    /// returns `Ok` with its index if it is found,
+    /// or `Err` with the index of the child that could contain it
+    fn search<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries[..self.len].binary_search_by(|entry| match entry {
+            Some(entry) => entry.key().borrow().cmp(key),
+            None => Ordering::Greater,
+        })
+    }
+
+    /// returns an optional reference to the `Entry` with key `key`
+    pub fn entry<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self;
+        loop {
+            match node.search(key) {
+                Ok(index) => return node.entries[index].as_ref(),
+                Err(index) => node = node.children[index].as_deref()?,
+            }
+        }
+    }
+
+    /// returns an optional mutable reference to the `Entry` with key `key`
+    pub fn entry_mut<Q>(&mut self, key: &Q) -> Option<&mut Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self;
+        loop {
+            match node.search(key) {
+                Ok(index) => return node.entries[index].as_mut(),
+                Err(index) => node = node.children[index].as_deref_mut()?,
+            }
+        }
+    }
+
+    /// returns a reference to the smallest entry
+    pub fn smallest(&self) -> Option<&Entry<K, V>> {
+        let mut node = self;
+        while let Some(ref child) = node.children[0] {
+            node = child;
+        }
+        node.entries[0].as_ref()
+    }
+
+    /// returns a reference to the largest entry
+    pub fn largest(&self) -> Option<&Entry<K, V>> {
+        let mut node = self;
+        while let Some(ref child) = node.children[node.len] {
+            node = child;
+        }
+        node.entries[node.len.checked_sub(1)?].as_ref()
+    }
+
+    /// adds an entry with key `key` and value `value` below `self`
+    /// if `key` already exists, its value is only replaced if `overwrite`
+    /// new nodes are allocated from `alloc`
+    pub fn insert(
+        &mut self,
+        key: K,
+        value: V,
+        overwrite: bool,
+        alloc: &A,
+    ) -> Insertion<K, V, B, A>
+    where
+        A: Clone,
+    {
+        match self.search(&key) {
+            Ok(index) => {
+                if let (true, Some(entry)) =
+                    (overwrite, &mut self.entries[index])
+                {
+                    entry.value = value;
+                }
+                Insertion::Existing
+            }
+            Err(index) => match self.children[index] {
+                Some(ref mut child) => {
+                    match child.insert(key, value, overwrite, alloc) {
+                        Insertion::Split(entry, right) => {
+                            self.insert_pair(index, entry, Some(right), alloc)
+                        }
+                        insertion => insertion,
+                    }
+                }
+                None => {
+                    let entry = Entry::new(key, value);
+                    self.insert_pair(index, entry, None, alloc)
+                }
+            },
+        }
+    }
+
+    /// inserts `entry` at `index`, with `right` as the child after it,
+    /// splitting `self` if it is already full
+    fn insert_pair(
+        &mut self,
+        index: usize,
+        entry: Entry<K, V>,
+        right: Child<K, V, B, A>,
+        alloc: &A,
+    ) -> Insertion<K, V, B, A>
+    where
+        A: Clone,
+    {
+        if self.len < Self::CAPACITY {
+            self.put_pair(index, entry, right);
+            return Insertion::Added;
+        }
+        // out of the `CAPACITY + 1` entries, the first `middle` stay in
+        // `self`, the next one moves up to the parent and the rest move to
+        // the new `sibling`
+        let middle = Self::CAPACITY.div_ceil(2);
+        let mut sibling = AllocBox::new_in(Node::new(), alloc.clone());
+        let median = match index.cmp(&middle) {
+            Ordering::Less => {
+                self.move_tail(middle, &mut sibling);
+                let (median, child) = self.pop_pair();
+                sibling.children[0] = child;
+                self.put_pair(index, entry, right);
+                median
+            }
+            Ordering::Equal => {
+                self.move_tail(middle, &mut sibling);
+                sibling.children[0] = right;
+                Some(entry)
+            }
+            Ordering::Greater => {
+                self.move_tail(middle + 1, &mut sibling);
+                let (median, child) = self.pop_pair();
+                sibling.children[0] = child;
+                sibling.put_pair(index - middle - 1, entry, right);
+                median
+            }
+        };
+        match median {
+            Some(median) => Insertion::Split(median, sibling),
+            None => Insertion::Added,
+        }
+    }
+
+    /// inserts `entry` at `index`, with `right` as the child after it
+    /// `self` must not be full
+    fn put_pair(
+        &mut self,
+        index: usize,
+        entry: Entry<K, V>,
+        right: Child<K, V, B, A>,
+    ) {
+        self.entries[index..=self.len].rotate_right(1);
+        self.children[index + 1..=self.len + 1].rotate_right(1);
+        self.entries[index] = Some(entry);
+        self.children[index + 1] = right;
+        self.len += 1;
+    }
+
+    /// appends `entry`, with `right` as the child after it
+    /// `self` must not be full
+    fn push_pair(&mut self, entry: Entry<K, V>, right: Child<K, V, B, A>) {
+        self.put_pair(self.len, entry, right);
+    }
+
+    /// removes the entry at `index` and the child after it
+    fn remove_pair(
+        &mut self,
+        index: usize,
+    ) -> (Option<Entry<K, V>>, Child<K, V, B, A>) {
+        let entry = self.entries[index].take();
+        let right = self.children[index + 1].take();
+        self.entries[index..self.len].rotate_left(1);
+        self.children[index + 1..=self.len].rotate_left(1);
+        self.len -= 1;
+        (entry, right)
+    }
+
+    /// removes the last entry and the last child
+    fn pop_pair(&mut self) -> (Option<Entry<K, V>>, Child<K, V, B, A>) {
+        self.remove_pair(self.len - 1)
+    }
+
+    /// removes the first child and the first entry
+    fn pop_front_pair(&mut self) -> (Child<K, V, B, A>, Option<Entry<K, V>>) {
+        let left = self.children[0].take();
+        let entry = self.entries[0].take();
+        self.entries[..self.len].rotate_left(1);
+        self.children[..=self.len].rotate_left(1);
+        self.len -= 1;
+        (left, entry)
+    }
+
+    /// prepends `entry`, with `left` as the child before it
+    /// `self` must not be full
+    fn push_front_pair(&mut self, left: Child<K, V, B, A>, entry: Entry<K, V>) {
+        self.entries[..=self.len].rotate_right(1);
+        self.children[..=self.len + 1].rotate_right(1);
+        self.children[0] = left;
+        self.entries[0] = Some(entry);
+        self.len += 1;
+    }
+
+    /// moves the entries from `index` onwards, and the children after them,
+    /// to the empty node `sibling`, leaving `sibling.children[0]` empty
+    fn move_tail(&mut self, index: usize, sibling: &mut Node<K, V, B, A>) {
+        for (offset, entry) in
+            self.entries[index..self.len].iter_mut().enumerate()
+        {
+            sibling.entries[offset] = entry.take();
+        }
+        for (offset, child) in
+            self.children[index + 1..=self.len].iter_mut().enumerate()
+        {
+            sibling.children[offset + 1] = child.take();
+        }
+        sibling.len = self.len - index;
+        self.len = index;
+    }
+
+    /// removes the entry with key `key` from below `self` and returns it
+    /// returns `None` if no such entry exists
+    ///
+    /// an entry in an inner node is replaced by its in-order predecessor
+    /// children left with fewer than `MIN` entries are refilled
+    /// on the way back up, but `self` may end up with less
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.search(key) {
+            Ok(index) if self.is_leaf() => self.remove_pair(index).0,
+            Ok(index) => {
+                let predecessor = self.children[index].as_mut()?.pop_last()?;
+                let removed = self.entries[index].replace(predecessor);
+                self.refill_child(index);
+                removed
+            }
+            Err(index) => {
+                let removed = self.children[index].as_mut()?.remove(key);
+                self.refill_child(index);
+                removed
+            }
+        }
+    }
+
+    /// removes the largest entry from below `self` and returns it
+    pub fn pop_last(&mut self) -> Option<Entry<K, V>> {
+        if self.is_leaf() {
+            return match self.len {
+                0 => None,
+                _ => self.pop_pair().0,
+            };
+        }
+        let last = self.children[self.len].as_mut()?.pop_last();
+        self.refill_child(self.len);
+        last
+    }
+
+    /// makes sure the child at `index` has at least `MIN` entries,
+    /// by borrowing from a sibling or merging with it
+    fn refill_child(&mut self, index: usize) {
+        let Some(ref child) = self.children[index] else {
+            return;
+        };
+        if child.len >= Self::MIN {
+            return;
+        }
+        let has_spare = |child: &Child<K, V, B, A>| {
+            child.as_ref().is_some_and(|child| child.len > Self::MIN)
+        };
+        if index > 0 && has_spare(&self.children[index - 1]) {
+            self.rotate_right(index - 1);
+        } else if index < self.len && has_spare(&self.children[index + 1]) {
+            self.rotate_left(index);
+        } else if index > 0 {
+            self.merge_children(index - 1);
+        } else {
+            self.merge_children(index);
+        }
+    }
+
+    /// moves the last entry of the child before `index` up into `index`,
+    /// and the entry that was there down into the child after it
+    fn rotate_right(&mut self, index: usize) {
+        let Some([left, right]) = children_around(&mut self.children, index)
+        else {
+            return;
+        };
+        let (Some(entry), child) = left.pop_pair() else {
+            return;
+        };
+        if let Some(separator) = self.entries[index].replace(entry) {
+            right.push_front_pair(child, separator);
+        }
+    }
+
+    /// moves the first entry of the child after `index` up into `index`,
+    /// and the entry that was there down into the child before it
+    fn rotate_left(&mut self, index: usize) {
+        let Some([left, right]) = children_around(&mut self.children, index)
+        else {
+            return;
+        };
+        let (child, Some(entry)) = right.pop_front_pair() else {
+            return;
+        };
+        if let Some(separator) = self.entries[index].replace(entry) {
+            left.push_pair(separator, child);
+        }
+    }
+
+    /// merges the entry at `index` and the child after it
+    /// into the child before it
+    fn merge_children(&mut self, index: usize) {
+        let (Some(separator), Some(mut right)) = self.remove_pair(index) else {
+            return;
+        };
+        let Some(ref mut left) = self.children[index] else {
+            return;
+        };
+        // borrow through the box once, so its fields can be split apart
+        let right = &mut *right;
+        let first = right.children[0].take();
+        left.push_pair(separator, first);
+        let len = right.len;
+        for (entry, child) in right.entries[..len]
+            .iter_mut()
+            .zip(&mut right.children[1..=len])
+        {
+            if let Some(entry) = entry.take() {
+                left.push_pair(entry, child.take());
+            }
+        }
+    }
+
+    /// checks the B-tree invariants of the subtree rooted at `self`
+    /// and returns its depth
+    #[cfg(test)]
+    pub fn check(&self, is_root: bool) -> usize {
+        assert!(self.len <= Self::CAPACITY);
+        assert!(is_root || self.len >= Self::MIN, "underfull node");
+        assert!(self.entries[self.len..].iter().all(Option::is_none));
+        let keys: Vec<&K> =
+            self.entries.iter().flatten().map(Entry::key).collect();
+        assert_eq!(keys.len(), self.len);
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+        if self.is_leaf() {
+            assert!(self.children.iter().all(Option::is_none));
+            return 1;
+        }
+        assert!(self.children[self.len + 1..].iter().all(Option::is_none));
+        let depths: Vec<usize> = self.children[..=self.len]
+            .iter()
+            .enumerate()
+            .map(|(index, child)| {
+                let child = child.as_ref().expect("missing child");
+                if index > 0 {
+                    assert!(
+                        child.smallest().map(Entry::key)
+                            > Some(keys[index - 1])
+                    );
+                }
+                if index < self.len {
+                    assert!(
+                        child.largest().map(Entry::key) < Some(keys[index])
+                    );
+                }
+                child.check(false)
+            })
+            .collect();
+        assert!(depths.windows(2).all(|pair| pair[0] == pair[1]));
+        depths[0] + 1
+    }
+}
+
+impl<'a, K, V, const B: usize, A> Expand for &'a Node<K, V, B, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    type Entry = (&'a K, &'a V);
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let first = self.children[0].as_deref().map(Step::Node);
+        let pairs = self.entries.iter().zip(&self.children[1..]).flat_map(
+            |(entry, child)| {
+                [
+                    entry.as_ref().map(|entry| Step::Entry(entry.split())),
+                    child.as_deref().map(Step::Node),
+                ]
+            },
+        );
+        iter::once(first).chain(pairs).flatten()
+    }
+}
+
+impl<'a, K, V, const B: usize, A> Expand for &'a mut Node<K, V, B, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    type Entry = (&'a K, &'a mut V);
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let (first, rest) = self.children.split_at_mut(1);
+        let first = first[0].as_deref_mut().map(Step::Node);
+        let pairs =
+            self.entries
+                .iter_mut()
+                .zip(rest)
+                .flat_map(|(entry, child)| {
+                    [
+                        entry
+                            .as_mut()
+                            .map(|entry| Step::Entry(entry.split_mut())),
+                        child.as_deref_mut().map(Step::Node),
+                    ]
+                });
+        iter::once(first).chain(pairs).flatten()
+    }
+}
+
+impl<K, V, const B: usize, A> Expand for AllocBox<Node<K, V, B, A>, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    type Entry = (K, V);
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let Node {
+            entries, children, ..
+        } = AllocBox::into_inner(self);
+        let mut children = children.into_iter();
+        let first = children.next().flatten().map(Step::Node);
+        let pairs =
+            entries
+                .into_iter()
+                .zip(children)
+                .flat_map(|(entry, child)| {
+                    [
+                        entry.map(|entry| Step::Entry(entry.into_pair())),
+                        child.map(Step::Node),
+                    ]
+                });
+        iter::once(first).chain(pairs).flatten()
+    }
+}
+
+/// a handle to a node that hands out the slots of its entries,
+/// so that entries can be taken out of the tree during a traversal
+/// the gaps left behind have to be closed before the tree is used again
+pub struct Slots<'a, K, V, const B: usize, A>(&'a mut Node<K, V, B, A>)
+where
+    K: Ord,
+    A: Allocator;
+
+impl<'a, K, V, const B: usize, A> Slots<'a, K, V, B, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    pub fn new(node: &'a mut Node<K, V, B, A>) -> Slots<'a, K, V, B, A> {
+        Slots(node)
+    }
 }
 
-impl<K, V> Node<K, V> where K: Ord {}
+impl<'a, K, V, const B: usize, A> Expand for Slots<'a, K, V, B, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    type Entry = &'a mut Option<Entry<K, V>>;
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let Node {
+            len,
+            entries,
+            children,
+        } = self.0;
+        let mut children = children[..=*len].iter_mut();
+        let child = |child: &'a mut Child<K, V, B, A>| {
+            child.as_deref_mut().map(|node| Step::Node(Slots(node)))
+        };
+        let first = children.next().and_then(child);
+        let pairs = entries[..*len].iter_mut().zip(children).flat_map(
+            move |(entry, right)| [Some(Step::Entry(entry)), child(right)],
+        );
+        iter::once(first).chain(pairs).flatten()
+    }
+}
+
+/// returns mutable references to the children on either side of the entry
+/// at `index`
+fn children_around<K, V, const B: usize, A>(
+    children: &mut [Child<K, V, B, A>],
+    index: usize,
+) -> Option<[&mut Node<K, V, B, A>; 2]>
+where
+    K: Ord,
+    A: Allocator,
+{
+    let (left, right) = children.split_at_mut(index + 1);
+    Some([left[index].as_deref_mut()?, right[0].as_deref_mut()?])
+}
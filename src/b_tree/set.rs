@@ -1 +1,237 @@
+//! a B-tree with keys but no associated value
+//! a degenerate form of `BTreeMap`
 
+use super::iter::Extraction;
+use super::map::{self, BTreeMap, DEFAULT_B};
+use crate::alloc::Global;
+use crate::iter::Keys;
+use alloc_crate::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BTreeSet<T, const B: usize = DEFAULT_B>
+where
+    T: Ord,
+{
+    map: BTreeMap<T, (), B>,
+}
+
+impl<K> BTreeSet<K>
+where
+    K: Ord,
+{
+    /// creates an empty `BTreeSet` with the default branching factor
+    pub const fn new() -> BTreeSet<K> {
+        BTreeSet::with_branching_factor()
+    }
+}
+
+impl<K, const B: usize> BTreeSet<K, B>
+where
+    K: Ord,
+{
+    /// creates an empty `BTreeSet` whose nodes have up to `B` children
+    /// `B` must be at least 3, smaller values are rejected at compile time
+    pub const fn with_branching_factor() -> BTreeSet<K, B> {
+        BTreeSet {
+            map: BTreeMap::with_branching_factor(),
+        }
+    }
+
+    /// inserts a new entry with key `key`
+    /// if such an entry already exists, nothing happens
+    pub fn insert(&mut self, key: K) {
+        self.map.try_insert(key, ());
+    }
+
+    /// removes the entry with key `key`
+    /// returns `true` if such an entry existed
+    pub fn remove<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.remove(key).is_some()
+    }
+
+    /// returns the number of entries in `self`
+    pub fn size(&self) -> usize {
+        self.map.size()
+    }
+
+    /// returns true if `self` contains an entry `key`
+    /// otherwise returns false
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.contains(key)
+    }
+
+    /// returns an optional reference to the smallest key
+    pub fn smallest(&self) -> Option<&K> {
+        self.map.smallest().map(|entry| entry.key())
+    }
+
+    /// returns an optional reference to the largest key
+    pub fn largest(&self) -> Option<&K> {
+        self.map.largest().map(|entry| entry.key())
+    }
+
+    /// removes all keys from `self`
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// keeps only the keys for which `f` returns `true`
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K) -> bool,
+    {
+        self.map.retain(|key, _| f(key));
+    }
+
+    /// returns an iterator that removes the keys for which `pred` returns
+    /// `true`, in order, and yields them
+    /// if the iterator is dropped early, the remaining keys are kept
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, F, B>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        ExtractIf {
+            extraction: Extraction::new(&mut self.map),
+            pred,
+        }
+    }
+
+    /// returns an iterator over the keys of `self`, in order
+    pub fn iter(&self) -> Iter<'_, K, B> {
+        Keys::new(self.map.iter())
+    }
+}
+
+impl<T, const B: usize> IntoIterator for BTreeSet<T, B>
+where
+    T: Ord,
+{
+    type Item = T;
+    type IntoIter = IntoIter<T, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Keys::new(self.map.into_iter())
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a BTreeSet<T, B>
+where
+    T: Ord,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, const B: usize> Default for BTreeSet<T, B>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::with_branching_factor()
+    }
+}
+
+impl<T, const B: usize> fmt::Debug for BTreeSet<T, B>
+where
+    T: Ord + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self).finish()
+    }
+}
+
+impl<T, const B: usize> FromIterator<T> for BTreeSet<T, B>
+where
+    T: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        BTreeSet {
+            map: iter.into_iter().map(|key| (key, ())).collect(),
+        }
+    }
+}
+
+impl<T, const B: usize> Extend<T> for BTreeSet<T, B>
+where
+    T: Ord,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|key| (key, ())));
+    }
+}
+
+impl<'a, T, const B: usize> Extend<&'a T> for BTreeSet<T, B>
+where
+    T: Ord + Copy,
+{
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+// like `new`, these only exist for the default branching factor, since
+// it could not be inferred otherwise
+impl<T, const N: usize> From<[T; N]> for BTreeSet<T>
+where
+    T: Ord,
+{
+    fn from(array: [T; N]) -> Self {
+        Self::from_iter(array)
+    }
+}
+
+impl<T> From<Vec<T>> for BTreeSet<T>
+where
+    T: Ord,
+{
+    fn from(vec: Vec<T>) -> Self {
+        Self::from_iter(vec)
+    }
+}
+
+/// an iterator over the keys of a `BTreeSet`, in order
+pub type Iter<'a, T, const B: usize = DEFAULT_B> =
+    Keys<map::Iter<'a, T, (), B>>;
+
+/// an owning iterator over the keys of a `BTreeSet`, in order
+pub type IntoIter<T, const B: usize = DEFAULT_B> =
+    Keys<map::IntoIter<T, (), B>>;
+
+/// an iterator that removes the keys of a `BTreeSet` matching a predicate
+/// as it goes, and returns them, in order
+/// keys it has not reached when dropped stay in the set
+pub struct ExtractIf<'a, T, F, const B: usize = DEFAULT_B>
+where
+    T: Ord,
+{
+    extraction: Extraction<'a, T, (), B, Global>,
+    pred: F,
+}
+
+impl<T, F, const B: usize> Iterator for ExtractIf<'_, T, F, B>
+where
+    T: Ord,
+    F: FnMut(&T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pred = &mut self.pred;
+        let (key, ()) = self.extraction.next(|key, _| pred(key))?;
+        Some(key)
+    }
+}
@@ -0,0 +1,158 @@
+//! a view into a single entry of an `ArenaBsTreeMap`,
+//! which may or may not exist
+use super::node::Arena;
+use crate::entry::Entry;
+use core::mem;
+
+/// an entry of an `ArenaBsTreeMap`, as returned by `ArenaBsTreeMap::entry`
+pub enum MapEntry<'a, K, V>
+where
+    K: Ord,
+{
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+/// an entry that exists in the tree
+/// `index` is the index of its node in `arena`
+pub struct OccupiedEntry<'a, K, V>
+where
+    K: Ord,
+{
+    index: u32,
+    arena: &'a mut Arena<K, V>,
+}
+
+/// an entry that does not exist in the tree yet
+pub struct VacantEntry<'a, K, V>
+where
+    K: Ord,
+{
+    key: K,
+    arena: &'a mut Arena<K, V>,
+}
+
+impl<'a, K, V> MapEntry<'a, K, V>
+where
+    K: Ord,
+{
+    /// returns a reference to the key of the entry
+    pub fn key(&self) -> &K {
+        match self {
+            MapEntry::Occupied(entry) => entry.key(),
+            MapEntry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// inserts `default` if the entry is vacant
+    /// returns a mutable reference to the value
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// inserts the result of `default` if the entry is vacant
+    /// returns a mutable reference to the value
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            MapEntry::Occupied(entry) => entry.into_mut(),
+            MapEntry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// calls `f` on the value if the entry is occupied
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let MapEntry::Occupied(ref mut entry) = self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V> MapEntry<'a, K, V>
+where
+    K: Ord,
+    V: Default,
+{
+    /// inserts the default value if the entry is vacant
+    /// returns a mutable reference to the value
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V>
+where
+    K: Ord,
+{
+    pub(super) fn new(
+        index: u32,
+        arena: &'a mut Arena<K, V>,
+    ) -> OccupiedEntry<'a, K, V> {
+        OccupiedEntry { index, arena }
+    }
+
+    /// returns a reference to the key of the entry
+    pub fn key(&self) -> &K {
+        self.arena.entry_at(self.index).key()
+    }
+
+    /// returns a reference to the value of the entry
+    pub fn get(&self) -> &V {
+        self.arena.entry_at(self.index).value()
+    }
+
+    /// returns a mutable reference to the value of the entry
+    pub fn get_mut(&mut self) -> &mut V {
+        self.arena.entry_at_mut(self.index).value_mut()
+    }
+
+    /// turns the entry into a mutable reference to its value
+    pub fn into_mut(self) -> &'a mut V {
+        self.arena.entry_at_mut(self.index).value_mut()
+    }
+
+    /// sets the value of the entry to `value`, and returns the old value
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// removes the entry from the tree and returns it
+    pub fn remove_entry(self) -> Entry<K, V> {
+        self.arena.remove_at(self.index)
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V>
+where
+    K: Ord,
+{
+    pub(super) fn new(
+        key: K,
+        arena: &'a mut Arena<K, V>,
+    ) -> VacantEntry<'a, K, V> {
+        VacantEntry { key, arena }
+    }
+
+    /// returns a reference to the key that would be used for the entry
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// takes back ownership of the key
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// inserts the entry into the tree with value `value`
+    /// returns a mutable reference to the value
+    pub fn insert(self, value: V) -> &'a mut V {
+        let (_, index) = self.arena.insert(Entry::new(self.key, value), false);
+        self.arena.entry_at_mut(index).value_mut()
+    }
+}
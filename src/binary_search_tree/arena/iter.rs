@@ -0,0 +1,102 @@
+//! in-order iterators over `ArenaBsTreeMap`
+use super::node::{Extraction, Node, NodeMut, NodeRef};
+use crate::iter;
+use alloc_crate::vec;
+use alloc_crate::vec::Vec;
+
+/// an iterator over the entries of an `ArenaBsTreeMap`,
+/// in order of their keys
+pub type Iter<'a, K, V> = iter::Iter<NodeRef<'a, K, V>>;
+
+/// a mutable iterator over the entries of an `ArenaBsTreeMap`,
+/// in order of their keys
+pub type IterMut<'a, K, V> = iter::Iter<NodeMut<'a, K, V>>;
+
+/// an iterator that removes the entries of an `ArenaBsTreeMap` matching a
+/// predicate as it goes, and returns them, in order of their keys
+/// entries it has not reached when dropped stay in the map
+pub struct ExtractIf<'a, K, V, F>
+where
+    K: Ord,
+{
+    extraction: Extraction<'a, K, V>,
+    pred: F,
+}
+
+impl<'a, K, V, F> ExtractIf<'a, K, V, F>
+where
+    K: Ord,
+{
+    pub(super) fn new(
+        extraction: Extraction<'a, K, V>,
+        pred: F,
+    ) -> ExtractIf<'a, K, V, F> {
+        ExtractIf { extraction, pred }
+    }
+}
+
+impl<K, V, F> Iterator for ExtractIf<'_, K, V, F>
+where
+    K: Ord,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.extraction.extract_next(&mut self.pred)
+    }
+}
+
+/// an iterator over the keys of an `ArenaBsTreeMap`, in order
+pub type Keys<'a, K, V> = iter::Keys<Iter<'a, K, V>>;
+
+/// an iterator over the values of an `ArenaBsTreeMap`,
+/// in order of their keys
+pub type Values<'a, K, V> = iter::Values<Iter<'a, K, V>>;
+
+/// a mutable iterator over the values of an `ArenaBsTreeMap`,
+/// in order of their keys
+pub type ValuesMut<'a, K, V> = iter::Values<IterMut<'a, K, V>>;
+
+/// an iterator over the entries of an `ArenaBsTreeMap` whose keys lie
+/// within a range, in order of their keys
+pub type Range<'a, K, V> = iter::Range<NodeRef<'a, K, V>>;
+
+/// a mutable iterator over the entries of an `ArenaBsTreeMap` whose keys
+/// lie within a range, in order of their keys
+pub type RangeMut<'a, K, V> = iter::Range<NodeMut<'a, K, V>>;
+
+/// an owning iterator over the entries of an `ArenaBsTreeMap`,
+/// in order of their keys
+/// the nodes are sorted up front, so this is a plain walk over a `Vec`
+pub struct IntoIter<K, V> {
+    nodes: vec::IntoIter<Node<K, V>>,
+}
+
+impl<K, V> IntoIter<K, V> {
+    pub(super) fn new(nodes: Vec<Node<K, V>>) -> IntoIter<K, V> {
+        IntoIter {
+            nodes: nodes.into_iter(),
+        }
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(Node::into_pair)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.nodes.next_back().map(Node::into_pair)
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
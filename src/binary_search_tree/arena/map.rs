@@ -0,0 +1,812 @@
+//! a binary search tree with keys and values, whose nodes live in a single
+//! arena rather than in a `Box` each
+//! note: like `BsTreeMap`, this tree makes no attempts to maintain balance
+//!
+//! the nodes are kept side by side in one `Vec`, and refer to their children
+//! by `u32` index, which makes lookups and traversals friendlier to the
+//! cache, and makes dropping and cloning the whole tree a matter of
+//! dropping and cloning that one buffer
+//! a tree holds fewer than 2^32 entries
+
+use super::node::{Arena, Extraction};
+use crate::entry::Entry;
+use crate::merge::merge_sorted;
+use alloc_crate::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::mem;
+use core::ops::RangeBounds;
+
+pub use super::entry::{MapEntry, OccupiedEntry, VacantEntry};
+pub use super::iter::{
+    ExtractIf, IntoIter, Iter, IterMut, Keys, Range, RangeMut, Values,
+    ValuesMut,
+};
+pub use crate::merge::{KeepLeft, KeepRight, MergePolicy};
+
+#[derive(Clone)]
+pub struct ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+{
+    arena: Arena<K, V>,
+}
+
+impl<K, V> ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+{
+    /// creates an empty ArenaBsTreeMap<K, V>
+    #[inline]
+    pub const fn new() -> ArenaBsTreeMap<K, V> {
+        ArenaBsTreeMap {
+            arena: Arena::new(),
+        }
+    }
+
+    /// returns the number of entries in `self`
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.arena.size()
+    }
+
+    /// returns `true` if `self` contains an node with key `key`
+    /// otherwise returns `false`
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.arena.find(key).is_some()
+    }
+
+    /// removes all entries from `self`
+    /// the arena keeps its capacity
+    pub fn clear(&mut self) {
+        self.arena.clear();
+    }
+
+    /// removes the entry with key `key` and returns it
+    /// returns `None` if no such entry exists
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.arena.remove(key)
+    }
+
+    /// sets the value of the key with key `key` to `value`
+    /// if `key` already exists, the value is overridden
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) {
+        self.arena.insert(Entry::new(key, value), true);
+    }
+
+    /// inserts a new entry with key `key` and value `value`
+    /// if `key` already exists, the old value is kept
+    pub fn try_insert(&mut self, key: K, value: V) {
+        self.arena.insert(Entry::new(key, value), false);
+    }
+
+    /// returns an optional reference to the `value` with key `key`
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.arena.entry(key).map(Entry::value)
+    }
+
+    /// returns an optional mutable reference to the `value` with key `key`
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.arena.entry_mut(key).map(Entry::value_mut)
+    }
+
+    /// returns an optional reference to the smallest node
+    pub fn smallest(&self) -> Option<&Entry<K, V>> {
+        self.arena.smallest()
+    }
+
+    /// returns an optional mutable reference to the smallest node
+    pub fn smallest_mut(&mut self) -> Option<&mut Entry<K, V>> {
+        self.arena.smallest_mut()
+    }
+
+    /// returns an optional reference to the largest node
+    pub fn largest(&self) -> Option<&Entry<K, V>> {
+        self.arena.largest()
+    }
+
+    /// returns an optional mutable reference to the largest node
+    pub fn largest_mut(&mut self) -> Option<&mut Entry<K, V>> {
+        self.arena.largest_mut()
+    }
+
+    /// removes the smallest entry from `self` and returns it
+    /// returns `None` if `self` is empty
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        self.arena.pop_smallest().map(Entry::into_pair)
+    }
+
+    /// removes the largest entry from `self` and returns it
+    /// returns `None` if `self` is empty
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        self.arena.pop_largest().map(Entry::into_pair)
+    }
+
+    /// returns the entry with the largest key that is at most `key`
+    pub fn floor<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.arena.below(key, true)
+    }
+
+    /// returns the entry with the smallest key that is at least `key`
+    pub fn ceiling<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.arena.above(key, true)
+    }
+
+    /// returns the first entry whose key is not less than `key`
+    /// the same as `ceiling`
+    pub fn lower_bound<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.ceiling(key)
+    }
+
+    /// returns the first entry whose key is greater than `key`
+    /// the same as `successor`
+    pub fn upper_bound<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.successor(key)
+    }
+
+    /// returns the entry with the largest key smaller than `key`
+    /// `key` does not have to be in `self`
+    pub fn predecessor<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.arena.below(key, false)
+    }
+
+    /// returns the entry with the smallest key greater than `key`
+    /// `key` does not have to be in `self`
+    pub fn successor<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.arena.above(key, false)
+    }
+
+    /// returns the entry with `index` smaller entries,
+    /// or `None` if `index` is out of bounds
+    /// takes time proportional to the height of `self`
+    pub fn nth(&self, index: usize) -> Option<(&K, &V)> {
+        self.arena.nth(index).map(Entry::split)
+    }
+
+    /// returns the entry with `index` smaller entries,
+    /// with a mutable reference to its value,
+    /// or `None` if `index` is out of bounds
+    pub fn nth_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.arena.nth_mut(index).map(Entry::split_mut)
+    }
+
+    /// returns the number of entries with keys smaller than `key`,
+    /// whether or not `self` contains `key`
+    /// takes time proportional to the height of `self`
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.arena.rank(key)
+    }
+
+    /// returns the key that has `rank` smaller keys,
+    /// the inverse of `rank` for keys in `self`
+    pub fn select(&self, rank: usize) -> Option<&K> {
+        self.nth(rank).map(|(key, _)| key)
+    }
+
+    /// returns the entry with key `key`, which can be filled in if it is
+    /// vacant, or read, changed and removed if it is occupied
+    pub fn entry(&mut self, key: K) -> MapEntry<'_, K, V> {
+        match self.arena.find(&key) {
+            Some(index) => {
+                MapEntry::Occupied(OccupiedEntry::new(index, &mut self.arena))
+            }
+            None => MapEntry::Vacant(VacantEntry::new(key, &mut self.arena)),
+        }
+    }
+
+    /// returns an optional reference to the `Entry` with key `key`
+    pub fn get_entry<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.arena.entry(key)
+    }
+
+    pub fn node_mut<Q>(&mut self, key: &Q) -> Option<&mut Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.arena.entry_mut(key)
+    }
+
+    /// returns an iterator over the entries of `self`, in order of their keys
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(self.arena.root(), self.size())
+    }
+
+    /// returns an iterator over the entries of `self`, in order of their keys,
+    /// with mutable references to the values
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let size = self.size();
+        IterMut::new(self.arena.root_mut(), size)
+    }
+
+    /// returns an iterator over the keys of `self`, in order
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys::new(self.iter())
+    }
+
+    /// returns an iterator over the values of `self`, in order of their keys
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values::new(self.iter())
+    }
+
+    /// returns an iterator over mutable references to the values of `self`,
+    /// in order of their keys
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut::new(self.iter_mut())
+    }
+
+    /// returns an iterator over the entries of `self` whose keys lie within
+    /// `range`, in order of their keys
+    /// if the start of `range` is after its end, the iterator is empty
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Range::new(self.arena.root(), &range)
+    }
+
+    /// returns an iterator over the entries of `self` whose keys lie within
+    /// `range`, in order of their keys, with mutable references to the values
+    /// if the start of `range` is after its end, the iterator is empty
+    pub fn range_mut<Q, R>(&mut self, range: R) -> RangeMut<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        RangeMut::new(self.arena.root_mut(), &range)
+    }
+
+    /// moves all entries of `other` into `self`
+    /// if both contain an entry with the same key, `policy` decides on
+    /// its value: `KeepLeft` keeps the value in `self`, `KeepRight` takes
+    /// the one from `other`, and a closure can combine the two
+    ///
+    /// runs in linear time, and leaves `self` balanced
+    pub fn merge<P>(&mut self, other: ArenaBsTreeMap<K, V>, policy: P)
+    where
+        P: MergePolicy<K, V>,
+    {
+        let this = mem::take(self);
+        let merged = merge_sorted(this.into_iter(), other.into_iter(), policy);
+        *self = ArenaBsTreeMap::from_sorted(merged);
+    }
+
+    /// moves every entry with a key of at least `key` into a new map
+    /// and returns it
+    pub fn split_off<Q>(&mut self, key: &Q) -> ArenaBsTreeMap<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut entries: Vec<(K, V)> = mem::take(self).into_iter().collect();
+        let at = entries.partition_point(|(left, _)| left.borrow() < key);
+        let right = entries.split_off(at);
+        *self = ArenaBsTreeMap::from_sorted(entries);
+        ArenaBsTreeMap::from_sorted(right)
+    }
+
+    /// moves all entries of `other` into `self`, leaving `other` empty
+    /// if both contain an entry with the same key, the value from `other`
+    /// is kept
+    pub fn append(&mut self, other: &mut ArenaBsTreeMap<K, V>) {
+        self.merge(mem::take(other), KeepRight);
+    }
+
+    /// keeps only the entries for which `f` returns `true`
+    ///
+    /// runs in linear time, and leaves `self` balanced
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.extract_if(|key, value| !f(key, value)).for_each(drop);
+    }
+
+    /// returns an iterator that removes the entries for which `pred`
+    /// returns `true`, in order of their keys, and yields them
+    /// if the iterator is dropped early, the remaining entries are kept,
+    /// and if `pred` panics, every entry not yet yielded is kept
+    ///
+    /// the nodes are sorted in place up front and relinked, balanced, once
+    /// the iterator is dropped, which takes linear time and no allocations
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        ExtractIf::new(Extraction::new(&mut self.arena), pred)
+    }
+
+    /// builds a balanced tree out of `entries`, which must be sorted by key
+    /// and free of duplicates
+    fn from_sorted(entries: Vec<(K, V)>) -> ArenaBsTreeMap<K, V> {
+        ArenaBsTreeMap {
+            arena: Arena::from_sorted(entries),
+        }
+    }
+}
+
+impl<K, V> IntoIterator for ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self.arena.into_sorted())
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> Default for ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// sorts the entries and builds a balanced tree out of them in one go
+/// if a key appears more than once, its last value is kept
+impl<K, V> FromIterator<(K, V)> for ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut entries: Vec<(K, V)> = iter.into_iter().collect();
+        entries.sort_by(|(left, _), (right, _)| left.cmp(right));
+        entries.dedup_by(|next, kept| {
+            let duplicate = next.0 == kept.0;
+            if duplicate {
+                mem::swap(&mut next.1, &mut kept.1);
+            }
+            duplicate
+        });
+        ArenaBsTreeMap::from_sorted(entries)
+    }
+}
+
+/// merges the new entries in with `merge`, which rebuilds a balanced tree,
+/// rather than inserting them one by one into a possibly degenerate one
+impl<K, V> Extend<(K, V)> for ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.merge(ArenaBsTreeMap::from_iter(iter), KeepRight);
+    }
+}
+
+impl<'a, K, V> Extend<(&'a K, &'a V)> for ArenaBsTreeMap<K, V>
+where
+    K: Ord + Copy,
+    V: Copy,
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(&key, &value)| (key, value)));
+    }
+}
+
+impl<K, V, const N: usize> From<[(K, V); N]> for ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from(array: [(K, V); N]) -> Self {
+        Self::from_iter(array)
+    }
+}
+
+impl<K, V> From<Vec<(K, V)>> for ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from(vec: Vec<(K, V)>) -> Self {
+        Self::from_iter(vec)
+    }
+}
+
+impl<K, V> fmt::Debug for ArenaBsTreeMap<K, V>
+where
+    K: Ord + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K, V> PartialEq for ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+    V: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.size() == other.size() && self.iter().eq(other)
+    }
+}
+
+impl<K, V> Eq for ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+    V: Eq,
+{
+}
+
+impl<K, V> Hash for ArenaBsTreeMap<K, V>
+where
+    K: Ord + Hash,
+    V: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.size());
+        for entry in self {
+            entry.hash(state);
+        }
+    }
+}
+
+impl<K, V> PartialOrd for ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+    V: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<K, V> Ord for ArenaBsTreeMap<K, V>
+where
+    K: Ord,
+    V: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc_crate::format;
+    use core::ops::Bound;
+
+    // set up a tree to apply tests to
+    fn basic_tree() -> ArenaBsTreeMap<usize, &'static str> {
+        let mut tree = ArenaBsTreeMap::new();
+        tree.insert(5, " , ");
+        tree.insert(3, "hello");
+        tree.insert(6, "world");
+        tree.insert(7, "this is the largest entry");
+        tree.insert(1, "this is the smallest entry");
+        tree.insert(4, "hmmm");
+
+        tree
+    }
+
+    // checks that every node is reachable, in order, with the right sizes
+    fn check<V>(tree: &ArenaBsTreeMap<usize, V>) {
+        let keys: Vec<usize> = tree.keys().copied().collect();
+        assert_eq!(keys.len(), tree.size());
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+        for (rank, key) in keys.iter().enumerate() {
+            assert_eq!(tree.rank(key), rank);
+            assert_eq!(tree.select(rank), Some(key));
+        }
+    }
+
+    #[test]
+    fn insert() {
+        let mut tree = ArenaBsTreeMap::new();
+        tree.insert(5, " , ");
+        tree.insert(3, "hello");
+        tree.insert(7, "haha get replaced");
+        tree.insert(6, "world");
+        tree.insert(7, "hii");
+
+        assert_eq!(tree.size(), 4);
+        assert_eq!(tree.get(&7), Some(&"hii"));
+        assert_eq!(tree.get(&8), None);
+
+        tree.try_insert(7, "already exists!");
+        assert_eq!(tree.get(&7), Some(&"hii"));
+        check(&tree);
+    }
+
+    #[test]
+    fn remove() {
+        let mut tree = basic_tree();
+        assert_eq!(tree.remove(&8), None);
+
+        // 3 has two children, so 4 is spliced into its place
+        assert_eq!(tree.remove(&3), Some(Entry::new(3, "hello")));
+        assert_eq!(tree.get(&1), Some(&"this is the smallest entry"));
+        assert_eq!(tree.get(&4), Some(&"hmmm"));
+        check(&tree);
+
+        assert_eq!(tree.remove(&5), Some(Entry::new(5, " , "))); // the root
+        assert_eq!(tree.remove(&6), Some(Entry::new(6, "world")));
+        assert_eq!(tree.remove(&6), None);
+        check(&tree);
+
+        for key in [1, 4, 7] {
+            assert!(tree.remove(&key).is_some());
+            check(&tree);
+        }
+        assert_eq!(tree.size(), 0);
+    }
+
+    #[test]
+    fn remove_moves_nodes() {
+        // removing a node moves the last one in the arena into its slot,
+        // whichever part of the tree that one is in
+        let mut tree: ArenaBsTreeMap<usize, usize> = ArenaBsTreeMap::new();
+        let keys: Vec<usize> = (0..200).map(|i| i * 37 % 200).collect();
+        for &key in &keys {
+            tree.insert(key, key * 2);
+        }
+        for &key in keys.iter().step_by(3) {
+            assert_eq!(
+                tree.remove(&key).map(Entry::into_pair),
+                Some((key, key * 2))
+            );
+            assert!(!tree.contains(&key));
+            check(&tree);
+        }
+        for (key, value) in &tree {
+            assert_eq!(*value, key * 2);
+        }
+    }
+
+    #[test]
+    fn pop_first_and_last() {
+        let mut tree = basic_tree();
+        assert_eq!(tree.pop_first(), Some((1, "this is the smallest entry")));
+        assert_eq!(tree.pop_last(), Some((7, "this is the largest entry")));
+        assert_eq!(tree.pop_first(), Some((3, "hello")));
+        check(&tree);
+        assert_eq!(tree.size(), 3);
+
+        let mut empty: ArenaBsTreeMap<usize, usize> = ArenaBsTreeMap::new();
+        assert_eq!(empty.pop_first(), None);
+        assert_eq!(empty.pop_last(), None);
+    }
+
+    #[test]
+    fn bounds_and_order_statistics() {
+        let tree = basic_tree();
+        assert_eq!(tree.smallest().map(Entry::key), Some(&1));
+        assert_eq!(tree.largest().map(Entry::key), Some(&7));
+        assert_eq!(tree.floor(&2).map(Entry::key), Some(&1));
+        assert_eq!(tree.ceiling(&2).map(Entry::key), Some(&3));
+        assert_eq!(tree.predecessor(&5).map(Entry::key), Some(&4));
+        assert_eq!(tree.successor(&5).map(Entry::key), Some(&6));
+        assert_eq!(tree.upper_bound(&7), None);
+        assert_eq!(tree.nth(2), Some((&4, &"hmmm")));
+        assert_eq!(tree.nth(6), None);
+        assert_eq!(tree.rank(&5), 3);
+        assert_eq!(tree.rank(&100), 6);
+    }
+
+    #[test]
+    fn entry() {
+        let mut tree = ArenaBsTreeMap::new();
+        for word in "the cat saw the dog and the bird".split(' ') {
+            tree.entry(word)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+        assert_eq!(tree.size(), 6);
+        assert_eq!(tree.get(&"the"), Some(&3));
+        *tree.entry("fox").or_default() += 10;
+        assert_eq!(tree.get(&"fox"), Some(&10));
+
+        match tree.entry("cat") {
+            MapEntry::Occupied(entry) => {
+                assert_eq!(entry.remove_entry(), Entry::new("cat", 1))
+            }
+            MapEntry::Vacant(_) => panic!("cat is in the tree"),
+        }
+        assert!(!tree.contains(&"cat"));
+        assert_eq!(tree.size(), 6);
+    }
+
+    #[test]
+    fn iter_and_range() {
+        let mut tree = basic_tree();
+        let keys: Vec<usize> = tree.keys().copied().collect();
+        assert_eq!(keys, [1, 3, 4, 5, 6, 7]);
+        assert_eq!(
+            tree.iter().next_back(),
+            Some((&7, &"this is the largest entry"))
+        );
+
+        let range: Vec<usize> = tree.range(3..6).map(|(key, _)| *key).collect();
+        assert_eq!(range, [3, 4, 5]);
+        let range: Vec<usize> = tree
+            .range((Bound::Excluded(5), Bound::Unbounded))
+            .map(|(key, _)| *key)
+            .collect();
+        assert_eq!(range, [6, 7]);
+
+        for (_, value) in tree.range_mut(..=3) {
+            *value = "changed";
+        }
+        for value in tree.values_mut().skip(5) {
+            *value = "last";
+        }
+        let values: Vec<&str> = tree.values().copied().collect();
+        assert_eq!(
+            values,
+            ["changed", "changed", "hmmm", " , ", "world", "last"]
+        );
+
+        let pairs: Vec<(usize, &str)> = tree.into_iter().rev().collect();
+        assert_eq!(pairs[0], (7, "last"));
+        assert_eq!(pairs.len(), 6);
+    }
+
+    #[test]
+    fn clone_is_independent() {
+        let mut tree = basic_tree();
+        let copy = tree.clone();
+        tree.insert(2, "two");
+        tree.remove(&5);
+        *tree.get_mut(&1).unwrap() = "one";
+        assert_eq!(copy, basic_tree());
+        assert_eq!(copy.get(&1), Some(&"this is the smallest entry"));
+        assert!(!copy.contains(&2));
+        check(&copy);
+    }
+
+    #[test]
+    fn merge_split_and_retain() {
+        let mut left: ArenaBsTreeMap<usize, usize> =
+            (0..10).map(|key| (key, key)).collect();
+        let right: ArenaBsTreeMap<usize, usize> =
+            (5..15).map(|key| (key, key * 10)).collect();
+        left.merge(right, |_: &usize, l: usize, r: usize| l + r);
+        assert_eq!(left.size(), 15);
+        assert_eq!(left.get(&7), Some(&77));
+        check(&left);
+
+        let mut upper = left.split_off(&10);
+        assert_eq!(left.size(), 10);
+        assert_eq!(upper.size(), 5);
+        check(&upper);
+
+        upper.retain(|key, _| key % 2 == 0);
+        assert_eq!(upper.keys().copied().collect::<Vec<_>>(), [10, 12, 14]);
+        let extracted: Vec<usize> = left
+            .extract_if(|key, _| *key < 3)
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(extracted, [0, 1, 2]);
+        check(&left);
+        let mut extracted = left.extract_if(|key, _| key % 3 == 0);
+        assert_eq!(extracted.next(), Some((3, 3)));
+        drop(extracted);
+        assert_eq!(left.size(), 6);
+        assert!(left.keys().copied().eq([4, 5, 6, 7, 8, 9]));
+        check(&left);
+        left.append(&mut upper);
+        assert!(upper.size() == 0);
+        check(&left);
+
+        left.clear();
+        assert_eq!(left.size(), 0);
+        assert_eq!(left.iter().next(), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn extract_if_survives_a_panicking_predicate() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut tree: ArenaBsTreeMap<usize, usize> =
+            (0..10).map(|key| (key, key)).collect();
+        let extract = catch_unwind(AssertUnwindSafe(|| {
+            let extracted = tree.extract_if(|key, _| {
+                assert_ne!(*key, 5);
+                key % 2 == 0
+            });
+            extracted.for_each(drop);
+        }));
+        assert!(extract.is_err());
+        assert!(tree.keys().copied().eq([1, 3, 5, 6, 7, 8, 9]));
+        check(&tree);
+    }
+
+    #[test]
+    fn std_traits() {
+        let tree =
+            ArenaBsTreeMap::from([(3, 'c'), (1, 'a'), (2, 'b'), (1, 'z')]);
+        assert_eq!(tree.get(&1), Some(&'z'));
+        assert_eq!(format!("{tree:?}"), "{1: 'z', 2: 'b', 3: 'c'}");
+        let mut other = ArenaBsTreeMap::default();
+        other.extend([(&2, &'b'), (&3, &'c'), (&1, &'z')]);
+        assert_eq!(tree, other);
+        other.insert(4, 'd');
+        assert!(tree < other);
+    }
+}
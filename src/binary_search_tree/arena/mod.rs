@@ -0,0 +1,5 @@
+//! a binary search tree whose nodes live in a single arena
+mod entry;
+mod iter;
+pub mod map;
+mod node;
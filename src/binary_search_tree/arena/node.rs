@@ -0,0 +1,732 @@
+//! the nodes of an `ArenaBsTreeMap`
+//! instead of each living in its own `Box`, the nodes sit side by side in
+//! one `Vec` and refer to their children by index
+//! the `Vec` never has holes: removing a node moves the last one into its
+//! place, and whoever pointed at the last one is pointed at its new index
+use crate::entry::Entry;
+use crate::traversal::{Expand, Split, Step};
+use alloc_crate::collections::VecDeque;
+use alloc_crate::vec;
+use alloc_crate::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::marker::PhantomData;
+use core::mem;
+use core::ptr::NonNull;
+
+/// the index of a possibly absent node
+pub type Link = Option<u32>;
+
+/// `left` and `right` are the indices of the children
+/// `size` is the number of entries in the subtree rooted at `self`,
+/// including `self`
+#[derive(Clone)]
+pub struct Node<K, V> {
+    entry: Entry<K, V>,
+    size: usize,
+    left: Link,
+    right: Link,
+}
+
+/// where a link is kept: in the arena as its root, or in a node
+#[derive(Clone, Copy)]
+enum Place {
+    Root,
+    Left(u32),
+    Right(u32),
+}
+
+/// every node of a tree, and the index of its root
+#[derive(Clone)]
+pub struct Arena<K, V> {
+    nodes: Vec<Node<K, V>>,
+    root: Link,
+}
+
+impl<K, V> Node<K, V> {
+    /// consumes `self`, returning its key and value
+    pub fn into_pair(self) -> (K, V) {
+        self.entry.into_pair()
+    }
+}
+
+impl<K, V> Arena<K, V>
+where
+    K: Ord,
+{
+    pub const fn new() -> Arena<K, V> {
+        Arena {
+            nodes: Vec::new(),
+            root: None,
+        }
+    }
+
+    /// builds a balanced tree out of `entries`, which must be sorted by key
+    /// and free of duplicates
+    pub fn from_sorted(entries: Vec<(K, V)>) -> Arena<K, V> {
+        let len = entries.len();
+        let mut arena = Arena {
+            nodes: Vec::with_capacity(len),
+            root: None,
+        };
+        arena.root = arena.build(len, &mut entries.into_iter());
+        arena
+    }
+
+    /// builds a balanced tree out of `nodes`, which must be sorted by key
+    /// and free of duplicates, relinking them where they lie
+    pub fn from_sorted_nodes(nodes: Vec<Node<K, V>>) -> Arena<K, V> {
+        let len = nodes.len();
+        let mut arena = Arena { nodes, root: None };
+        arena.root = arena.relink(0, len);
+        arena
+    }
+
+    /// links the `len` nodes from index `start` on into a balanced subtree,
+    /// and returns its root
+    fn relink(&mut self, start: usize, len: usize) -> Link {
+        if len == 0 {
+            return None;
+        }
+        let middle = start + len / 2;
+        let left = self.relink(start, len / 2);
+        let right = self.relink(middle + 1, len - len / 2 - 1);
+        let node = &mut self.nodes[middle];
+        node.size = len;
+        node.left = left;
+        node.right = right;
+        Some(middle as u32)
+    }
+
+    /// adds the first `len` entries of `entries` as a balanced subtree,
+    /// and returns its root
+    fn build(
+        &mut self,
+        len: usize,
+        entries: &mut impl Iterator<Item = (K, V)>,
+    ) -> Link {
+        if len == 0 {
+            return None;
+        }
+        let left = self.build(len / 2, entries);
+        let (key, value) = entries.next()?;
+        let index = self.push(Node {
+            entry: Entry::new(key, value),
+            size: len,
+            left,
+            right: None,
+        });
+        let right = self.build(len - len / 2 - 1, entries);
+        self.node_mut(index).right = right;
+        Some(index)
+    }
+
+    /// returns the number of entries
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// removes every node at once
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.root = None;
+    }
+
+    fn node(&self, index: u32) -> &Node<K, V> {
+        &self.nodes[index as usize]
+    }
+
+    fn node_mut(&mut self, index: u32) -> &mut Node<K, V> {
+        &mut self.nodes[index as usize]
+    }
+
+    fn link(&self, place: Place) -> Link {
+        match place {
+            Place::Root => self.root,
+            Place::Left(index) => self.node(index).left,
+            Place::Right(index) => self.node(index).right,
+        }
+    }
+
+    fn set_link(&mut self, place: Place, link: Link) {
+        match place {
+            Place::Root => self.root = link,
+            Place::Left(index) => self.node_mut(index).left = link,
+            Place::Right(index) => self.node_mut(index).right = link,
+        }
+    }
+
+    /// returns the number of entries in the subtree rooted at `link`
+    fn size_of(&self, link: Link) -> usize {
+        link.map_or(0, |index| self.node(index).size)
+    }
+
+    /// appends `node` to the arena and returns its index
+    fn push(&mut self, node: Node<K, V>) -> u32 {
+        let index = u32::try_from(self.nodes.len())
+            .expect("an arena holds fewer than 2^32 nodes");
+        self.nodes.push(node);
+        index
+    }
+
+    /// returns the place of the link that holds the entry with key `key`,
+    /// or of the empty link where it would be inserted
+    fn place_of<Q>(&self, key: &Q) -> Place
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut place = Place::Root;
+        while let Some(index) = self.link(place) {
+            place = match key.cmp(self.node(index).entry.key().borrow()) {
+                Ordering::Less => Place::Left(index),
+                Ordering::Greater => Place::Right(index),
+                Ordering::Equal => return place,
+            };
+        }
+        place
+    }
+
+    /// like `place_of`, but increments the size of every node above the
+    /// link, for a key that is not in the tree yet
+    fn descend(&mut self, key: &K) -> Place {
+        let mut place = Place::Root;
+        while let Some(index) = self.link(place) {
+            let node = self.node_mut(index);
+            place = match key.cmp(node.entry.key()) {
+                Ordering::Less => Place::Left(index),
+                Ordering::Greater => Place::Right(index),
+                Ordering::Equal => return place,
+            };
+            node.size += 1;
+        }
+        place
+    }
+
+    /// returns the index of the node with key `key`
+    pub fn find<Q>(&self, key: &Q) -> Link
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.link(self.place_of(key))
+    }
+
+    /// returns the entry of the node at `index`
+    pub fn entry_at(&self, index: u32) -> &Entry<K, V> {
+        &self.node(index).entry
+    }
+
+    /// returns a mutable reference to the entry of the node at `index`
+    pub fn entry_at_mut(&mut self, index: u32) -> &mut Entry<K, V> {
+        &mut self.node_mut(index).entry
+    }
+
+    /// returns a reference to the entry with key `key`
+    pub fn entry<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).map(|index| self.entry_at(index))
+    }
+
+    /// returns a mutable reference to the entry with key `key`
+    pub fn entry_mut<Q>(&mut self, key: &Q) -> Option<&mut Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).map(|index| self.entry_at_mut(index))
+    }
+
+    /// adds `entry` to the tree
+    /// if an entry with the same key already exists, its value is only
+    /// replaced if `overwrite` is set
+    /// returns whether a new node was added, and the index of the node
+    pub fn insert(
+        &mut self,
+        entry: Entry<K, V>,
+        overwrite: bool,
+    ) -> (bool, u32) {
+        if let Some(index) = self.find(entry.key()) {
+            if overwrite {
+                self.entry_at_mut(index).value = entry.value;
+            }
+            return (false, index);
+        }
+        let place = self.descend(entry.key());
+        let index = self.push(Node {
+            entry,
+            size: 1,
+            left: None,
+            right: None,
+        });
+        self.set_link(place, Some(index));
+        (true, index)
+    }
+
+    /// removes the entry with key `key` and returns it
+    /// returns `None` if no such entry exists
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.find(key)?;
+        Some(self.remove_at(index))
+    }
+
+    /// removes the node at `index` and returns its entry
+    /// a node with two children is replaced by its in-order successor
+    pub fn remove_at(&mut self, index: u32) -> Entry<K, V> {
+        let place = self.descend_to(index);
+        let node = self.node(index);
+        let (left, right, size) = (node.left, node.right, node.size);
+        let replacement = match (left, right) {
+            (None, None) => None,
+            (Some(child), None) | (None, Some(child)) => Some(child),
+            (Some(left), Some(_)) => {
+                let successor = self.take_smallest(Place::Right(index));
+                let right = self.node(index).right;
+                if let Some(successor) = successor {
+                    let node = self.node_mut(successor);
+                    node.size = size - 1;
+                    node.left = Some(left);
+                    node.right = right;
+                }
+                successor
+            }
+        };
+        self.set_link(place, replacement);
+        self.free(index)
+    }
+
+    /// returns the place of the link that holds the node at `index`,
+    /// decrementing the size of every node above it
+    fn descend_to(&mut self, index: u32) -> Place {
+        let mut place = Place::Root;
+        while let Some(current) = self.link(place) {
+            if current == index {
+                break;
+            }
+            let key = self.node(index).entry.key();
+            place = match key.cmp(self.node(current).entry.key()) {
+                Ordering::Less => Place::Left(current),
+                _ => Place::Right(current),
+            };
+            self.node_mut(current).size -= 1;
+        }
+        place
+    }
+
+    /// removes the node at `index`, which must already be detached from the
+    /// tree, and returns its entry
+    /// the last node is moved into its place
+    fn free(&mut self, index: u32) -> Entry<K, V> {
+        let last = self.nodes.len() as u32 - 1;
+        if index != last {
+            let place = self.place_of(self.node(last).entry.key());
+            self.set_link(place, Some(index));
+        }
+        self.nodes.swap_remove(index as usize).entry
+    }
+
+    /// detaches the node with the smallest key from the subtree at `place`,
+    /// putting its right child in its place, and returns its index
+    fn take_smallest(&mut self, mut place: Place) -> Link {
+        loop {
+            let index = self.link(place)?;
+            let node = self.node_mut(index);
+            if node.left.is_none() {
+                let right = node.right.take();
+                self.set_link(place, right);
+                return Some(index);
+            }
+            node.size -= 1;
+            place = Place::Left(index);
+        }
+    }
+
+    /// detaches the node with the largest key from the subtree at `place`,
+    /// putting its left child in its place, and returns its index
+    fn take_largest(&mut self, mut place: Place) -> Link {
+        loop {
+            let index = self.link(place)?;
+            let node = self.node_mut(index);
+            if node.right.is_none() {
+                let left = node.left.take();
+                self.set_link(place, left);
+                return Some(index);
+            }
+            node.size -= 1;
+            place = Place::Right(index);
+        }
+    }
+
+    /// removes the smallest entry and returns it
+    pub fn pop_smallest(&mut self) -> Option<Entry<K, V>> {
+        let index = self.take_smallest(Place::Root)?;
+        Some(self.free(index))
+    }
+
+    /// removes the largest entry and returns it
+    pub fn pop_largest(&mut self) -> Option<Entry<K, V>> {
+        let index = self.take_largest(Place::Root)?;
+        Some(self.free(index))
+    }
+
+    /// returns the index of the node with the smallest key
+    fn smallest_index(&self) -> Link {
+        let mut index = self.root?;
+        while let Some(left) = self.node(index).left {
+            index = left;
+        }
+        Some(index)
+    }
+
+    /// returns the index of the node with the largest key
+    fn largest_index(&self) -> Link {
+        let mut index = self.root?;
+        while let Some(right) = self.node(index).right {
+            index = right;
+        }
+        Some(index)
+    }
+
+    /// returns a reference to the smallest entry
+    pub fn smallest(&self) -> Option<&Entry<K, V>> {
+        self.smallest_index().map(|index| self.entry_at(index))
+    }
+
+    /// returns a mutable reference to the smallest entry
+    pub fn smallest_mut(&mut self) -> Option<&mut Entry<K, V>> {
+        self.smallest_index().map(|index| self.entry_at_mut(index))
+    }
+
+    /// returns a reference to the largest entry
+    pub fn largest(&self) -> Option<&Entry<K, V>> {
+        self.largest_index().map(|index| self.entry_at(index))
+    }
+
+    /// returns a mutable reference to the largest entry
+    pub fn largest_mut(&mut self) -> Option<&mut Entry<K, V>> {
+        self.largest_index().map(|index| self.entry_at_mut(index))
+    }
+
+    /// returns the index of the node with `rank` smaller entries
+    fn nth_index(&self, mut rank: usize) -> Link {
+        let mut index = self.root?;
+        loop {
+            let node = self.node(index);
+            let left = self.size_of(node.left);
+            index = match rank.cmp(&left) {
+                Ordering::Less => node.left?,
+                Ordering::Equal => return Some(index),
+                Ordering::Greater => {
+                    rank -= left + 1;
+                    node.right?
+                }
+            };
+        }
+    }
+
+    /// returns the entry with `rank` smaller entries
+    pub fn nth(&self, rank: usize) -> Option<&Entry<K, V>> {
+        self.nth_index(rank).map(|index| self.entry_at(index))
+    }
+
+    /// returns a mutable reference to the entry with `rank` smaller entries
+    pub fn nth_mut(&mut self, rank: usize) -> Option<&mut Entry<K, V>> {
+        self.nth_index(rank).map(|index| self.entry_at_mut(index))
+    }
+
+    /// returns the number of entries with keys smaller than `key`
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut rank = 0;
+        let mut link = self.root;
+        while let Some(index) = link {
+            let node = self.node(index);
+            link = match key.cmp(node.entry.key().borrow()) {
+                Ordering::Less => node.left,
+                Ordering::Equal => return rank + self.size_of(node.left),
+                Ordering::Greater => {
+                    rank += self.size_of(node.left) + 1;
+                    node.right
+                }
+            };
+        }
+        rank
+    }
+
+    /// returns the entry with the smallest key greater than `key`,
+    /// or equal to it if `inclusive` is set
+    pub fn above<Q>(&self, key: &Q, inclusive: bool) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut found = None;
+        let mut link = self.root;
+        while let Some(index) = link {
+            let node = self.node(index);
+            link = match key.cmp(node.entry.key().borrow()) {
+                Ordering::Less => {
+                    found = Some(&node.entry);
+                    node.left
+                }
+                Ordering::Equal if inclusive => return Some(&node.entry),
+                _ => node.right,
+            };
+        }
+        found
+    }
+
+    /// returns the entry with the largest key smaller than `key`,
+    /// or equal to it if `inclusive` is set
+    pub fn below<Q>(&self, key: &Q, inclusive: bool) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut found = None;
+        let mut link = self.root;
+        while let Some(index) = link {
+            let node = self.node(index);
+            link = match key.cmp(node.entry.key().borrow()) {
+                Ordering::Greater => {
+                    found = Some(&node.entry);
+                    node.right
+                }
+                Ordering::Equal if inclusive => return Some(&node.entry),
+                _ => node.left,
+            };
+        }
+        found
+    }
+
+    /// consumes `self`, returning its nodes in order of their keys
+    /// the nodes are moved into order in place, in linear time
+    pub fn into_sorted(self) -> Vec<Node<K, V>> {
+        // the in-order position of every node
+        let mut positions = vec![0; self.nodes.len()];
+        let mut pending = Vec::new();
+        let mut link = self.root;
+        let mut position = 0;
+        loop {
+            while let Some(index) = link {
+                pending.push(index);
+                link = self.node(index).left;
+            }
+            let Some(index) = pending.pop() else { break };
+            positions[index as usize] = position;
+            position += 1;
+            link = self.node(index).right;
+        }
+        let mut nodes = self.nodes;
+        for index in 0..nodes.len() {
+            while positions[index] != index {
+                let target = positions[index];
+                nodes.swap(index, target);
+                positions.swap(index, target);
+            }
+        }
+        nodes
+    }
+
+    /// returns a handle to the root, for traversals
+    pub fn root(&self) -> Option<NodeRef<'_, K, V>> {
+        self.root.map(|index| NodeRef { arena: self, index })
+    }
+
+    /// returns a handle to the root with mutable access to the values,
+    /// for traversals
+    pub fn root_mut(&mut self) -> Option<NodeMut<'_, K, V>> {
+        let index = self.root?;
+        Some(NodeMut {
+            nodes: NonNull::from(&mut self.nodes[..]).cast(),
+            index,
+            marker: PhantomData,
+        })
+    }
+}
+
+/// takes the nodes of an arena out in order, and puts the ones that are
+/// left back together into a balanced tree when dropped, so that the arena
+/// stays whole even if a user callback panics in between
+/// `nodes` holds the `unvisited` nodes that have yet to be looked at,
+/// followed by the nodes that are kept, both in ascending order
+pub struct Extraction<'a, K, V>
+where
+    K: Ord,
+{
+    arena: &'a mut Arena<K, V>,
+    nodes: VecDeque<Node<K, V>>,
+    unvisited: usize,
+}
+
+impl<'a, K, V> Extraction<'a, K, V>
+where
+    K: Ord,
+{
+    pub fn new(arena: &'a mut Arena<K, V>) -> Extraction<'a, K, V> {
+        let nodes =
+            VecDeque::from(mem::replace(arena, Arena::new()).into_sorted());
+        let unvisited = nodes.len();
+        Extraction {
+            arena,
+            nodes,
+            unvisited,
+        }
+    }
+
+    /// walks the unvisited nodes from the smallest, keeping them until
+    /// `pred` returns `true` for one, which is removed instead
+    /// returns the entry of the removed node
+    ///
+    /// `pred` only ever sees a node that is still part of the arena, and
+    /// kept nodes only take the room of removed ones, so nothing allocates
+    pub fn extract_next<F>(&mut self, mut pred: F) -> Option<(K, V)>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        while self.unvisited > 0 {
+            let entry = &mut self.nodes.front_mut()?.entry;
+            let (key, value) = entry.split_mut();
+            let extract = pred(key, value);
+            self.unvisited -= 1;
+            let node = self.nodes.pop_front()?;
+            if extract {
+                return Some(node.into_pair());
+            }
+            self.nodes.push_back(node);
+        }
+        None
+    }
+}
+
+impl<K, V> Drop for Extraction<'_, K, V>
+where
+    K: Ord,
+{
+    fn drop(&mut self) {
+        let mut nodes = mem::take(&mut self.nodes);
+        nodes.rotate_left(self.unvisited);
+        *self.arena = Arena::from_sorted_nodes(Vec::from(nodes));
+    }
+}
+
+/// a handle to a node of an `Arena`
+pub struct NodeRef<'a, K, V> {
+    arena: &'a Arena<K, V>,
+    index: u32,
+}
+
+/// a handle to a node of an `Arena` that gives mutable access to values
+/// a traversal visits every node at most once, so no two handles ever hand
+/// out references into the same node
+pub struct NodeMut<'a, K, V> {
+    nodes: NonNull<Node<K, V>>,
+    index: u32,
+    marker: PhantomData<&'a mut Node<K, V>>,
+}
+
+// SAFETY: `NodeMut` only hands out unique references into disjoint nodes,
+// like `&mut Node` would
+unsafe impl<K: Send, V: Send> Send for NodeMut<'_, K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for NodeMut<'_, K, V> {}
+
+impl<'a, K, V> Expand for NodeRef<'a, K, V>
+where
+    K: Ord,
+{
+    type Entry = (&'a K, &'a V);
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let (left, entry, right) = self.split();
+        [
+            left.map(Step::Node),
+            Some(Step::Entry(entry)),
+            right.map(Step::Node),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl<K, V> Split for NodeRef<'_, K, V>
+where
+    K: Ord,
+{
+    type Key = K;
+
+    fn split(self) -> (Option<Self>, Self::Entry, Option<Self>) {
+        let arena = self.arena;
+        let node = arena.node(self.index);
+        let handle = |index| NodeRef { arena, index };
+        (
+            node.left.map(handle),
+            node.entry.split(),
+            node.right.map(handle),
+        )
+    }
+
+    fn key(entry: &Self::Entry) -> &K {
+        entry.0
+    }
+}
+
+impl<'a, K, V> Expand for NodeMut<'a, K, V>
+where
+    K: Ord,
+{
+    type Entry = (&'a K, &'a mut V);
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let (left, entry, right) = self.split();
+        [
+            left.map(Step::Node),
+            Some(Step::Entry(entry)),
+            right.map(Step::Node),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl<K, V> Split for NodeMut<'_, K, V>
+where
+    K: Ord,
+{
+    type Key = K;
+
+    fn split(self) -> (Option<Self>, Self::Entry, Option<Self>) {
+        let nodes = self.nodes;
+        let handle = |index| NodeMut {
+            nodes,
+            index,
+            marker: PhantomData,
+        };
+        // SAFETY: `index` is a node of the arena, which is borrowed mutably
+        // for `'a`, and this is the only handle to that node, so the
+        // reference to its entry is unique
+        let node = unsafe { &mut *nodes.as_ptr().add(self.index as usize) };
+        (
+            node.left.map(handle),
+            node.entry.split_mut(),
+            node.right.map(handle),
+        )
+    }
+
+    fn key(entry: &Self::Entry) -> &K {
+        entry.0
+    }
+}
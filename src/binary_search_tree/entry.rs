@@ -0,0 +1,229 @@
+//! a view into a single entry of a `BsTreeMap`, which may or may not exist
+//!
+//! looking an entry up descends the tree once, noting the rank of the key
+//! on the way, and an occupied entry keeps the node it found
+//! only adding or removing the entry descends again, by that rank, to
+//! update the sizes of the nodes above it
+use super::node::{Link, Node};
+use crate::alloc::{Allocator, Global};
+use crate::entry::Entry;
+use core::mem;
+use core::ptr::NonNull;
+
+/// an entry of a `BsTreeMap`, as returned by `BsTreeMap::entry`
+pub enum MapEntry<'a, K, V, A = Global>
+where
+    K: Ord,
+    A: Allocator,
+{
+    Occupied(OccupiedEntry<'a, K, V, A>),
+    Vacant(VacantEntry<'a, K, V, A>),
+}
+
+/// an entry that exists in the tree
+/// `node` is the node of the entry, `rank` the number of smaller keys,
+/// `root` the root of the tree and `size` its number of entries
+pub struct OccupiedEntry<'a, K, V, A = Global>
+where
+    K: Ord,
+    A: Allocator,
+{
+    node: NonNull<Node<K, V, A>>,
+    rank: usize,
+    root: &'a mut Link<K, V, A>,
+    size: &'a mut usize,
+}
+
+// SAFETY: the entry has exclusive access to the tree, like the
+// `&mut BsTreeMap` it comes from
+unsafe impl<K, V, A> Send for OccupiedEntry<'_, K, V, A>
+where
+    K: Ord + Send,
+    V: Send,
+    A: Allocator + Send,
+{
+}
+
+unsafe impl<K, V, A> Sync for OccupiedEntry<'_, K, V, A>
+where
+    K: Ord + Sync,
+    V: Sync,
+    A: Allocator + Sync,
+{
+}
+
+/// an entry that does not exist in the tree yet
+/// `rank` is the number of smaller keys, and `alloc` the allocator of
+/// the tree
+pub struct VacantEntry<'a, K, V, A = Global>
+where
+    K: Ord,
+    A: Allocator,
+{
+    key: K,
+    rank: usize,
+    root: &'a mut Link<K, V, A>,
+    size: &'a mut usize,
+    alloc: &'a A,
+}
+
+impl<'a, K, V, A> MapEntry<'a, K, V, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    /// looks `key` up in the tree rooted at `root`, of `size` entries,
+    /// descending it once
+    pub(super) fn new(
+        key: K,
+        root: &'a mut Link<K, V, A>,
+        size: &'a mut usize,
+        alloc: &'a A,
+    ) -> MapEntry<'a, K, V, A> {
+        let (rank, node) = Node::search(root, &key);
+        match node.map(NonNull::from) {
+            Some(node) => MapEntry::Occupied(OccupiedEntry {
+                node,
+                rank,
+                root,
+                size,
+            }),
+            None => MapEntry::Vacant(VacantEntry {
+                key,
+                rank,
+                root,
+                size,
+                alloc,
+            }),
+        }
+    }
+}
+
+impl<'a, K, V, A> MapEntry<'a, K, V, A>
+where
+    K: Ord,
+    A: Allocator + Clone,
+{
+    /// returns a reference to the key of the entry
+    pub fn key(&self) -> &K {
+        match self {
+            MapEntry::Occupied(entry) => entry.key(),
+            MapEntry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// inserts `default` if the entry is vacant
+    /// returns a mutable reference to the value
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// inserts the result of `default` if the entry is vacant
+    /// returns a mutable reference to the value
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            MapEntry::Occupied(entry) => entry.into_mut(),
+            MapEntry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// calls `f` on the value if the entry is occupied
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let MapEntry::Occupied(ref mut entry) = self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V, A> MapEntry<'a, K, V, A>
+where
+    K: Ord,
+    V: Default,
+    A: Allocator + Clone,
+{
+    /// inserts the default value if the entry is vacant
+    /// returns a mutable reference to the value
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K, V, A> OccupiedEntry<'a, K, V, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    /// returns a reference to the key of the entry
+    pub fn key(&self) -> &K {
+        self.get_node().key()
+    }
+
+    /// returns a reference to the value of the entry
+    pub fn get(&self) -> &V {
+        self.get_node().value()
+    }
+
+    /// returns a mutable reference to the value of the entry
+    pub fn get_mut(&mut self) -> &mut V {
+        // SAFETY: as in `get_node`, and `self` is borrowed mutably
+        unsafe { self.node.as_mut() }.value_mut()
+    }
+
+    /// turns the entry into a mutable reference to its value
+    pub fn into_mut(mut self) -> &'a mut V {
+        // SAFETY: as in `get_node`, and the tree is borrowed mutably for
+        // `'a`, which `self` gives up
+        unsafe { self.node.as_mut() }.value_mut()
+    }
+
+    /// sets the value of the entry to `value`, and returns the old value
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// removes the entry from the tree and returns it
+    pub fn remove_entry(self) -> Entry<K, V> {
+        *self.size -= 1;
+        let entry = Node::remove_nth(self.root, self.rank);
+        // SAFETY: `rank` is the rank of `node`, which is still in the tree,
+        // so there is an entry to remove
+        unsafe { entry.unwrap_unchecked() }
+    }
+
+    fn get_node(&self) -> &Node<K, V, A> {
+        // SAFETY: `node` was found in the tree, which nothing but `self`
+        // can change, and `self` only changes values until it is consumed
+        unsafe { self.node.as_ref() }
+    }
+}
+
+impl<'a, K, V, A> VacantEntry<'a, K, V, A>
+where
+    K: Ord,
+    A: Allocator + Clone,
+{
+    /// returns a reference to the key that would be used for the entry
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// takes back ownership of the key
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// inserts the entry into the tree with value `value`
+    /// returns a mutable reference to the value
+    pub fn insert(self, value: V) -> &'a mut V {
+        *self.size += 1;
+        let entry = Entry::new(self.key, value);
+        Node::insert_nth(self.root, self.rank, entry, self.alloc).value_mut()
+    }
+}
@@ -0,0 +1,74 @@
+//! in-order iterators over `BsTreeMap`
+use super::node::{Node, Subtree};
+use super::vine::Rebuild;
+use crate::alloc::{Allocator, Global};
+use crate::iter;
+
+/// an iterator over the entries of a `BsTreeMap`, in order of their keys
+pub type Iter<'a, K, V, A = Global> = iter::Iter<&'a Node<K, V, A>, &'a A>;
+
+/// a mutable iterator over the entries of a `BsTreeMap`,
+/// in order of their keys
+pub type IterMut<'a, K, V, A = Global> =
+    iter::Iter<&'a mut Node<K, V, A>, &'a A>;
+
+/// an owning iterator over the entries of a `BsTreeMap`,
+/// in order of their keys
+pub type IntoIter<K, V, A = Global> = iter::IntoIter<Subtree<K, V, A>, A>;
+
+/// an iterator over the keys of a `BsTreeMap`, in order
+pub type Keys<'a, K, V, A = Global> = iter::Keys<Iter<'a, K, V, A>>;
+
+/// an iterator over the values of a `BsTreeMap`, in order of their keys
+pub type Values<'a, K, V, A = Global> = iter::Values<Iter<'a, K, V, A>>;
+
+/// a mutable iterator over the values of a `BsTreeMap`,
+/// in order of their keys
+pub type ValuesMut<'a, K, V, A = Global> = iter::Values<IterMut<'a, K, V, A>>;
+
+/// an iterator over the entries of a `BsTreeMap` whose keys lie within
+/// a range, in order of their keys
+pub type Range<'a, K, V, A = Global> = iter::Range<&'a Node<K, V, A>, &'a A>;
+
+/// a mutable iterator over the entries of a `BsTreeMap` whose keys lie
+/// within a range, in order of their keys
+pub type RangeMut<'a, K, V, A = Global> =
+    iter::Range<&'a mut Node<K, V, A>, &'a A>;
+
+/// an iterator that removes the entries of a `BsTreeMap` matching a
+/// predicate as it goes, and returns them, in order of their keys
+/// entries it has not reached when dropped stay in the map
+pub struct ExtractIf<'a, K, V, F, A = Global>
+where
+    K: Ord,
+    A: Allocator,
+{
+    rebuild: Rebuild<'a, K, V, A>,
+    pred: F,
+}
+
+impl<'a, K, V, F, A> ExtractIf<'a, K, V, F, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    pub(super) fn new(
+        rebuild: Rebuild<'a, K, V, A>,
+        pred: F,
+    ) -> ExtractIf<'a, K, V, F, A> {
+        ExtractIf { rebuild, pred }
+    }
+}
+
+impl<K, V, F, A> Iterator for ExtractIf<'_, K, V, F, A>
+where
+    K: Ord,
+    F: FnMut(&K, &mut V) -> bool,
+    A: Allocator,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rebuild.extract_next(&mut self.pred)
+    }
+}
@@ -1,31 +1,74 @@
 //! a binary search tree with keys and values
 //! note: this tree makes no attempts to maintain balance
 
-use std::cmp::Ordering;
-use super::node::Node;
+use super::node::{Link, Node, Subtree};
+use super::vine::{Rebuild, Vine};
+use crate::alloc::{Allocator, Global};
+use crate::boxed::AllocBox;
 use crate::entry::Entry;
-pub struct BsTreeMap<K, V>
+use alloc_crate::boxed::Box;
+use alloc_crate::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::mem;
+use core::ops::RangeBounds;
+
+pub use super::entry::{MapEntry, OccupiedEntry, VacantEntry};
+pub use super::iter::{
+    ExtractIf, IntoIter, Iter, IterMut, Keys, Range, RangeMut, Values,
+    ValuesMut,
+};
+pub use crate::entry::Value;
+pub use crate::merge::{KeepLeft, KeepRight, MergePolicy};
+
+/// every node is allocated from `alloc`
+pub struct BsTreeMap<K, V, A = Global>
 where
     K: Ord,
-    V: ?Sized,
+    V: Value + ?Sized,
+    A: Allocator,
 {
-    root: Option<Box<Node<K, V>>>,
+    root: Link<K, V, A>,
     size: usize,
+    alloc: A,
 }
 
 impl<K, V> BsTreeMap<K, V>
 where
     K: Ord,
+    V: Value + ?Sized,
 {
-    /// creates an empty BsTree<T>
+    /// creates an empty `BsTreeMap`
     #[inline]
     pub const fn new() -> BsTreeMap<K, V> {
+        BsTreeMap::new_in(Global)
+    }
+}
+
+impl<K, V, A> BsTreeMap<K, V, A>
+where
+    K: Ord,
+    V: Value + ?Sized,
+    A: Allocator,
+{
+    /// creates an empty `BsTreeMap` whose nodes come from `alloc`
+    #[inline]
+    pub const fn new_in(alloc: A) -> BsTreeMap<K, V, A> {
         BsTreeMap {
             root: None,
             size: 0,
+            alloc,
         }
     }
 
+    /// returns a reference to the allocator of `self`
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
     /// returns the number of entries in `self`
     #[inline]
     pub fn size(&self) -> usize {
@@ -35,58 +78,70 @@ where
     /// returns `true` if `self` contains an node with key `key`
     /// otherwise returns `false`
     #[inline]
-    pub fn contains(&self, key: &K) -> bool {
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         match self.root {
             Some(ref node) => node.contains(key),
             None => false,
         }
     }
 
-    /// merges `other` with `self`
-    pub fn merge(&mut self, other: BsTreeMap<K, V>) {
-        self.size += other.size;
-        todo!();
+    /// removes all entries from `self`
+    pub fn clear(&mut self) {
+        if let Some(root) = self.root.take() {
+            Node::dismantle(root);
+        }
+        self.size = 0;
     }
 
-    /// removes the node with the given key
-    pub fn remove(&mut self, key: &K) -> bool {
-        todo!();
+    /// removes the entry with key `key` and returns it
+    /// returns `None` if no such entry exists
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let entry = Node::remove(&mut self.root, key)?;
+        self.size -= 1;
+        Some(entry)
     }
 
     /// sets the value of the key with key `key` to `value`
     /// if `key` already exists, the value is overridden
-    #[inline]
-    pub fn insert(&mut self, key: K, value: V) {
-        match self.root {
-            Some(ref mut node) => {
-                if node.insert(key, value) {
-                    self.size += 1;
-                }
-            }
-            None => {
-                self.root = Some(Box::from(Node::new(key, value)));
-                self.size += 1;
-            }
+    /// unlike `insert`, this works for unsized values
+    pub fn insert_boxed(&mut self, key: K, value: Box<V>)
+    where
+        A: Clone,
+    {
+        let entry = Entry::from_box(key, value);
+        if Node::insert(&mut self.root, entry, &self.alloc) {
+            self.size += 1;
         }
     }
 
-    pub fn try_insert(&mut self, key: K, value: V) {
-        match self.root {
-            Some(ref mut node) => {
-                if node.try_insert(key, value) {
-                    self.size += 1;
-                }
-            }
-            None => {
-                self.root = Some(Box::from(Node::new(key, value)));
-                self.size += 1;
-            }
+    /// inserts a new entry with key `key` and value `value`
+    /// if `key` already exists, the old value is kept
+    /// unlike `try_insert`, this works for unsized values
+    pub fn try_insert_boxed(&mut self, key: K, value: Box<V>)
+    where
+        A: Clone,
+    {
+        let entry = Entry::from_box(key, value);
+        if Node::try_insert(&mut self.root, entry, &self.alloc) {
+            self.size += 1;
         }
     }
 
     /// returns an optional reference to the `value` with key `key`
     #[inline]
-    pub fn get(&self, key: &K) -> Option<&V> {
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         match self.root {
             Some(ref node) => node.get(key),
             None => None,
@@ -94,7 +149,11 @@ where
     }
 
     /// returns an optional mutable reference to the `value` with key `key`
-    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         match self.root {
             Some(ref mut node) => node.get_mut(key),
             None => None,
@@ -133,19 +192,406 @@ where
         }
     }
 
-    pub fn entry(&self, key: &K) -> Option<&Entry<K, V>> {
+    /// returns the entry with the largest key that is at most `key`
+    pub fn floor<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.root.as_ref()?.below(key, true)
+    }
+
+    /// returns the entry with the smallest key that is at least `key`
+    pub fn ceiling<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.root.as_ref()?.above(key, true)
+    }
+
+    /// returns the first entry whose key is not less than `key`
+    /// the same as `ceiling`
+    pub fn lower_bound<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.ceiling(key)
+    }
+
+    /// returns the first entry whose key is greater than `key`
+    /// the same as `successor`
+    pub fn upper_bound<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.successor(key)
+    }
+
+    /// returns the entry with the largest key smaller than `key`
+    /// `key` does not have to be in `self`
+    pub fn predecessor<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.root.as_ref()?.below(key, false)
+    }
+
+    /// returns the entry with the smallest key greater than `key`
+    /// `key` does not have to be in `self`
+    pub fn successor<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.root.as_ref()?.above(key, false)
+    }
+
+    /// returns the entry with `index` smaller entries,
+    /// or `None` if `index` is out of bounds
+    /// takes time proportional to the height of `self`
+    pub fn nth(&self, index: usize) -> Option<(&K, &V)> {
+        self.root.as_ref()?.nth(index).map(Entry::split)
+    }
+
+    /// returns the entry with `index` smaller entries,
+    /// with a mutable reference to its value,
+    /// or `None` if `index` is out of bounds
+    pub fn nth_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.root.as_mut()?.nth_mut(index).map(Entry::split_mut)
+    }
+
+    /// returns the number of entries with keys smaller than `key`,
+    /// whether or not `self` contains `key`
+    /// takes time proportional to the height of `self`
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.root.as_ref().map_or(0, |node| node.rank(key))
+    }
+
+    /// returns the key that has `rank` smaller keys,
+    /// the inverse of `rank` for keys in `self`
+    pub fn select(&self, rank: usize) -> Option<&K> {
+        self.nth(rank).map(|(key, _)| key)
+    }
+
+    /// returns an optional reference to the `Entry` with key `key`
+    pub fn get_entry<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         match self.root {
             Some(ref node) => node.entry(key),
             None => None,
         }
     }
 
-    pub fn node_mut(&mut self, key: &K) -> Option<&mut Entry<K, V>> {
+    pub fn node_mut<Q>(&mut self, key: &Q) -> Option<&mut Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         match self.root {
             Some(ref mut node) => node.entry_mut(key),
             None => None,
         }
     }
+
+    /// returns an iterator over the entries of `self`, in order of their keys
+    pub fn iter(&self) -> Iter<'_, K, V, A> {
+        Iter::new_in(self.root.as_deref(), self.size, &self.alloc)
+    }
+
+    /// returns an iterator over the entries of `self`, in order of their keys,
+    /// with mutable references to the values
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V, A> {
+        IterMut::new_in(self.root.as_deref_mut(), self.size, &self.alloc)
+    }
+
+    /// returns an iterator over the keys of `self`, in order
+    pub fn keys(&self) -> Keys<'_, K, V, A> {
+        Keys::new(self.iter())
+    }
+
+    /// returns an iterator over the values of `self`, in order of their keys
+    pub fn values(&self) -> Values<'_, K, V, A> {
+        Values::new(self.iter())
+    }
+
+    /// returns an iterator over mutable references to the values of `self`,
+    /// in order of their keys
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V, A> {
+        ValuesMut::new(self.iter_mut())
+    }
+
+    /// returns an iterator over the entries of `self` whose keys lie within
+    /// `range`, in order of their keys
+    /// if the start of `range` is after its end, the iterator is empty
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V, A>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Range::new_in(self.root.as_deref(), &range, &self.alloc)
+    }
+
+    /// returns an iterator over the entries of `self` whose keys lie within
+    /// `range`, in order of their keys, with mutable references to the values
+    /// if the start of `range` is after its end, the iterator is empty
+    pub fn range_mut<Q, R>(&mut self, range: R) -> RangeMut<'_, K, V, A>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        RangeMut::new_in(self.root.as_deref_mut(), &range, &self.alloc)
+    }
+}
+
+/// the methods that move values in or out of the tree,
+/// which need them to be sized
+impl<K, V, A> BsTreeMap<K, V, A>
+where
+    K: Ord,
+    A: Allocator + Clone,
+{
+    /// sets the value of the key with key `key` to `value`
+    /// if `key` already exists, the value is overridden
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) {
+        if Node::insert(&mut self.root, Entry::new(key, value), &self.alloc) {
+            self.size += 1;
+        }
+    }
+
+    /// inserts a new entry with key `key` and value `value`
+    /// if `key` already exists, the old value is kept
+    pub fn try_insert(&mut self, key: K, value: V) {
+        let entry = Entry::new(key, value);
+        if Node::try_insert(&mut self.root, entry, &self.alloc) {
+            self.size += 1;
+        }
+    }
+
+    /// returns the entry with key `key`, which can be filled in if it is
+    /// vacant, or read, changed and removed if it is occupied
+    /// the tree is only descended once
+    pub fn entry(&mut self, key: K) -> MapEntry<'_, K, V, A> {
+        MapEntry::new(key, &mut self.root, &mut self.size, &self.alloc)
+    }
+
+    /// removes the smallest entry from `self` and returns it
+    /// returns `None` if `self` is empty
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let entry = Node::pop_smallest(&mut self.root)?;
+        self.size -= 1;
+        Some(entry.into_pair())
+    }
+
+    /// removes the largest entry from `self` and returns it
+    /// returns `None` if `self` is empty
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let entry = Node::pop_largest(&mut self.root)?;
+        self.size -= 1;
+        Some(entry.into_pair())
+    }
+
+    /// moves all entries of `other` into `self`
+    /// if both contain an entry with the same key, `policy` decides on
+    /// its value: `KeepLeft` keeps the value in `self`, `KeepRight` takes
+    /// the one from `other`, and a closure can combine the two
+    /// if `policy` panics, `self` keeps the entries merged so far
+    /// and the rest of its own
+    ///
+    /// runs in linear time, reuses the nodes, and leaves `self` balanced
+    /// of two entries with the same key, the node from `self` is kept
+    pub fn merge<P>(&mut self, mut other: BsTreeMap<K, V, A>, mut policy: P)
+    where
+        P: MergePolicy<K, V>,
+    {
+        let mut right = Vine::from_tree(other.root.take());
+        let mut merge = Rebuild::new(&mut self.root, &mut self.size);
+        loop {
+            let ordering = match (merge.lower.peek(), right.peek()) {
+                (Some(left), Some(right)) => left.key().cmp(right.key()),
+                (Some(_), None) => Ordering::Greater,
+                (None, Some(_)) => Ordering::Less,
+                (None, None) => return,
+            };
+            let node = match ordering {
+                Ordering::Greater => merge.lower.pop(),
+                Ordering::Less => right.pop(),
+                Ordering::Equal => {
+                    merge.lower.pop().zip(right.pop()).map(|(left, right)| {
+                        let (_, right) = Node::into_pair(right);
+                        Node::map_value(left, |key, left| {
+                            policy.resolve(key, left, right)
+                        })
+                    })
+                }
+            };
+            if let Some(node) = node {
+                merge.upper.push(node);
+            }
+        }
+    }
+
+    /// moves every entry with a key of at least `key` into a new map
+    /// and returns it
+    ///
+    /// takes time proportional to the height of `self`, and reuses the nodes
+    pub fn split_off<Q>(&mut self, key: &Q) -> BsTreeMap<K, V, A>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let root = Node::split_off(&mut self.root, key);
+        let size = Node::size_of(&root);
+        self.size -= size;
+        BsTreeMap {
+            root,
+            size,
+            alloc: self.alloc.clone(),
+        }
+    }
+
+    /// moves all entries of `other` into `self`, leaving `other` empty
+    /// if both contain an entry with the same key, the value from `other`
+    /// is kept
+    pub fn append(&mut self, other: &mut BsTreeMap<K, V, A>) {
+        self.merge(other.take(), KeepRight);
+    }
+
+    /// keeps only the entries for which `f` returns `true`
+    ///
+    /// runs in linear time, reuses the nodes, and leaves `self` balanced
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.extract_if(|key, value| !f(key, value)).for_each(drop);
+    }
+
+    /// returns an iterator that removes the entries for which `pred`
+    /// returns `true`, in order of their keys, and yields them
+    /// if the iterator is dropped early, the remaining entries are kept,
+    /// and if `pred` panics, every entry not yet yielded is kept
+    ///
+    /// the tree is taken apart up front and rebuilt, balanced, once the
+    /// iterator is dropped, which takes linear time and reuses the nodes
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, F, A>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        ExtractIf::new(self.take_apart(), pred)
+    }
+
+    /// takes the tree apart, to be rebuilt when the result is dropped
+    pub(super) fn take_apart(&mut self) -> Rebuild<'_, K, V, A> {
+        Rebuild::ascending(&mut self.root, &mut self.size)
+    }
+
+    /// replaces `self` with an empty map with the same allocator,
+    /// and returns the old one
+    fn take(&mut self) -> BsTreeMap<K, V, A> {
+        mem::replace(self, BsTreeMap::new_in(self.alloc.clone()))
+    }
+
+    /// builds a balanced tree in `alloc` out of the `size` entries of
+    /// `entries`, which must be sorted by key and free of duplicates
+    fn from_sorted<I>(size: usize, entries: I, alloc: A) -> BsTreeMap<K, V, A>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let root = Node::from_sorted(size, &mut entries.into_iter(), &alloc);
+        BsTreeMap { root, size, alloc }
+    }
+
+    /// builds a balanced tree in `alloc` out of `entries`
+    /// if a key appears more than once, its last value is kept
+    ///
+    /// the nodes are sorted as a vine, so nothing but the nodes themselves
+    /// is allocated
+    fn from_entries<I>(entries: I, alloc: A) -> BsTreeMap<K, V, A>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut arrived = Vine::new();
+        for (key, value) in entries {
+            arrived
+                .push(AllocBox::new_in(Node::new(key, value), alloc.clone()));
+        }
+        let mut vine = Vine::new();
+        vine.push_reversed(&mut arrived);
+        vine.sort();
+        vine.dedup_keep_last();
+        let size = vine.len();
+        let root = vine.into_tree();
+        BsTreeMap { root, size, alloc }
+    }
+}
+
+impl<K, V, A> Drop for BsTreeMap<K, V, A>
+where
+    K: Ord,
+    V: Value + ?Sized,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        if let Some(root) = self.root.take() {
+            Node::dismantle(root);
+        }
+    }
+}
+
+impl<K, V, A> IntoIterator for BsTreeMap<K, V, A>
+where
+    K: Ord,
+    A: Allocator + Clone,
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, A>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let root = self.root.take().map(Subtree::new);
+        IntoIter::new_in(root, self.size, self.alloc.clone())
+    }
+}
+
+impl<'a, K, V, A> IntoIterator for &'a BsTreeMap<K, V, A>
+where
+    K: Ord,
+    V: Value + ?Sized,
+    A: Allocator,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, A> IntoIterator for &'a mut BsTreeMap<K, V, A>
+where
+    K: Ord,
+    V: Value + ?Sized,
+    A: Allocator,
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
 }
 
 impl<K, V> From<Node<K, V>> for BsTreeMap<K, V>
@@ -155,8 +601,9 @@ where
     fn from(value: Node<K, V>) -> Self {
         let size = value.size();
         BsTreeMap {
-            root: Some(Box::from(value)),
+            root: Some(AllocBox::new_in(value, Global)),
             size,
+            alloc: Global,
         }
     }
 }
@@ -164,15 +611,156 @@ where
 impl<K, V> Default for BsTreeMap<K, V>
 where
     K: Ord,
+    V: Value + ?Sized,
 {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// sorts the entries and builds a balanced tree out of them in one go
+/// if a key appears more than once, its last value is kept
+impl<K, V> FromIterator<(K, V)> for BsTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        BsTreeMap::from_entries(iter, Global)
+    }
+}
+
+/// sorts the new entries and merges them in with `merge`, which rebuilds
+/// a balanced tree in time linear in the size of both, whatever order
+/// the entries come in
+/// if a key appears more than once, its last value is kept
+impl<K, V, A> Extend<(K, V)> for BsTreeMap<K, V, A>
+where
+    K: Ord,
+    A: Allocator + Clone,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let other = BsTreeMap::from_entries(iter, self.alloc.clone());
+        self.merge(other, KeepRight);
+    }
+}
+
+impl<'a, K, V, A> Extend<(&'a K, &'a V)> for BsTreeMap<K, V, A>
+where
+    K: Ord + Copy,
+    V: Copy,
+    A: Allocator + Clone,
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(&key, &value)| (key, value)));
+    }
+}
+
+impl<K, V, const N: usize> From<[(K, V); N]> for BsTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from(array: [(K, V); N]) -> Self {
+        Self::from_iter(array)
+    }
+}
+
+impl<K, V> From<Vec<(K, V)>> for BsTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from(vec: Vec<(K, V)>) -> Self {
+        Self::from_iter(vec)
+    }
+}
+
+impl<K, V, A> Clone for BsTreeMap<K, V, A>
+where
+    K: Ord + Clone,
+    V: Clone,
+    A: Allocator + Clone,
+{
+    fn clone(&self) -> Self {
+        let entries =
+            self.iter().map(|(key, value)| (key.clone(), value.clone()));
+        BsTreeMap::from_sorted(self.size, entries, self.alloc.clone())
+    }
+}
+
+impl<K, V, A> fmt::Debug for BsTreeMap<K, V, A>
+where
+    K: Ord + fmt::Debug,
+    V: fmt::Debug + Value + ?Sized,
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K, V, A> PartialEq for BsTreeMap<K, V, A>
+where
+    K: Ord,
+    V: PartialEq + Value + ?Sized,
+    A: Allocator,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.size() == other.size() && self.iter().eq(other)
+    }
+}
+
+impl<K, V, A> Eq for BsTreeMap<K, V, A>
+where
+    K: Ord,
+    V: Eq + Value + ?Sized,
+    A: Allocator,
+{
+}
+
+impl<K, V, A> Hash for BsTreeMap<K, V, A>
+where
+    K: Ord + Hash,
+    V: Hash + Value + ?Sized,
+    A: Allocator,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.size());
+        for entry in self {
+            entry.hash(state);
+        }
+    }
+}
+
+impl<K, V, A> PartialOrd for BsTreeMap<K, V, A>
+where
+    K: Ord,
+    V: PartialOrd + Value + ?Sized,
+    A: Allocator,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<K, V, A> Ord for BsTreeMap<K, V, A>
+where
+    K: Ord,
+    V: Ord + Value + ?Sized,
+    A: Allocator,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::alloc::Counting;
+    use crate::entry::Counted;
+    use alloc_crate::string::{String, ToString};
+    use alloc_crate::{format, vec};
+    use core::cell::Cell;
+    use core::ops::Bound;
 
     // set up a tree to apply tests to
     fn basic_tree() -> BsTreeMap<usize, &'static str> {
@@ -227,6 +815,238 @@ mod tests {
         assert_eq!(tree.root.as_ref().unwrap().size(), tree.size());
     }
 
+    #[test]
+    fn remove_returns_entry() {
+        let mut tree = basic_tree();
+        assert_eq!(tree.remove(&8), None);
+        assert_eq!(tree.size(), 6);
+
+        // 3 has two children, so 4 is spliced into its place
+        assert_eq!(tree.remove(&3), Some(Entry::new(3, "hello")));
+        assert_eq!(tree.get(&1), Some(&"this is the smallest entry"));
+        assert_eq!(tree.get(&4), Some(&"hmmm"));
+
+        assert_eq!(tree.remove(&6), Some(Entry::new(6, "world"))); // one child
+        assert_eq!(tree.get(&7), Some(&"this is the largest entry"));
+        assert_eq!(tree.remove(&6), None);
+
+        for key in [5, 1, 4, 7] {
+            assert!(tree.remove(&key).is_some());
+        }
+        assert_eq!(tree.size(), 0);
+        assert!(tree.root.is_none());
+    }
+
+    #[test]
+    fn entry() {
+        let mut tree = BsTreeMap::new();
+        for word in "the cat saw the dog and the bird".split(' ') {
+            tree.entry(word)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+        assert_eq!(tree.size(), 6);
+        assert_eq!(tree.get(&"the"), Some(&3));
+        assert_eq!(tree.get(&"cat"), Some(&1));
+
+        *tree.entry("dog").or_default() += 10;
+        *tree.entry("fox").or_default() += 10;
+        assert_eq!(tree.get(&"dog"), Some(&11));
+        assert_eq!(tree.get(&"fox"), Some(&10));
+        assert_eq!(*tree.entry("cow").or_insert_with(|| 5), 5);
+        assert_eq!(tree.entry("cow").key(), &"cow");
+        assert_eq!(tree.size(), 8);
+    }
+
+    #[test]
+    fn occupied_and_vacant_entries() {
+        let mut tree = basic_tree();
+        match tree.entry(3) {
+            MapEntry::Occupied(mut entry) => {
+                assert_eq!(entry.get(), &"hello");
+                assert_eq!(entry.insert("goodbye"), "hello");
+            }
+            MapEntry::Vacant(_) => panic!("3 is in the tree"),
+        }
+        assert_eq!(tree.get(&3), Some(&"goodbye"));
+
+        match tree.entry(2) {
+            MapEntry::Vacant(entry) => {
+                assert_eq!(entry.key(), &2);
+                *entry.insert("two") = "2";
+            }
+            MapEntry::Occupied(_) => panic!("2 is not in the tree"),
+        }
+        assert_eq!(tree.get(&2), Some(&"2"));
+        assert_eq!(tree.size(), 7);
+
+        // 3 has two children, so its successor is spliced into its place
+        if let MapEntry::Occupied(entry) = tree.entry(3) {
+            assert_eq!(entry.remove_entry(), Entry::new(3, "goodbye"));
+        }
+        assert!(!tree.contains(&3));
+        assert_eq!(tree.size(), 6);
+        let keys: Vec<&usize> = tree.keys().collect();
+        assert_eq!(keys, [&1, &2, &4, &5, &6, &7]);
+        assert_eq!(tree.rank(&6), 4);
+        assert_eq!(tree.select(5), Some(&7));
+    }
+
+    #[test]
+    fn entries_descend_once() {
+        let compared = Cell::new(0);
+        // keys inserted in order leave a tree that is a single path
+        let mut tree = BsTreeMap::new();
+        for key in 0..50 {
+            tree.insert(Counted(key, &compared), key);
+        }
+
+        compared.set(0);
+        match tree.entry(Counted(49, &compared)) {
+            MapEntry::Occupied(mut entry) => {
+                assert_eq!(entry.key().0, 49);
+                *entry.get_mut() += 1;
+                assert_eq!(entry.insert(0), 50);
+                assert_eq!(entry.remove_entry().into_pair().1, 0);
+            }
+            MapEntry::Vacant(_) => panic!("49 is in the tree"),
+        }
+        assert_eq!(compared.get(), 50);
+
+        compared.set(0);
+        match tree.entry(Counted(49, &compared)) {
+            MapEntry::Vacant(entry) => *entry.insert(0) = 49,
+            MapEntry::Occupied(_) => panic!("49 is not in the tree"),
+        }
+        assert_eq!(compared.get(), 49);
+
+        assert_eq!(tree.size(), 50);
+        let values: Vec<usize> = tree.values().copied().collect();
+        assert_eq!(values, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn borrowed_lookups() {
+        let mut tree = BsTreeMap::new();
+        for word in ["pear", "apple", "plum", "fig"] {
+            tree.insert(word.to_string(), word.len());
+        }
+        assert!(tree.contains("fig"));
+        assert_eq!(tree.get("plum"), Some(&4));
+        if let Some(value) = tree.get_mut("apple") {
+            *value = 0;
+        }
+        assert_eq!(tree.get_entry("apple").map(|entry| entry.value), Some(0));
+        assert_eq!(tree.remove("pear"), Some(Entry::new("pear".into(), 4)));
+        assert_eq!(tree.remove("kiwi"), None);
+        let range =
+            tree.range::<str, _>((Bound::Included("b"), Bound::Excluded("g")));
+        assert!(range.map(|(key, _)| key).eq(["fig"]));
+    }
+
+    #[test]
+    fn merge() {
+        let mut left = BsTreeMap::new();
+        let mut right = BsTreeMap::new();
+        for key in 0..10 {
+            left.insert(key, "left");
+            right.insert(key + 5, "right");
+        }
+        let mut tree = left;
+        tree.merge(right, KeepLeft);
+        assert_eq!(tree.size(), 15);
+        assert!(tree.keys().copied().eq(0..15));
+        assert_eq!(tree.get(&7), Some(&"left"));
+        assert_eq!(tree.get(&12), Some(&"right"));
+        assert_eq!(tree.root.as_ref().map(|root| root.size()), Some(15));
+
+        let mut other = BsTreeMap::new();
+        other.insert(3, "other");
+        tree.merge(other, KeepRight);
+        assert_eq!(tree.get(&3), Some(&"other"));
+        assert_eq!(tree.size(), 15);
+        tree.merge(BsTreeMap::new(), KeepRight);
+        assert_eq!(tree.size(), 15);
+    }
+
+    #[test]
+    fn merge_with_closure() {
+        let mut counts = BsTreeMap::new();
+        let mut more_counts = BsTreeMap::new();
+        for key in 0..1000 {
+            counts.insert(key, 1);
+            more_counts.insert(key * 2, 10);
+        }
+        counts.merge(more_counts, |_: &usize, left: usize, right| left + right);
+        assert_eq!(counts.size(), 1500);
+        assert_eq!(counts.get(&500), Some(&11));
+        assert_eq!(counts.get(&501), Some(&1));
+        assert_eq!(counts.get(&1500), Some(&10));
+        assert_eq!(counts.values().sum::<usize>(), 11_000);
+    }
+
+    #[test]
+    fn merge_reuses_nodes() {
+        let counting = Counting::default();
+        let mut left = BsTreeMap::new_in(&counting);
+        let mut right = BsTreeMap::new_in(&counting);
+        for key in 0..100 {
+            left.insert(key, 1);
+            right.insert(key + 50, 10);
+        }
+        assert_eq!(counting.allocated.get(), 200);
+        left.merge(right, |_: &usize, left: usize, right| left + right);
+        assert_eq!(counting.allocated.get(), 200);
+        assert_eq!(counting.live.get(), 150);
+        assert_eq!(left.size(), 150);
+        assert_eq!(left.get(&75), Some(&11));
+        assert_eq!(left.values().sum::<usize>(), 100 + 500 + 50 * 10);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn merge_survives_a_panicking_policy() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut tree: BsTreeMap<usize, usize> =
+            (0..10).map(|key| (key, key)).collect();
+        let other: BsTreeMap<usize, usize> =
+            (5..15).map(|key| (key, 0)).collect();
+        let merge = catch_unwind(AssertUnwindSafe(|| {
+            tree.merge(other, |key: &usize, left, _| {
+                assert_ne!(*key, 7);
+                left
+            });
+        }));
+        assert!(merge.is_err());
+        // 7 went into the policy, everything else in `tree` is still there
+        assert!(tree.keys().copied().eq((0..7).chain(8..15)));
+        assert_eq!(tree.size(), 14);
+        assert_eq!(tree.get(&3), Some(&3));
+        assert_eq!(tree.get(&12), Some(&0));
+        assert_eq!(tree.root.as_ref().map(|root| root.size()), Some(14));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn extract_if_survives_a_panicking_predicate() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut tree: BsTreeMap<usize, usize> =
+            (0..10).map(|key| (key, key)).collect();
+        let extract = catch_unwind(AssertUnwindSafe(|| {
+            let extracted = tree.extract_if(|key, _| {
+                assert_ne!(*key, 5);
+                key % 2 == 0
+            });
+            extracted.for_each(drop);
+        }));
+        assert!(extract.is_err());
+        assert!(tree.keys().copied().eq([1, 3, 5, 6, 7, 8, 9]));
+        assert_eq!(tree.size(), 7);
+        assert_eq!(tree.root.as_ref().map(|root| root.size()), Some(7));
+    }
+
     #[test]
     fn contains() {
         let tree = basic_tree();
@@ -244,6 +1064,144 @@ mod tests {
         );
     }
 
+    #[test]
+    fn iter() {
+        let tree = basic_tree();
+        let keys: Vec<usize> = tree.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys, [1, 3, 4, 5, 6, 7]);
+
+        let mut iter = tree.iter();
+        assert_eq!(iter.len(), 6);
+        assert_eq!(iter.next(), Some((&1, &"this is the smallest entry")));
+        assert_eq!(iter.next_back(), Some((&7, &"this is the largest entry")));
+        assert_eq!(iter.len(), 4);
+        let rest: Vec<&usize> = iter.rev().map(|(key, _)| key).collect();
+        assert_eq!(rest, [&6, &5, &4, &3]);
+
+        assert_eq!(BsTreeMap::<usize, ()>::new().iter().next(), None);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut tree = BsTreeMap::new();
+        for key in [4, 2, 6, 1, 3, 5, 7] {
+            tree.insert(key, key * 10);
+        }
+        for (key, value) in &mut tree {
+            *value += key;
+        }
+        for value in tree.values_mut().rev().take(2) {
+            *value = 0;
+        }
+        let values: Vec<usize> = tree.values().copied().collect();
+        assert_eq!(values, [11, 22, 33, 44, 55, 0, 0]);
+    }
+
+    #[test]
+    fn into_iter() {
+        let tree = basic_tree();
+        let keys: Vec<&usize> = tree.keys().collect();
+        assert_eq!(keys, [&1, &3, &4, &5, &6, &7]);
+
+        let mut iter = tree.into_iter();
+        assert_eq!(iter.next_back(), Some((7, "this is the largest entry")));
+        assert_eq!(iter.next(), Some((1, "this is the smallest entry")));
+        assert_eq!(iter.len(), 4);
+        let keys: Vec<usize> = iter.map(|(key, _)| key).collect();
+        assert_eq!(keys, [3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn range() {
+        let tree = basic_tree();
+        let keys = |range: Range<usize, _>| {
+            range.map(|(key, _)| *key).collect::<Vec<usize>>()
+        };
+        assert_eq!(keys(tree.range(3..6)), [3, 4, 5]);
+        assert_eq!(keys(tree.range(2..=6)), [3, 4, 5, 6]);
+        assert_eq!(keys(tree.range(..4)), [1, 3]);
+        assert_eq!(keys(tree.range(5..)), [5, 6, 7]);
+        assert_eq!(keys(tree.range(..)), [1, 3, 4, 5, 6, 7]);
+        assert_eq!(keys(tree.range(8..)), []);
+        assert_eq!(
+            keys(tree.range((Bound::Included(6), Bound::Excluded(3)))),
+            []
+        );
+
+        let mut range = tree.range((Bound::Excluded(1), Bound::Excluded(7)));
+        assert_eq!(range.next_back(), Some((&6, &"world")));
+        assert_eq!(range.next(), Some((&3, &"hello")));
+        let rest: Vec<&usize> = range.rev().map(|(key, _)| key).collect();
+        assert_eq!(rest, [&5, &4]);
+    }
+
+    #[test]
+    fn range_mut() {
+        let mut tree = BsTreeMap::new();
+        for key in [50, 20, 80, 10, 30, 70, 90, 25, 35, 75] {
+            tree.insert(key, 0);
+        }
+        for (key, value) in tree.range_mut(25..75) {
+            *value = *key;
+        }
+        let values: Vec<usize> = tree.values().copied().collect();
+        assert_eq!(values, [0, 0, 25, 30, 35, 50, 70, 0, 0, 0]);
+    }
+
+    #[test]
+    fn bounds() {
+        let tree = basic_tree();
+        let key =
+            |entry: Option<&Entry<usize, _>>| entry.map(|entry| *entry.key());
+        assert_eq!(key(tree.floor(&2)), Some(1));
+        assert_eq!(key(tree.floor(&3)), Some(3));
+        assert_eq!(key(tree.floor(&0)), None);
+        assert_eq!(key(tree.ceiling(&2)), Some(3));
+        assert_eq!(key(tree.ceiling(&5)), Some(5));
+        assert_eq!(key(tree.ceiling(&8)), None);
+        assert_eq!(key(tree.lower_bound(&4)), Some(4));
+        assert_eq!(key(tree.upper_bound(&4)), Some(5));
+        assert_eq!(key(tree.predecessor(&5)), Some(4));
+        assert_eq!(key(tree.predecessor(&1)), None);
+        assert_eq!(key(tree.successor(&5)), Some(6));
+        assert_eq!(key(tree.successor(&7)), None);
+        assert_eq!(key(tree.successor(&100)), None);
+        assert_eq!(BsTreeMap::<usize, ()>::new().floor(&1), None);
+    }
+
+    #[test]
+    fn pop_first_and_last() {
+        let mut tree = basic_tree();
+        assert_eq!(tree.pop_first(), Some((1, "this is the smallest entry")));
+        assert_eq!(tree.pop_last(), Some((7, "this is the largest entry")));
+        assert_eq!(tree.size(), 4);
+        assert_eq!(Node::size_of(&tree.root), 4);
+        assert_eq!(tree.pop_first(), Some((3, "hello")));
+        assert!(tree.keys().copied().eq([4, 5, 6]));
+
+        // the root is the smallest entry, then the largest one
+        let mut tree = BsTreeMap::new();
+        for key in [1, 3, 2] {
+            tree.insert(key, ());
+        }
+        assert_eq!(tree.pop_first(), Some((1, ())));
+        assert_eq!(tree.pop_last(), Some((3, ())));
+        assert_eq!(tree.pop_last(), Some((2, ())));
+        assert_eq!(tree.pop_last(), None);
+        assert_eq!(tree.pop_first(), None);
+        assert_eq!(tree.size(), 0);
+        assert!(tree.root.is_none());
+
+        let mut tree: BsTreeMap<_, _> =
+            (0..100).map(|key| (key, key)).collect();
+        for key in 0..50 {
+            assert_eq!(tree.pop_first(), Some((key, key)));
+            assert_eq!(tree.pop_last(), Some((99 - key, 99 - key)));
+            assert_eq!(Node::size_of(&tree.root), tree.size());
+        }
+        assert!(tree.root.is_none());
+    }
+
     #[test]
     fn largest() {
         let tree = basic_tree();
@@ -252,4 +1210,274 @@ mod tests {
             Some(&Entry::new(7, "this is the largest entry"))
         );
     }
+
+    #[test]
+    fn order_statistics() {
+        let mut tree = BsTreeMap::new();
+        for key in 0..200 {
+            tree.insert((key * 7919) % 200, key);
+        }
+        for key in (0..200).step_by(3) {
+            tree.remove(&key);
+        }
+        for key in (1..200).step_by(5) {
+            if let MapEntry::Occupied(entry) = tree.entry(key) {
+                entry.remove_entry();
+            }
+        }
+        for key in 200..210 {
+            tree.entry(key).or_insert(key);
+        }
+        assert_eq!(Node::size_of(&tree.root), tree.size());
+        let keys: Vec<usize> = tree.keys().copied().collect();
+        for (index, key) in keys.iter().enumerate() {
+            assert_eq!(tree.nth(index).map(|(key, _)| key), Some(key));
+            assert_eq!(tree.select(index), Some(key));
+            assert_eq!(tree.rank(key), index);
+            assert_eq!(tree.rank(&(key + 1)), index + 1);
+        }
+        assert_eq!(tree.nth(keys.len()), None);
+        assert_eq!(tree.rank(&1000), keys.len());
+        if let Some((_, value)) = tree.nth_mut(0) {
+            *value = 1000;
+        }
+        assert_eq!(tree.get(&keys[0]), Some(&1000));
+        assert_eq!(BsTreeMap::<usize, usize>::new().nth(0), None);
+    }
+
+    #[test]
+    fn split_off_and_append() {
+        let mut tree: BsTreeMap<usize, usize> =
+            (0..100).map(|key| (key, key)).collect();
+        for at in [50, 0, 100] {
+            let mut right = tree.split_off(&at);
+            assert!(tree.keys().copied().eq(0..at));
+            assert!(right.keys().copied().eq(at..100));
+            assert_eq!(tree.size() + right.size(), 100);
+            tree.append(&mut right);
+            assert_eq!(right.size(), 0);
+            assert_eq!(tree.size(), 100);
+        }
+        let mut other: BsTreeMap<_, _> =
+            (90..110).map(|key| (key, 0)).collect();
+        tree.append(&mut other);
+        assert_eq!(tree.size(), 110);
+        assert_eq!(tree.get(&95), Some(&0));
+        assert_eq!(tree.get(&5), Some(&5));
+        assert!(tree.split_off(&200).iter().next().is_none());
+        assert_eq!(tree.size(), 110);
+
+        // splitting only relinks the nodes along one path
+        let mut degenerate = BsTreeMap::new();
+        for key in (0..10_000).rev() {
+            degenerate.insert(key, key);
+        }
+        let right = degenerate.split_off(&2_500);
+        assert_eq!(degenerate.size(), 2_500);
+        assert_eq!(right.size(), 7_500);
+        assert_eq!(right.rank(&5_000), 2_500);
+        assert_eq!(degenerate.nth(2_499), Some((&2_499, &2_499)));
+        assert!(right.keys().copied().eq(2_500..10_000));
+    }
+
+    #[test]
+    fn retain_extract_and_clear() {
+        let mut tree: BsTreeMap<usize, usize> =
+            (0..100).map(|key| (key, key)).collect();
+        tree.retain(|key, value| {
+            *value += 1;
+            key % 3 != 0
+        });
+        assert_eq!(tree.size(), 66);
+        assert_eq!(tree.get(&1), Some(&2));
+        assert!(!tree.contains(&99));
+
+        let extracted: Vec<_> = tree.extract_if(|key, _| *key < 10).collect();
+        assert_eq!(extracted, [(1, 2), (2, 3), (4, 5), (5, 6), (7, 8), (8, 9)]);
+        assert_eq!(tree.size(), 60);
+        assert_eq!(tree.smallest().map(|entry| *entry.key()), Some(10));
+        assert_eq!(tree.root.as_ref().map(|root| root.size()), Some(60));
+
+        // entries are only removed as the iterator reaches them
+        drop(tree.extract_if(|key, _| key % 2 == 0));
+        assert_eq!(tree.size(), 60);
+        let mut evens = tree.extract_if(|key, _| key % 2 == 0);
+        assert_eq!(evens.next(), Some((10, 11)));
+        assert_eq!(evens.next(), Some((14, 15)));
+        drop(evens);
+        assert_eq!(tree.size(), 58);
+        assert_eq!(tree.root.as_ref().map(|root| root.size()), Some(58));
+        assert_eq!(tree.extract_if(|key, _| key % 2 == 0).count(), 28);
+        assert!(tree.keys().all(|key| key % 2 == 1));
+        assert_eq!(tree.size(), 30);
+
+        tree.clear();
+        assert_eq!(tree.size(), 0);
+        assert!(tree.iter().next().is_none());
+        tree.insert(1, 1);
+        assert_eq!(tree.get(&1), Some(&1));
+    }
+
+    #[test]
+    fn unsized_values() {
+        let mut names: BsTreeMap<u32, str> = BsTreeMap::new();
+        names.insert_boxed(2, "two".into());
+        names.insert_boxed(1, "one".into());
+        names.try_insert_boxed(2, "deux".into());
+        assert_eq!(names.get(&2), Some("two"));
+        names.insert_boxed(2, String::from("deux").into_boxed_str());
+        assert_eq!(names.get(&2), Some("deux"));
+        assert!(names.values().eq(["one", "deux"]));
+        assert_eq!(format!("{names:?}"), r#"{1: "one", 2: "deux"}"#);
+        let entry = names.remove(&1).map(Entry::into_boxed_pair);
+        assert_eq!(entry, Some((1, "one".into())));
+        assert_eq!(names.size(), 1);
+
+        let mut bytes: BsTreeMap<u32, [u8]> = BsTreeMap::new();
+        bytes.insert_boxed(0, Box::new([1, 2, 3]));
+        bytes.insert_boxed(1, Box::new([]));
+        if let Some(value) = bytes.get_mut(&0) {
+            value[0] = 4;
+        }
+        for (_, value) in bytes.iter_mut() {
+            value.reverse();
+        }
+        assert_eq!(bytes.get(&0), Some(&[3, 2, 4][..]));
+        assert_eq!(bytes.nth(1), Some((&1, &[][..])));
+
+        let mut handlers: BsTreeMap<&str, dyn Fn(u32) -> u32> =
+            BsTreeMap::new();
+        handlers.insert_boxed("double", Box::new(|x| x * 2));
+        handlers.insert_boxed("square", Box::new(|x| x * x));
+        let results: Vec<_> =
+            handlers.iter().map(|(name, f)| (*name, f(5))).collect();
+        assert_eq!(results, [("double", 10), ("square", 25)]);
+        assert_eq!(handlers.range("r"..).count(), 1);
+        assert_eq!(
+            handlers.floor("e").map(|entry| entry.key()),
+            Some(&"double")
+        );
+    }
+
+    #[test]
+    fn std_traits() {
+        let tree: BsTreeMap<usize, usize> =
+            (0..100).rev().map(|key| (key, key)).collect();
+        assert_eq!(tree.size(), 100);
+        assert!(tree.keys().copied().eq(0..100));
+        assert_eq!(tree.root.as_ref().map(|root| root.size()), Some(100));
+        let mut other = tree.clone();
+        assert_eq!(other, tree);
+        other.extend([(100, 100), (0, 1)]);
+        other.extend([(&101, &101)]);
+        assert_eq!(other.size(), 102);
+        assert_eq!(other.get(&0), Some(&1));
+        assert!(tree < other);
+
+        let small = BsTreeMap::from([(2, "b"), (1, "a"), (2, "c")]);
+        assert_eq!(format!("{small:?}"), r#"{1: "a", 2: "c"}"#);
+        assert_eq!(small, BsTreeMap::from(vec![(1, "a"), (2, "c")]));
+        assert_ne!(small, BsTreeMap::from([(1, "a")]));
+
+        // every key comes up five times, and the last value must win
+        let shuffled = (0..500).map(|index| (index * 37 % 100, index));
+        let tree: BsTreeMap<usize, usize> = shuffled.clone().collect();
+        assert_eq!(tree.size(), 100);
+        assert_eq!(tree.root.as_ref().map(|root| root.size()), Some(100));
+        for (key, index) in shuffled {
+            assert!(tree.get(&key) >= Some(&index));
+        }
+        assert!(tree
+            .iter()
+            .all(|(key, index)| index / 100 == 4 && index * 37 % 100 == *key));
+    }
+
+    fn height<K: Ord, V>(link: &Link<K, V>) -> usize {
+        link.as_ref()
+            .map_or(0, |node| 1 + height(&node.left).max(height(&node.right)))
+    }
+
+    #[test]
+    fn extend_stays_balanced() {
+        // a filter hides the length, and the keys come in sorted
+        let mut tree: BsTreeMap<usize, usize> = BsTreeMap::new();
+        tree.extend((0..1000).filter(|key| key % 2 == 0).map(|key| (key, 0)));
+        assert_eq!(tree.size(), 500);
+        assert_eq!(height(&tree.root), 9);
+
+        // a small batch into a large map
+        tree.extend((0..10).map(|key| (key, 1)));
+        assert_eq!(tree.size(), 505);
+        assert_eq!(height(&tree.root), 9);
+        assert_eq!(tree.get(&4), Some(&1));
+        assert_eq!(tree.get(&12), Some(&0));
+
+        // a large batch into a small map
+        let mut tree = BsTreeMap::from([(5000, 0)]);
+        tree.extend((0..4000).map(|key| (key, key)));
+        assert_eq!(tree.size(), 4001);
+        assert_eq!(height(&tree.root), 12);
+        assert!(tree.keys().copied().eq((0..4000).chain([5000])));
+    }
+
+    #[test]
+    fn degenerate_tree() {
+        const SIZE: usize = 20_000;
+        let mut tree = BsTreeMap::new();
+        for key in 0..SIZE {
+            tree.insert(key, key);
+        }
+        tree.try_insert(0, 1);
+        assert_eq!(tree.size(), SIZE);
+        assert_eq!(tree.get(&0), Some(&0));
+        assert!(tree.contains(&(SIZE - 1)));
+        assert_eq!(tree.largest_mut().map(|entry| entry.value), Some(SIZE - 1));
+        assert_eq!(
+            tree.remove(&(SIZE / 2)),
+            Some(Entry::new(SIZE / 2, SIZE / 2))
+        );
+        assert_eq!(tree.root.as_ref().map(|root| root.size()), Some(SIZE - 1));
+
+        let mut iter = tree.clone().into_iter();
+        assert_eq!(iter.next(), Some((0, 0)));
+        assert_eq!(iter.next_back(), Some((SIZE - 1, SIZE - 1)));
+        drop(iter);
+
+        let reversed: BsTreeMap<_, _> =
+            (0..SIZE).rev().map(|key| (key, ())).collect();
+        drop(reversed);
+        drop(tree);
+    }
+
+    #[test]
+    fn custom_allocator() {
+        let counting = Counting::default();
+        let mut tree = BsTreeMap::new_in(&counting);
+        for key in [3, 1, 4, 5, 9, 2, 6] {
+            tree.insert(key, key * 10);
+        }
+        tree.entry(8).or_insert(80);
+        assert_eq!(counting.allocated.get(), 8);
+        assert_eq!(tree.remove(&4).map(|entry| *entry.key()), Some(4));
+        assert_eq!(counting.live.get(), 7);
+
+        let right = tree.split_off(&5);
+        assert_eq!(counting.allocated.get(), 8);
+        assert_eq!(counting.live.get(), 7);
+
+        // cloning walks `right` with an iterator, which keeps its pending
+        // steps in the same allocator, and frees them once it is done
+        let copy = right.clone();
+        tree.extend([(7, 70)]);
+        assert_eq!(counting.allocated.get(), 14);
+        assert_eq!(counting.live.get(), 12);
+        assert!(tree.keys().copied().eq([1, 2, 3, 7]));
+        assert!(copy.range(6..).map(|(key, _)| *key).eq([6, 8, 9]));
+        assert!(counting.allocated.get() > 14);
+        assert_eq!(counting.live.get(), 12);
+        drop(copy);
+        drop(right);
+        tree.clear();
+        assert_eq!(counting.live.get(), 0);
+    }
 }
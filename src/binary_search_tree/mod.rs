@@ -1,3 +1,7 @@
+pub mod arena;
+mod entry;
+mod iter;
 pub mod map;
 mod node;
 pub mod set;
+mod vine;
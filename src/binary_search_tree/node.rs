@@ -1,46 +1,114 @@
-//! an node in `BsTreeMap`
-use crate::entry::Entry;
-use std::cmp::Ordering;
+//! a node in `BsTreeMap`
+//! every operation walks the tree with a loop rather than recursion,
+//! since the tree may be arbitrarily deep
+use super::vine::Vine;
+use crate::alloc::{Allocator, Global};
+use crate::boxed::AllocBox;
+use crate::entry::{Entry, Value};
+use crate::traversal::{Expand, Split, Step};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::mem;
+
+/// a possibly empty subtree
+pub type Link<K, V, A = Global> = Option<AllocBox<Node<K, V, A>, A>>;
 
 /// `left` represents entrys that have smaller `key`s than `self.key`
 /// `right` represents entrys that have greater `key`s than `self.key`
-pub struct Node<K, V>
+/// `size` is the number of entries in the subtree rooted at `self`,
+/// including `self`
+pub struct Node<K, V, A = Global>
 where
     K: Ord,
-    V: ?Sized,
+    V: Value + ?Sized,
+    A: Allocator,
 {
     entry: Entry<K, V>,
-    pub left: Option<Box<Node<K, V>>>,
-    pub right: Option<Box<Node<K, V>>>,
+    pub size: usize,
+    pub left: Link<K, V, A>,
+    pub right: Link<K, V, A>,
 }
 
-impl<K, V> Node<K, V>
+impl<K, V, A> Node<K, V, A>
 where
     K: Ord,
+    A: Allocator,
 {
     /// creates a single `Entry` with key `key` and value `value`
     #[inline]
-    pub fn new(key: K, value: V) -> Node<K, V> {
+    pub fn new(key: K, value: V) -> Node<K, V, A> {
         Node {
             entry: Entry::new(key, value),
+            size: 1,
             left: None,
             right: None,
         }
     }
 
-    /// returns an optional reference to the `value` with key `key`
-    pub fn get(&self, key: &K) -> Option<&V> {
-        match key.cmp(self.key()) {
-            Ordering::Less => match self.left {
-                Some(ref node) => node.get(key),
-                None => None,
-            },
-            Ordering::Greater => match self.right {
-                Some(ref node) => node.get(key),
-                None => None,
-            },
-            Ordering::Equal => Some(&self.entry.value),
+    /// frees `node`, returning its key and value
+    pub fn into_pair(node: AllocBox<Node<K, V, A>, A>) -> (K, V) {
+        AllocBox::into_inner(node).entry.into_pair()
+    }
+
+    /// replaces the value of `node` with what `f` makes of it, reusing
+    /// the node
+    /// if `f` panics, the node is freed along with its key
+    pub fn map_value<F>(
+        node: AllocBox<Node<K, V, A>, A>,
+        f: F,
+    ) -> AllocBox<Node<K, V, A>, A>
+    where
+        F: FnOnce(&K, V) -> V,
+    {
+        AllocBox::map_in_place(node, |node| {
+            let (key, value) = node.entry.into_pair();
+            let value = f(&key, value);
+            Node {
+                entry: Entry::new(key, value),
+                ..node
+            }
+        })
+    }
+
+    /// builds a balanced tree out of the first `len` entries of `entries`,
+    /// which must be sorted by key, with nodes from `alloc`
+    pub fn from_sorted(
+        len: usize,
+        entries: &mut impl Iterator<Item = (K, V)>,
+        alloc: &A,
+    ) -> Link<K, V, A>
+    where
+        A: Clone,
+    {
+        if len == 0 {
+            return None;
         }
+        let left = Node::from_sorted(len / 2, entries, alloc);
+        let (key, value) = entries.next()?;
+        let right = Node::from_sorted(len - len / 2 - 1, entries, alloc);
+        let node = Node {
+            entry: Entry::new(key, value),
+            size: len,
+            left,
+            right,
+        };
+        Some(AllocBox::new_in(node, alloc.clone()))
+    }
+}
+
+impl<K, V, A> Node<K, V, A>
+where
+    K: Ord,
+    V: Value + ?Sized,
+    A: Allocator,
+{
+    /// returns an optional reference to the `value` with key `key`
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entry(key).map(Entry::value)
     }
 
     /// returns a reference to the key
@@ -50,176 +118,665 @@ where
 
     /// returns a reference to the value
     pub fn value(&self) -> &V {
-        &self.entry.value
+        self.entry.value()
     }
 
     /// returns a mutable reference to the value
     pub fn value_mut(&mut self) -> &mut V {
-        &mut self.entry.value
+        self.entry.value_mut()
+    }
+
+    /// returns a reference to the key and a mutable reference to the value
+    pub fn split_mut(&mut self) -> (&K, &mut V) {
+        self.entry.split_mut()
     }
 
     /// returns an optional mutable reference to the `value` with key `key`
-    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
-        match key.cmp(self.key()) {
-            Ordering::Less => match self.left {
-                Some(ref mut node) => node.get_mut(key),
-                None => None,
-            },
-            Ordering::Greater => match self.right {
-                Some(ref mut node) => node.get_mut(key),
-                None => None,
-            },
-            Ordering::Equal => Some(&mut self.entry.value),
-        }
-    }
-
-    /// sets the value of the entry with key `key` to `value`
-    /// if `key` already exists, the value is overwritten
-    /// otherwise, a new entry is added
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entry_mut(key).map(Entry::value_mut)
+    }
+
+    /// adds `entry` to the subtree rooted at `link`, in a node from `alloc`
+    /// if an entry with the same key already exists, its value is
+    /// overwritten
     /// returns `true` if a *new* entry is added (not overwritten)
     /// else returns `false`
-    pub fn insert(&mut self, key: K, value: V) -> bool {
-        match key.cmp(self.entry.key()) {
-            Ordering::Less => match self.left {
-                Some(ref mut node) => node.insert(key, value),
-                None => {
-                    self.left = Some(Box::from(Node::new(key, value)));
-                    true
+    pub fn insert(
+        link: &mut Link<K, V, A>,
+        entry: Entry<K, V>,
+        alloc: &A,
+    ) -> bool
+    where
+        A: Clone,
+    {
+        Node::put(link, entry, true, alloc)
+    }
+
+    /// adds `entry` to the subtree rooted at `link`, in a node from `alloc`
+    /// if an entry with the same key already exists, the old value is kept
+    /// returns `true` if a new entry is created
+    /// else returns false
+    pub fn try_insert(
+        link: &mut Link<K, V, A>,
+        entry: Entry<K, V>,
+        alloc: &A,
+    ) -> bool
+    where
+        A: Clone,
+    {
+        Node::put(link, entry, false, alloc)
+    }
+
+    /// the sizes are counted up on the way down, as if `entry` were new,
+    /// and only counted back down, in a second descent, if its key turns
+    /// out to be there already
+    fn put(
+        link: &mut Link<K, V, A>,
+        mut entry: Entry<K, V>,
+        overwrite: bool,
+        alloc: &A,
+    ) -> bool
+    where
+        A: Clone,
+    {
+        let found = Node::descend(link, entry.key(), |size| *size += 1);
+        match found {
+            Some(node) => {
+                if overwrite {
+                    mem::swap(&mut node.entry.value, &mut entry.value);
+                }
+            }
+            None => {
+                let node = Node {
+                    entry,
+                    size: 1,
+                    left: None,
+                    right: None,
+                };
+                *found = Some(AllocBox::new_in(node, alloc.clone()));
+                return true;
+            }
+        }
+        Node::descend(link, entry.key(), |size| *size -= 1);
+        false
+    }
+
+    /// adds `entry` to the subtree rooted at `link`, in a node from `alloc`,
+    /// where `index` of the entries in the subtree have smaller keys
+    /// the key of `entry` must not be in the subtree yet
+    /// returns a mutable reference to the new entry
+    ///
+    /// the way down follows from `index`, so no keys are compared
+    pub fn insert_nth<'a>(
+        mut link: &'a mut Link<K, V, A>,
+        mut index: usize,
+        entry: Entry<K, V>,
+        alloc: &A,
+    ) -> &'a mut Entry<K, V>
+    where
+        A: Clone,
+    {
+        while let Some(node) = link {
+            node.size += 1;
+            let smaller = Node::size_of(&node.left);
+            link = if index <= smaller {
+                &mut node.left
+            } else {
+                index -= smaller + 1;
+                &mut node.right
+            };
+        }
+        let node = Node {
+            entry,
+            size: 1,
+            left: None,
+            right: None,
+        };
+        &mut link.insert(AllocBox::new_in(node, alloc.clone())).entry
+    }
+
+    /// removes the entry with key `key` from the subtree rooted at `link`
+    /// returns the removed entry, or `None` if no such entry exists
+    ///
+    /// the sizes are counted down on the way down, as if `key` were there,
+    /// and only counted back up, in a second descent, if it turns out not
+    /// to be
+    /// a node with two children is replaced by its in-order successor
+    pub fn remove<Q>(link: &mut Link<K, V, A>, key: &Q) -> Option<Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let found = Node::descend(link, key, |size| *size -= 1);
+        if found.is_some() {
+            return Node::remove_root(found);
+        }
+        Node::descend(link, key, |size| *size += 1);
+        None
+    }
+
+    /// removes the entry with `index` smaller entries from the subtree
+    /// rooted at `link` and returns it
+    /// returns `None` if `index` is out of bounds
+    ///
+    /// the way down follows from `index`, so no keys are compared
+    pub fn remove_nth(
+        mut link: &mut Link<K, V, A>,
+        mut index: usize,
+    ) -> Option<Entry<K, V>> {
+        if index >= Node::size_of(link) {
+            return None;
+        }
+        loop {
+            let smaller = Node::size_of(&link.as_ref()?.left);
+            link = match (index.cmp(&smaller), link) {
+                (Ordering::Less, Some(node)) => {
+                    node.size -= 1;
+                    &mut node.left
                 }
-            },
-            Ordering::Greater => match self.right {
-                Some(ref mut node) => node.insert(key, value),
-                None => {
-                    self.right = Some(Box::from(Node::new(key, value)));
-                    true
+                (Ordering::Greater, Some(node)) => {
+                    node.size -= 1;
+                    index -= smaller + 1;
+                    &mut node.right
                 }
-            },
-            Ordering::Equal => {
-                *self.entry.value = value;
-                false
+                (_, link) => return Node::remove_root(link),
+            };
+        }
+    }
+
+    /// removes the root of the subtree rooted at `link` and returns its entry
+    /// returns `None` if `link` is empty
+    /// the sizes of any nodes above `link` are left to the caller
+    ///
+    /// a node with two children is replaced by its in-order successor
+    pub fn remove_root(link: &mut Link<K, V, A>) -> Option<Entry<K, V>> {
+        let mut node = link.take()?;
+        *link = match (node.left.take(), node.right.take()) {
+            (None, None) => None,
+            (Some(child), None) | (None, Some(child)) => Some(child),
+            (Some(left), Some(right)) => {
+                let mut right = Some(right);
+                let mut successor = Node::take_smallest(&mut right)?;
+                successor.size = node.size - 1;
+                successor.left = Some(left);
+                successor.right = right;
+                Some(successor)
+            }
+        };
+        Some(AllocBox::into_inner(node).entry)
+    }
+
+    /// moves every entry with a key of at least `key` out of the subtree
+    /// rooted at `link` and returns them as a tree of their own
+    ///
+    /// the nodes are only relinked, along the path to where `key` would be,
+    /// so this takes time proportional to the height of the subtree
+    /// which way each node goes follows from the rank of `key`, so once
+    /// that is known no more keys are compared
+    pub fn split_off<Q>(link: &mut Link<K, V, A>, key: &Q) -> Link<K, V, A>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        // the number of entries below `key` in the subtree at `current`
+        let mut below = link.as_ref().map_or(0, |node| node.rank(key));
+        let mut current = link.take();
+        let mut split = None;
+        let (mut left, mut right) = (link, &mut split);
+        while let Some(mut node) = current {
+            let smaller = Node::size_of(&node.left);
+            if smaller < below {
+                current = node.right.take();
+                node.size = below;
+                below -= smaller + 1;
+                left = &mut left.insert(node).right;
+            } else {
+                current = node.left.take();
+                node.size -= below;
+                right = &mut right.insert(node).left;
             }
         }
+        split
     }
 
-    /// inserts a new entry with key and value `key`, `value`
-    /// if an entry with `key` already exists, does not overwrite old value
-    /// returns `true` if a new entry is created
-    /// else returns false
-    pub fn try_insert(&mut self, key: K, value: V) -> bool {
-        match key.cmp(self.entry.key()) {
-            Ordering::Less => match self.left {
-                Some(ref mut node) => node.insert(key, value),
-                None => {
-                    self.left = Some(Box::from(Node::new(key, value)));
-                    true
+    /// returns the link in the subtree rooted at `link` that holds the entry
+    /// with key `key`, or the empty link where it would be inserted,
+    /// calling `adjust` on the size of every node above it
+    /// which is done before it is known whether `key` is in the subtree,
+    /// so the caller has to undo it if that turns out to matter
+    fn descend<'a, Q>(
+        mut link: &'a mut Link<K, V, A>,
+        key: &Q,
+        adjust: impl Fn(&mut usize),
+    ) -> &'a mut Link<K, V, A>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        loop {
+            let ordering = match *link {
+                Some(ref node) => key.cmp(node.key().borrow()),
+                None => return link,
+            };
+            link = match (ordering, link) {
+                (Ordering::Less, Some(node)) => {
+                    adjust(&mut node.size);
+                    &mut node.left
                 }
-            },
-            Ordering::Greater => match self.right {
-                Some(ref mut node) => node.insert(key, value),
-                None => {
-                    self.right = Some(Box::from(Node::new(key, value)));
-                    true
+                (Ordering::Greater, Some(node)) => {
+                    adjust(&mut node.size);
+                    &mut node.right
                 }
-            },
-            Ordering::Equal => false,
+                (_, link) => return link,
+            };
         }
     }
 
-    // pub fn remove(&mut self, key: &K) -> Result<(), RemoveError> {
-    //     match key.cmp(self.key()) {
-    //         Ordering::Less => todo!(),
-    //         Ordering::Greater => todo!(),
-    //         Ordering::Equal => Err(RemoveError::RemoveMeIsRoot),
-    //     }
-    // }
+    /// returns the number of entries in the subtree rooted at `link` with
+    /// keys smaller than `key`, along with the node with key `key`,
+    /// if there is one
+    /// nothing is changed, so this can be followed up by `insert_nth` or
+    /// `remove_nth` with the same rank
+    pub fn search<'a, Q>(
+        mut link: &'a mut Link<K, V, A>,
+        key: &Q,
+    ) -> (usize, Option<&'a mut Node<K, V, A>>)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut rank = 0;
+        while let Some(node) = link {
+            link = match key.cmp(node.key().borrow()) {
+                Ordering::Less => &mut node.left,
+                Ordering::Equal => {
+                    return (rank + Node::size_of(&node.left), Some(node))
+                }
+                Ordering::Greater => {
+                    rank += Node::size_of(&node.left) + 1;
+                    &mut node.right
+                }
+            };
+        }
+        (rank, None)
+    }
+
+    /// detaches the node with the smallest key from the subtree rooted at
+    /// `link`, putting its right child in its place
+    fn take_smallest(mut link: &mut Link<K, V, A>) -> Link<K, V, A> {
+        while link.as_ref()?.left.is_some() {
+            let node = link.as_mut()?;
+            node.size -= 1;
+            link = &mut node.left;
+        }
+        let mut node = link.take()?;
+        *link = node.right.take();
+        Some(node)
+    }
+
+    /// detaches the node with the largest key from the subtree rooted at
+    /// `link`, putting its left child in its place
+    fn take_largest(mut link: &mut Link<K, V, A>) -> Link<K, V, A> {
+        while link.as_ref()?.right.is_some() {
+            let node = link.as_mut()?;
+            node.size -= 1;
+            link = &mut node.right;
+        }
+        let mut node = link.take()?;
+        *link = node.left.take();
+        Some(node)
+    }
+
+    /// removes the smallest entry from the subtree rooted at `link`
+    /// and returns it
+    pub fn pop_smallest(link: &mut Link<K, V, A>) -> Option<Entry<K, V>> {
+        Node::take_smallest(link).map(|node| AllocBox::into_inner(node).entry)
+    }
+
+    /// removes the largest entry from the subtree rooted at `link`
+    /// and returns it
+    pub fn pop_largest(link: &mut Link<K, V, A>) -> Option<Entry<K, V>> {
+        Node::take_largest(link).map(|node| AllocBox::into_inner(node).entry)
+    }
 
     /// returns a reference to the `Entry` with key `key`
-    pub fn entry(&self, key: &K) -> Option<&Entry<K, V>> {
-        match key.cmp(self.key()) {
-            Ordering::Less => match self.left {
-                Some(ref node) => node.entry(key),
-                None => None,
-            },
-            Ordering::Greater => match self.right {
-                Some(ref node) => node.entry(key),
-                None => None,
-            },
-            Ordering::Equal => Some(&self.entry),
+    pub fn entry<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self;
+        loop {
+            node = match key.cmp(node.key().borrow()) {
+                Ordering::Less => node.left.as_deref()?,
+                Ordering::Greater => node.right.as_deref()?,
+                Ordering::Equal => return Some(&node.entry),
+            };
         }
     }
 
     /// returns a mutable reference to the `Entry` with key `key`
-    pub fn entry_mut(&mut self, key: &K) -> Option<&mut Entry<K, V>> {
-        match key.cmp(self.entry.key()) {
-            Ordering::Less => match self.left {
-                Some(ref mut entry) => entry.entry_mut(key),
-                None => None,
-            },
-            Ordering::Greater => match self.right {
-                Some(ref mut node) => node.entry_mut(key),
-                None => None,
-            },
-            Ordering::Equal => Some(&mut self.entry),
+    pub fn entry_mut<Q>(&mut self, key: &Q) -> Option<&mut Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self;
+        loop {
+            node = match key.cmp(node.key().borrow()) {
+                Ordering::Less => node.left.as_deref_mut()?,
+                Ordering::Greater => node.right.as_deref_mut()?,
+                Ordering::Equal => return Some(&mut node.entry),
+            };
         }
     }
 
-    pub fn merge(&mut self, other: Node<K, V>) {
-        todo!()
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entry(key).is_some()
     }
 
-    pub fn contains(&self, key: &K) -> bool {
-        match key.cmp(self.entry.key()) {
-            Ordering::Less => match self.left {
-                Some(ref node) => node.contains(key),
-                None => false,
-            },
-            Ordering::Greater => match self.right {
-                Some(ref node) => node.contains(key),
-                None => false,
-            },
-            Ordering::Equal => true,
+    /// returns the number of entries in the subtree rooted at `self`
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// returns the number of entries in the subtree rooted at `link`
+    pub fn size_of(link: &Link<K, V, A>) -> usize {
+        link.as_ref().map_or(0, |node| node.size)
+    }
+
+    /// returns the entry with `index` smaller entries
+    /// in the subtree rooted at `self`
+    pub fn nth(&self, mut index: usize) -> Option<&Entry<K, V>> {
+        let mut node = self;
+        loop {
+            let left = Node::size_of(&node.left);
+            node = match index.cmp(&left) {
+                Ordering::Less => node.left.as_deref()?,
+                Ordering::Equal => return Some(&node.entry),
+                Ordering::Greater => {
+                    index -= left + 1;
+                    node.right.as_deref()?
+                }
+            };
         }
     }
 
-    pub fn size(&self) -> usize {
-        let mut size = 1;
-        if let Some(ref node) = self.right {
-            size += node.size();
+    /// returns a mutable reference to the entry with `index` smaller entries
+    /// in the subtree rooted at `self`
+    pub fn nth_mut(&mut self, mut index: usize) -> Option<&mut Entry<K, V>> {
+        let mut node = self;
+        loop {
+            let left = Node::size_of(&node.left);
+            node = match index.cmp(&left) {
+                Ordering::Less => node.left.as_deref_mut()?,
+                Ordering::Equal => return Some(&mut node.entry),
+                Ordering::Greater => {
+                    index -= left + 1;
+                    node.right.as_deref_mut()?
+                }
+            };
         }
-        if let Some(ref entry) = self.left {
-            size += entry.size();
+    }
+
+    /// returns the number of entries in the subtree rooted at `self`
+    /// with keys smaller than `key`
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut rank = 0;
+        let mut link = Some(self);
+        while let Some(node) = link {
+            link = match key.cmp(node.key().borrow()) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Equal => return rank + Node::size_of(&node.left),
+                Ordering::Greater => {
+                    rank += Node::size_of(&node.left) + 1;
+                    node.right.as_deref()
+                }
+            };
+        }
+        rank
+    }
+
+    /// returns the entry with the smallest key greater than `key`,
+    /// or equal to it if `inclusive` is set
+    pub fn above<Q>(&self, key: &Q, inclusive: bool) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut found = None;
+        let mut link = Some(self);
+        while let Some(node) = link {
+            link = match key.cmp(node.key().borrow()) {
+                Ordering::Less => {
+                    found = Some(&node.entry);
+                    node.left.as_deref()
+                }
+                Ordering::Equal if inclusive => return Some(&node.entry),
+                _ => node.right.as_deref(),
+            };
+        }
+        found
+    }
+
+    /// returns the entry with the largest key smaller than `key`,
+    /// or equal to it if `inclusive` is set
+    pub fn below<Q>(&self, key: &Q, inclusive: bool) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut found = None;
+        let mut link = Some(self);
+        while let Some(node) = link {
+            link = match key.cmp(node.key().borrow()) {
+                Ordering::Greater => {
+                    found = Some(&node.entry);
+                    node.right.as_deref()
+                }
+                Ordering::Equal if inclusive => return Some(&node.entry),
+                _ => node.left.as_deref(),
+            };
         }
-        size
+        found
     }
 
     /// returns a reference to the smallest entry
     pub fn smallest(&self) -> &Entry<K, V> {
-        match self.left {
-            Some(ref entry) => entry.smallest(),
-            None => &self.entry,
+        let mut node = self;
+        while let Some(ref left) = node.left {
+            node = left;
         }
+        &node.entry
     }
 
     /// returns a mutable reference to the smallest entry
     pub fn smallest_mut(&mut self) -> &mut Entry<K, V> {
-        match self.left {
-            Some(ref mut entry) => entry.smallest_mut(),
-            None => &mut self.entry,
+        let mut node = self;
+        while let Some(ref mut left) = node.left {
+            node = left;
         }
+        &mut node.entry
     }
+
     /// returns a reference to the largest entry
     pub fn largest(&self) -> &Entry<K, V> {
-        match self.right {
-            Some(ref entry) => entry.largest(),
-            None => &self.entry,
+        let mut node = self;
+        while let Some(ref right) = node.right {
+            node = right;
         }
+        &node.entry
     }
 
     /// returns a mutable reference to the largest entry
     pub fn largest_mut(&mut self) -> &mut Entry<K, V> {
-        match self.left {
-            Some(ref mut entry) => entry.largest_mut(),
-            None => &mut self.entry,
+        let mut node = self;
+        while let Some(ref mut right) = node.right {
+            node = right;
+        }
+        &mut node.entry
+    }
+
+    /// drops the subtree rooted at `root` one node at a time,
+    /// so that even a degenerate tree cannot overflow the stack
+    /// flattening it into a vine first takes no memory of its own
+    pub fn dismantle(root: AllocBox<Node<K, V, A>, A>) {
+        drop(Vine::from_tree(Some(root)));
+    }
+}
+
+impl<'a, K, V, A> Expand for &'a Node<K, V, A>
+where
+    K: Ord,
+    V: Value + ?Sized,
+    A: Allocator,
+{
+    type Entry = (&'a K, &'a V);
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let (left, entry, right) = self.split();
+        [
+            left.map(Step::Node),
+            Some(Step::Entry(entry)),
+            right.map(Step::Node),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl<K, V, A> Split for &Node<K, V, A>
+where
+    K: Ord,
+    V: Value + ?Sized,
+    A: Allocator,
+{
+    type Key = K;
+
+    fn split(self) -> (Option<Self>, Self::Entry, Option<Self>) {
+        (
+            self.left.as_deref(),
+            self.entry.split(),
+            self.right.as_deref(),
+        )
+    }
+
+    fn key(entry: &Self::Entry) -> &K {
+        entry.0
+    }
+}
+
+impl<'a, K, V, A> Expand for &'a mut Node<K, V, A>
+where
+    K: Ord,
+    V: Value + ?Sized,
+    A: Allocator,
+{
+    type Entry = (&'a K, &'a mut V);
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let (left, entry, right) = self.split();
+        [
+            left.map(Step::Node),
+            Some(Step::Entry(entry)),
+            right.map(Step::Node),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl<K, V, A> Split for &mut Node<K, V, A>
+where
+    K: Ord,
+    V: Value + ?Sized,
+    A: Allocator,
+{
+    type Key = K;
+
+    fn split(self) -> (Option<Self>, Self::Entry, Option<Self>) {
+        (
+            self.left.as_deref_mut(),
+            self.entry.split_mut(),
+            self.right.as_deref_mut(),
+        )
+    }
+
+    fn key(entry: &Self::Entry) -> &K {
+        entry.0
+    }
+}
+
+/// an owned subtree, as handed out by `IntoIter`
+/// unlike a plain `AllocBox<Node>`, it is dismantled iteratively when dropped
+pub struct Subtree<K, V, A = Global>(Link<K, V, A>)
+where
+    K: Ord,
+    A: Allocator;
+
+impl<K, V, A> Subtree<K, V, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    pub fn new(root: AllocBox<Node<K, V, A>, A>) -> Subtree<K, V, A> {
+        Subtree(Some(root))
+    }
+}
+
+impl<K, V, A> Drop for Subtree<K, V, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        if let Some(root) = self.0.take() {
+            Node::dismantle(root);
         }
     }
 }
+
+impl<K, V, A> Expand for Subtree<K, V, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    type Entry = (K, V);
+
+    fn expand(
+        mut self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let (left, entry, right) = match self.0.take() {
+            Some(node) => {
+                let Node {
+                    entry, left, right, ..
+                } = AllocBox::into_inner(node);
+                (left, Some(entry.into_pair()), right)
+            }
+            None => (None, None, None),
+        };
+        [
+            left.map(Subtree::new).map(Step::Node),
+            entry.map(Step::Entry),
+            right.map(Subtree::new).map(Step::Node),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
@@ -1,13 +1,26 @@
 //! a binary search tree with keys but no associated value
 //! a degenerate form of `BsTreeMap`
 
-use super::{map::BsTreeMap, node::Node};
+use super::map::{self, BsTreeMap};
+use super::vine::Rebuild;
+use crate::alloc::{Allocator, Global};
 use crate::entry::Entry;
-pub struct BsTreeSet<T>
+use crate::iter::Keys;
+use crate::set_ops;
+use alloc_crate::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::{BitAnd, BitOr, BitXor, RangeBounds, Sub};
+
+/// every node is allocated from the allocator of the map underneath
+pub struct BsTreeSet<T, A = Global>
 where
     T: Ord,
+    A: Allocator,
 {
-    map: BsTreeMap<T, ()>,
+    map: BsTreeMap<T, (), A>,
 }
 
 impl<K> BsTreeSet<K>
@@ -15,20 +28,35 @@ where
     K: Ord,
 {
     pub fn new() -> BsTreeSet<K> {
+        BsTreeSet::new_in(Global)
+    }
+}
+
+impl<K, A> BsTreeSet<K, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    /// creates an empty `BsTreeSet` whose nodes come from `alloc`
+    pub const fn new_in(alloc: A) -> BsTreeSet<K, A> {
         BsTreeSet {
-            map: BsTreeMap::new(),
+            map: BsTreeMap::new_in(alloc),
         }
     }
 
-    /// inserts a new entry with key `key`
-    /// if such an
-    pub fn insert(&mut self, key: K) {
-        self.map.insert(key, ());
+    /// returns a reference to the allocator of `self`
+    pub fn allocator(&self) -> &A {
+        self.map.allocator()
     }
 
     /// removes the entry with key `key`
-    pub fn remove(&mut self, key: &K) {
-        self.map.remove(key);
+    /// returns `true` if such an entry existed
+    pub fn remove<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.remove(key).is_some()
     }
 
     /// returns the number of entries in `self`
@@ -38,14 +66,14 @@ where
 
     /// returns true if `self` contains an entry `key`
     /// otherwise returns false
-    pub fn contains(&self, key: &K) -> bool {
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         self.map.contains(key)
     }
 
-    pub fn extend(&mut self, other: BsTreeSet<K>) {
-        self.map.merge(other.map);
-    }
-
     pub fn smallest(&self) -> Option<&Entry<K, ()>> {
         self.map.smallest()
     }
@@ -59,7 +87,169 @@ where
     }
 
     pub fn largest_mut(&mut self) -> Option<&mut Entry<K, ()>> {
-        self.map.smallest_mut()
+        self.map.largest_mut()
+    }
+
+    /// returns the key with `index` smaller keys,
+    /// or `None` if `index` is out of bounds
+    pub fn nth(&self, index: usize) -> Option<&K> {
+        self.map.select(index)
+    }
+
+    /// returns the number of keys smaller than `key`
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.rank(key)
+    }
+
+    /// removes all keys from `self`
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// returns an iterator over the keys of `self`, in order
+    pub fn iter(&self) -> Iter<'_, K, A> {
+        Keys::new(self.map.iter())
+    }
+
+    /// returns an iterator over the keys of `self` that lie within `range`,
+    /// in order
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, A>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Keys::new(self.map.range(range))
+    }
+
+    /// returns an iterator over the keys in `self` or `other`, in order
+    pub fn union<'a>(&'a self, other: &'a BsTreeSet<K, A>) -> Union<'a, K, A> {
+        Union::new(self.iter(), other.iter())
+    }
+
+    /// returns an iterator over the keys in both `self` and `other`,
+    /// in order
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a BsTreeSet<K, A>,
+    ) -> Intersection<'a, K, A> {
+        Intersection::new(self.iter(), other.iter())
+    }
+
+    /// returns an iterator over the keys in `self` but not in `other`,
+    /// in order
+    pub fn difference<'a>(
+        &'a self,
+        other: &'a BsTreeSet<K, A>,
+    ) -> Difference<'a, K, A> {
+        Difference::new(self.iter(), other.iter())
+    }
+
+    /// returns an iterator over the keys in exactly one of `self` and
+    /// `other`, in order
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a BsTreeSet<K, A>,
+    ) -> SymmetricDifference<'a, K, A> {
+        SymmetricDifference::new(self.iter(), other.iter())
+    }
+
+    /// returns `true` if every key in `self` is also in `other`
+    pub fn is_subset(&self, other: &BsTreeSet<K, A>) -> bool {
+        self.size() <= other.size() && self.difference(other).next().is_none()
+    }
+
+    /// returns `true` if every key in `other` is also in `self`
+    pub fn is_superset(&self, other: &BsTreeSet<K, A>) -> bool {
+        other.is_subset(self)
+    }
+
+    /// returns `true` if `self` and `other` have no keys in common
+    pub fn is_disjoint(&self, other: &BsTreeSet<K, A>) -> bool {
+        self.intersection(other).next().is_none()
+    }
+}
+
+/// the methods that add or take out nodes
+impl<K, A> BsTreeSet<K, A>
+where
+    K: Ord,
+    A: Allocator + Clone,
+{
+    /// inserts a new entry with key `key`
+    /// if such an
+    pub fn insert(&mut self, key: K) {
+        self.map.insert(key, ());
+    }
+
+    /// removes the smallest key from `self` and returns it
+    /// returns `None` if `self` is empty
+    pub fn pop_first(&mut self) -> Option<K> {
+        self.map.pop_first().map(|(key, ())| key)
+    }
+
+    /// removes the largest key from `self` and returns it
+    /// returns `None` if `self` is empty
+    pub fn pop_last(&mut self) -> Option<K> {
+        self.map.pop_last().map(|(key, ())| key)
+    }
+
+    /// moves all keys of `other` into `self`, leaving `other` empty
+    ///
+    /// runs in linear time, and reuses the nodes of both sets
+    pub fn append(&mut self, other: &mut BsTreeSet<K, A>) {
+        self.map.append(&mut other.map);
+    }
+
+    /// keeps only the keys for which `f` returns `true`
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K) -> bool,
+    {
+        self.map.retain(|key, _| f(key));
+    }
+
+    /// returns an iterator that removes the keys for which `pred` returns
+    /// `true`, in order, and yields them
+    /// if the iterator is dropped early, the remaining keys are kept
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, F, A>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        ExtractIf {
+            rebuild: self.map.take_apart(),
+            pred,
+        }
+    }
+}
+
+impl<T, A> IntoIterator for BsTreeSet<T, A>
+where
+    T: Ord,
+    A: Allocator + Clone,
+{
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Keys::new(self.map.into_iter())
+    }
+}
+
+impl<'a, T, A> IntoIterator for &'a BsTreeSet<T, A>
+where
+    T: Ord,
+    A: Allocator,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
 }
 
@@ -71,3 +261,296 @@ where
         Self::new()
     }
 }
+
+impl<T, A> Clone for BsTreeSet<T, A>
+where
+    T: Ord + Clone,
+    A: Allocator + Clone,
+{
+    fn clone(&self) -> Self {
+        BsTreeSet {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<T, A> fmt::Debug for BsTreeSet<T, A>
+where
+    T: Ord + fmt::Debug,
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self).finish()
+    }
+}
+
+impl<T, A> PartialEq for BsTreeSet<T, A>
+where
+    T: Ord,
+    A: Allocator,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T, A> Eq for BsTreeSet<T, A>
+where
+    T: Ord,
+    A: Allocator,
+{
+}
+
+impl<T, A> Hash for BsTreeSet<T, A>
+where
+    T: Ord + Hash,
+    A: Allocator,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.map.hash(state);
+    }
+}
+
+impl<T, A> PartialOrd for BsTreeSet<T, A>
+where
+    T: Ord,
+    A: Allocator,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, A> Ord for BsTreeSet<T, A>
+where
+    T: Ord,
+    A: Allocator,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.map.cmp(&other.map)
+    }
+}
+
+impl<T> FromIterator<T> for BsTreeSet<T>
+where
+    T: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        BsTreeSet {
+            map: iter.into_iter().map(|key| (key, ())).collect(),
+        }
+    }
+}
+
+impl<T, A> Extend<T> for BsTreeSet<T, A>
+where
+    T: Ord,
+    A: Allocator + Clone,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|key| (key, ())));
+    }
+}
+
+impl<'a, T, A> Extend<&'a T> for BsTreeSet<T, A>
+where
+    T: Ord + Copy,
+    A: Allocator + Clone,
+{
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for BsTreeSet<T>
+where
+    T: Ord,
+{
+    fn from(array: [T; N]) -> Self {
+        Self::from_iter(array)
+    }
+}
+
+impl<T> From<Vec<T>> for BsTreeSet<T>
+where
+    T: Ord,
+{
+    fn from(vec: Vec<T>) -> Self {
+        Self::from_iter(vec)
+    }
+}
+
+impl<T> BitOr<&BsTreeSet<T>> for &BsTreeSet<T>
+where
+    T: Ord + Clone,
+{
+    type Output = BsTreeSet<T>;
+
+    /// returns the union of `self` and `rhs` as a new set
+    fn bitor(self, rhs: &BsTreeSet<T>) -> BsTreeSet<T> {
+        self.union(rhs).cloned().collect()
+    }
+}
+
+impl<T> BitAnd<&BsTreeSet<T>> for &BsTreeSet<T>
+where
+    T: Ord + Clone,
+{
+    type Output = BsTreeSet<T>;
+
+    /// returns the intersection of `self` and `rhs` as a new set
+    fn bitand(self, rhs: &BsTreeSet<T>) -> BsTreeSet<T> {
+        self.intersection(rhs).cloned().collect()
+    }
+}
+
+impl<T> Sub<&BsTreeSet<T>> for &BsTreeSet<T>
+where
+    T: Ord + Clone,
+{
+    type Output = BsTreeSet<T>;
+
+    /// returns the difference of `self` and `rhs` as a new set
+    fn sub(self, rhs: &BsTreeSet<T>) -> BsTreeSet<T> {
+        self.difference(rhs).cloned().collect()
+    }
+}
+
+impl<T> BitXor<&BsTreeSet<T>> for &BsTreeSet<T>
+where
+    T: Ord + Clone,
+{
+    type Output = BsTreeSet<T>;
+
+    /// returns the symmetric difference of `self` and `rhs` as a new set
+    fn bitxor(self, rhs: &BsTreeSet<T>) -> BsTreeSet<T> {
+        self.symmetric_difference(rhs).cloned().collect()
+    }
+}
+
+/// an iterator over the keys of a `BsTreeSet`, in order
+pub type Iter<'a, T, A = Global> = Keys<map::Iter<'a, T, (), A>>;
+
+/// an owning iterator over the keys of a `BsTreeSet`, in order
+pub type IntoIter<T, A = Global> = Keys<map::IntoIter<T, (), A>>;
+
+/// an iterator that removes the keys of a `BsTreeSet` matching a predicate
+/// as it goes, and returns them, in order
+/// keys it has not reached when dropped stay in the set
+pub struct ExtractIf<'a, T, F, A = Global>
+where
+    T: Ord,
+    A: Allocator,
+{
+    rebuild: Rebuild<'a, T, (), A>,
+    pred: F,
+}
+
+impl<T, F, A> Iterator for ExtractIf<'_, T, F, A>
+where
+    T: Ord,
+    F: FnMut(&T) -> bool,
+    A: Allocator,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pred = &mut self.pred;
+        let (key, ()) = self.rebuild.extract_next(|key, _| pred(key))?;
+        Some(key)
+    }
+}
+
+/// an iterator over the keys of a `BsTreeSet` that lie within a range,
+/// in order
+pub type Range<'a, T, A = Global> = Keys<map::Range<'a, T, (), A>>;
+
+/// an iterator over the keys in either of two `BsTreeSet`s, in order
+pub type Union<'a, T, A = Global> = set_ops::Union<Iter<'a, T, A>>;
+
+/// an iterator over the keys in both of two `BsTreeSet`s, in order
+pub type Intersection<'a, T, A = Global> =
+    set_ops::Intersection<Iter<'a, T, A>>;
+
+/// an iterator over the keys in the first of two `BsTreeSet`s but not the
+/// second, in order
+pub type Difference<'a, T, A = Global> = set_ops::Difference<Iter<'a, T, A>>;
+
+/// an iterator over the keys in exactly one of two `BsTreeSet`s, in order
+pub type SymmetricDifference<'a, T, A = Global> =
+    set_ops::SymmetricDifference<Iter<'a, T, A>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alloc::Counting;
+
+    #[test]
+    fn set_algebra() {
+        let evens: BsTreeSet<u32> = (0..20).step_by(2).collect();
+        let threes: BsTreeSet<u32> = (0..20).step_by(3).collect();
+
+        assert!(evens
+            .union(&threes)
+            .eq(&[0, 2, 3, 4, 6, 8, 9, 10, 12, 14, 15, 16, 18]));
+        assert!(evens.intersection(&threes).eq(&[0, 6, 12, 18]));
+        assert!(evens.difference(&threes).eq(&[2, 4, 8, 10, 14, 16]));
+        assert!(threes.difference(&evens).eq(&[3, 9, 15]));
+        assert!(evens
+            .symmetric_difference(&threes)
+            .eq(&[2, 3, 4, 8, 9, 10, 14, 15, 16]));
+
+        assert_eq!(&evens | &threes, evens.union(&threes).copied().collect());
+        assert_eq!(&evens & &threes, BsTreeSet::from([0, 6, 12, 18]));
+        assert_eq!(&threes - &evens, BsTreeSet::from([3, 9, 15]));
+        assert_eq!((&evens ^ &threes).size(), 9);
+
+        let sixes = &evens & &threes;
+        assert!(sixes.is_subset(&evens));
+        assert!(threes.is_superset(&sixes));
+        assert!(!evens.is_subset(&threes));
+        assert!(!evens.is_disjoint(&threes));
+        assert!(evens.is_disjoint(&(&threes - &evens)));
+
+        let empty = BsTreeSet::new();
+        assert!(empty.is_subset(&evens));
+        assert!(empty.is_disjoint(&evens));
+        assert!(evens.union(&empty).eq(evens.iter()));
+        assert!(empty.intersection(&evens).next().is_none());
+        assert_eq!(&evens - &empty, evens);
+    }
+
+    #[test]
+    fn retain_and_extract_if() {
+        let mut set: BsTreeSet<u32> = (0..20).collect();
+        set.retain(|key| key % 2 == 0);
+        assert!(set.iter().copied().eq((0..20).step_by(2)));
+
+        let mut small = set.extract_if(|key| *key < 10);
+        assert_eq!(small.next(), Some(0));
+        drop(small);
+        assert_eq!(set.size(), 9);
+        let small: Vec<u32> = set.extract_if(|key| *key < 10).collect();
+        assert_eq!(small, [2, 4, 6, 8]);
+        assert!(set.iter().copied().eq((10..20).step_by(2)));
+    }
+
+    #[test]
+    fn append_reuses_nodes() {
+        let counting = Counting::default();
+        let mut set = BsTreeSet::new_in(&counting);
+        let mut other = BsTreeSet::new_in(&counting);
+        for key in 0..100 {
+            set.insert(key * 2);
+            other.insert(key * 3);
+        }
+        assert_eq!(counting.allocated.get(), 200);
+        set.append(&mut other);
+        assert_eq!(counting.allocated.get(), 200);
+        assert_eq!(counting.live.get(), 166);
+        assert_eq!(set.size(), 166);
+        assert_eq!(other.size(), 0);
+        assert!(set.contains(&297) && set.contains(&198) && !set.contains(&1));
+        assert_eq!(set.nth(100), Some(&150));
+    }
+}
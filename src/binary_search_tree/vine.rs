@@ -0,0 +1,268 @@
+//! a vine: the nodes of a `BsTreeMap` strung into a list through `right`
+//! flattening a tree into a vine and building a balanced tree back out of
+//! one both take linear time and reuse the nodes, so the bulk operations
+//! of `BsTreeMap` need neither allocations nor scratch buffers
+use super::node::{Link, Node};
+use crate::alloc::{Allocator, Global};
+use crate::boxed::AllocBox;
+use crate::entry::Value;
+use core::mem;
+
+/// the order of the nodes is up to whoever fills the vine,
+/// but `into_tree` expects them in ascending order of their keys
+pub struct Vine<K, V, A = Global>
+where
+    K: Ord,
+    V: Value + ?Sized,
+    A: Allocator,
+{
+    head: Link<K, V, A>,
+    len: usize,
+}
+
+impl<K, V, A> Vine<K, V, A>
+where
+    K: Ord,
+    V: Value + ?Sized,
+    A: Allocator,
+{
+    /// creates an empty vine
+    pub const fn new() -> Vine<K, V, A> {
+        Vine { head: None, len: 0 }
+    }
+
+    /// flattens the tree rooted at `root` into a vine,
+    /// in descending order of the keys
+    pub fn from_tree(root: Link<K, V, A>) -> Vine<K, V, A> {
+        let mut vine = Vine::new();
+        let mut current = root;
+        while let Some(mut node) = current {
+            current = match node.left.take() {
+                // rotate right, until the smallest node is on top
+                Some(mut left) => {
+                    node.left = left.right.take();
+                    left.right = Some(node);
+                    Some(left)
+                }
+                None => {
+                    let right = node.right.take();
+                    vine.push(node);
+                    right
+                }
+            };
+        }
+        vine
+    }
+
+    /// returns the number of nodes in `self`
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// returns a reference to the first node of `self`
+    pub fn peek(&self) -> Option<&Node<K, V, A>> {
+        self.head.as_deref()
+    }
+
+    /// returns a mutable reference to the first node of `self`
+    pub fn peek_mut(&mut self) -> Option<&mut Node<K, V, A>> {
+        self.head.as_deref_mut()
+    }
+
+    /// puts `node`, which must not have a left child, in front of `self`
+    pub fn push(&mut self, mut node: AllocBox<Node<K, V, A>, A>) {
+        node.right = self.head.take();
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    /// detaches the first node of `self` and returns it
+    pub fn pop(&mut self) -> Link<K, V, A> {
+        let mut node = self.head.take()?;
+        self.head = node.right.take();
+        self.len -= 1;
+        Some(node)
+    }
+
+    /// moves every node of `other` in front of `self`,
+    /// reversing their order
+    pub fn push_reversed(&mut self, other: &mut Vine<K, V, A>) {
+        while let Some(node) = other.pop() {
+            self.push(node);
+        }
+    }
+
+    /// sorts `self` in ascending order of the keys, keeping nodes with
+    /// equal keys in the order they were in
+    /// a merge sort that only relinks the nodes, recursing log n deep
+    pub fn sort(&mut self) {
+        let len = self.len;
+        *self = self.sort_first(len);
+    }
+
+    /// detaches the first `len` nodes of `self` and returns them sorted
+    fn sort_first(&mut self, len: usize) -> Vine<K, V, A> {
+        let mut sorted = Vine::new();
+        if len <= 1 {
+            if let Some(node) = self.pop().filter(|_| len == 1) {
+                sorted.push(node);
+            }
+            return sorted;
+        }
+        let mut left = self.sort_first(len / 2);
+        let mut right = self.sort_first(len - len / 2);
+        // merged in descending order, so that on a tie, the node from
+        // `left` ends up first once it is reversed
+        let mut merged = Vine::new();
+        loop {
+            let next = match (left.peek(), right.peek()) {
+                (Some(low), Some(high)) if high.key() < low.key() => &mut right,
+                (Some(_), _) => &mut left,
+                (None, Some(_)) => &mut right,
+                (None, None) => break,
+            };
+            if let Some(node) = next.pop() {
+                merged.push(node);
+            }
+        }
+        sorted.push_reversed(&mut merged);
+        sorted
+    }
+
+    /// drops every node of `self`, which must be in ascending order, whose
+    /// key is the same as that of the node after it
+    pub fn dedup_keep_last(&mut self) {
+        let mut kept = Vine::new();
+        while let Some(node) = self.pop() {
+            if kept.peek().is_some_and(|last| last.key() == node.key()) {
+                kept.pop();
+            }
+            kept.push(node);
+        }
+        self.push_reversed(&mut kept);
+    }
+
+    /// builds a balanced tree out of `self`,
+    /// whose nodes must be in ascending order of their keys
+    pub fn into_tree(mut self) -> Link<K, V, A> {
+        let len = self.len;
+        self.build(len)
+    }
+
+    /// builds a balanced tree out of the first `len` nodes of `self`
+    /// the recursion only goes as deep as the tree it builds
+    fn build(&mut self, len: usize) -> Link<K, V, A> {
+        if len == 0 {
+            return None;
+        }
+        let left = self.build(len / 2);
+        let mut node = self.pop()?;
+        node.right = self.build(len - len / 2 - 1);
+        node.left = left;
+        node.size = len;
+        Some(node)
+    }
+}
+
+impl<K, V, A> Default for Vine<K, V, A>
+where
+    K: Ord,
+    V: Value + ?Sized,
+    A: Allocator,
+{
+    fn default() -> Self {
+        Vine::new()
+    }
+}
+
+/// drops the nodes one at a time, however long the vine is
+impl<K, V, A> Drop for Vine<K, V, A>
+where
+    K: Ord,
+    V: Value + ?Sized,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+/// takes a tree apart into two vines, and puts it back together when
+/// dropped, so that the tree stays whole even if a user callback panics
+/// in between
+/// `lower` is in descending order and `upper` in ascending order,
+/// and every node in `lower` must be smaller than every node in `upper`
+pub struct Rebuild<'a, K, V, A = Global>
+where
+    K: Ord,
+    A: Allocator,
+{
+    root: &'a mut Link<K, V, A>,
+    size: &'a mut usize,
+    pub lower: Vine<K, V, A>,
+    pub upper: Vine<K, V, A>,
+}
+
+impl<'a, K, V, A> Rebuild<'a, K, V, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    /// takes the tree rooted at `root`, of `size` entries, apart into `lower`
+    pub fn new(
+        root: &'a mut Link<K, V, A>,
+        size: &'a mut usize,
+    ) -> Rebuild<'a, K, V, A> {
+        let lower = Vine::from_tree(root.take());
+        *size = 0;
+        Rebuild {
+            root,
+            size,
+            lower,
+            upper: Vine::new(),
+        }
+    }
+
+    /// takes the tree rooted at `root`, of `size` entries, apart into `upper`
+    pub fn ascending(
+        root: &'a mut Link<K, V, A>,
+        size: &'a mut usize,
+    ) -> Rebuild<'a, K, V, A> {
+        let mut rebuild = Rebuild::new(root, size);
+        rebuild.upper.push_reversed(&mut rebuild.lower);
+        rebuild
+    }
+
+    /// walks `upper` from its smallest node, moving the nodes to `lower`
+    /// until `pred` returns `true` for one, which is removed instead
+    /// returns the entry of the removed node
+    ///
+    /// `pred` only ever sees a node that is still part of the tree
+    pub fn extract_next<F>(&mut self, mut pred: F) -> Option<(K, V)>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        while let Some(node) = self.upper.peek_mut() {
+            let (key, value) = node.split_mut();
+            let extract = pred(key, value);
+            let node = self.upper.pop()?;
+            if extract {
+                return Some(Node::into_pair(node));
+            }
+            self.lower.push(node);
+        }
+        None
+    }
+}
+
+impl<K, V, A> Drop for Rebuild<'_, K, V, A>
+where
+    K: Ord,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        self.upper.push_reversed(&mut self.lower);
+        *self.size = self.upper.len();
+        *self.root = mem::take(&mut self.upper).into_tree();
+    }
+}
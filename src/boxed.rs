@@ -0,0 +1,137 @@
+//! an owning pointer to a value on the heap of an `Allocator`
+//! the standard `Box` only takes an allocator on nightly, so the trees keep
+//! their nodes in this instead
+use crate::alloc::Allocator;
+use alloc_crate::alloc::{handle_alloc_error, Layout};
+use core::marker::PhantomData;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+
+/// like `Box<T, A>`, the allocator is kept alongside the pointer, which
+/// costs nothing for a zero-sized allocator such as `Global`
+pub struct AllocBox<T, A>
+where
+    A: Allocator,
+{
+    ptr: NonNull<T>,
+    alloc: A,
+    marker: PhantomData<T>,
+}
+
+// SAFETY: `AllocBox` owns its value just like a `Box` would
+unsafe impl<T: Send, A: Allocator + Send> Send for AllocBox<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for AllocBox<T, A> {}
+
+impl<T, A> AllocBox<T, A>
+where
+    A: Allocator,
+{
+    /// moves `value` into memory from `alloc`
+    /// aborts if `alloc` runs out of memory, like `Box::new`
+    pub fn new_in(value: T, alloc: A) -> AllocBox<T, A> {
+        let layout = Layout::new::<T>();
+        let ptr = match alloc.allocate(layout) {
+            Ok(ptr) => ptr.cast::<T>(),
+            Err(_) => handle_alloc_error(layout),
+        };
+        // SAFETY: `ptr` is fresh memory that fits a `T`
+        unsafe { ptr.as_ptr().write(value) };
+        AllocBox {
+            ptr,
+            alloc,
+            marker: PhantomData,
+        }
+    }
+
+    /// consumes `this`, freeing its memory and returning the value
+    pub fn into_inner(this: AllocBox<T, A>) -> T {
+        let (ptr, alloc) = AllocBox::into_raw(this);
+        // SAFETY: `ptr` holds a value, which is moved out before the memory
+        // goes back to the allocator it came from
+        unsafe {
+            let value = ptr.as_ptr().read();
+            alloc.deallocate(ptr.cast(), Layout::new::<T>());
+            value
+        }
+    }
+
+    /// consumes `this` without freeing it, returning the pointer and the
+    /// allocator that must eventually free it
+    pub fn into_raw(this: AllocBox<T, A>) -> (NonNull<T>, A) {
+        let this = ManuallyDrop::new(this);
+        // SAFETY: `this` is never used or dropped again
+        (this.ptr, unsafe { ptr::read(&this.alloc) })
+    }
+
+    /// takes back ownership of a pointer from `into_raw`
+    ///
+    /// # Safety
+    /// `ptr` must come from `into_raw` with `alloc` or an allocator that
+    /// shares its memory, and must not be used afterwards
+    pub unsafe fn from_raw_in(ptr: NonNull<T>, alloc: A) -> AllocBox<T, A> {
+        AllocBox {
+            ptr,
+            alloc,
+            marker: PhantomData,
+        }
+    }
+
+    /// moves the value out of `this`, and moves what `f` makes of it back
+    /// into the same memory
+    /// if `f` panics, the memory goes back to the allocator
+    pub fn map_in_place<F>(this: AllocBox<T, A>, f: F) -> AllocBox<T, A>
+    where
+        F: FnOnce(T) -> T,
+    {
+        let (ptr, alloc) = AllocBox::into_raw(this);
+        // SAFETY: a `MaybeUninit<T>` has the layout of a `T`, so the memory
+        // is freed the same way, but without dropping a value
+        let mut slot = unsafe {
+            AllocBox::from_raw_in(ptr.cast::<MaybeUninit<T>>(), alloc)
+        };
+        // SAFETY: `slot` holds a value, which is moved out and only
+        // counted as there again once it is replaced
+        let value = f(unsafe { slot.assume_init_read() });
+        slot.write(value);
+        let (ptr, alloc) = AllocBox::into_raw(slot);
+        // SAFETY: the memory holds a value again
+        unsafe { AllocBox::from_raw_in(ptr.cast(), alloc) }
+    }
+}
+
+impl<T, A> Deref for AllocBox<T, A>
+where
+    A: Allocator,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: `ptr` holds a value, which is borrowed along with `self`
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T, A> DerefMut for AllocBox<T, A>
+where
+    A: Allocator,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: `ptr` holds a value, which is borrowed along with `self`
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T, A> Drop for AllocBox<T, A>
+where
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        // SAFETY: `ptr` holds a value, which is dropped before the memory
+        // goes back to the allocator it came from
+        unsafe {
+            ptr::drop_in_place(self.ptr.as_ptr());
+            self.alloc.deallocate(self.ptr.cast(), Layout::new::<T>());
+        }
+    }
+}
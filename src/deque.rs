@@ -0,0 +1,243 @@
+//! a double-ended queue on the heap of an `Allocator`
+//! the standard `VecDeque` only takes an allocator on nightly, so the
+//! traversals keep their pending steps in this instead
+use crate::alloc::{Allocator, Global};
+use alloc_crate::alloc::{handle_alloc_error, Layout};
+use core::marker::PhantomData;
+use core::mem;
+use core::ptr::NonNull;
+
+/// a ring buffer of `capacity` slots, of which the `len` from `head` on,
+/// wrapping around at the end, hold values
+///
+/// values still in a deque when it is dropped are leaked, not dropped, so
+/// that a deque of borrowed handles does not need the borrow to last until
+/// it is dropped, like the standard `VecDeque` can on nightly
+/// a deque of values that need dropping has to be cleared first
+pub struct Deque<T, A = Global>
+where
+    A: Allocator,
+{
+    buffer: Buffer<A>,
+    capacity: usize,
+    head: usize,
+    len: usize,
+    marker: PhantomData<T>,
+}
+
+/// the memory of a deque, which is given back when dropped
+struct Buffer<A>
+where
+    A: Allocator,
+{
+    ptr: NonNull<u8>,
+    layout: Layout,
+    alloc: A,
+}
+
+// SAFETY: `Deque` owns its values just like a `VecDeque` would
+unsafe impl<T: Send, A: Allocator + Send> Send for Deque<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for Deque<T, A> {}
+
+impl<T, A> Deque<T, A>
+where
+    A: Allocator,
+{
+    /// creates an empty deque, which allocates from `alloc` once it grows
+    pub const fn new_in(alloc: A) -> Deque<T, A> {
+        Deque {
+            buffer: Buffer {
+                ptr: NonNull::<T>::dangling().cast(),
+                layout: Layout::new::<()>(),
+                alloc,
+            },
+            // zero-sized values never need any memory
+            capacity: if mem::size_of::<T>() == 0 {
+                usize::MAX
+            } else {
+                0
+            },
+            head: 0,
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// adds `value` to the front of `self`
+    pub fn push_front(&mut self, value: T) {
+        if self.len == self.capacity {
+            self.grow();
+        }
+        self.head = match self.head {
+            0 => self.capacity - 1,
+            head => head - 1,
+        };
+        self.len += 1;
+        // SAFETY: the slot at `head` is within the buffer, and was free
+        unsafe { self.ptr().add(self.head).write(value) };
+    }
+
+    /// adds `value` to the back of `self`
+    pub fn push_back(&mut self, value: T) {
+        if self.len == self.capacity {
+            self.grow();
+        }
+        let slot = self.slot(self.len);
+        self.len += 1;
+        // SAFETY: the slot after the last value is within the buffer,
+        // and was free
+        unsafe { self.ptr().add(slot).write(value) };
+    }
+
+    /// removes the first value and returns it
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let slot = self.head;
+        self.head = self.slot(1);
+        self.len -= 1;
+        // SAFETY: the slot at the old `head` held a value, which is no
+        // longer counted
+        Some(unsafe { self.ptr().add(slot).read() })
+    }
+
+    /// removes the last value and returns it
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.slot(self.len);
+        // SAFETY: the slot held the last value, which is no longer counted
+        Some(unsafe { self.ptr().add(slot).read() })
+    }
+
+    /// drops every value, keeping the buffer
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    fn ptr(&self) -> NonNull<T> {
+        self.buffer.ptr.cast()
+    }
+
+    /// returns the slot `offset` places after `head`, wrapping around
+    /// `offset` must be less than the capacity
+    fn slot(&self, offset: usize) -> usize {
+        let room = self.capacity - self.head;
+        if offset >= room {
+            offset - room
+        } else {
+            self.head + offset
+        }
+    }
+
+    /// doubles the capacity, moving the values to the start of a new buffer
+    fn grow(&mut self) {
+        let capacity = (self.capacity * 2).max(4);
+        let layout = match Layout::array::<T>(capacity) {
+            Ok(layout) => layout,
+            Err(_) => panic!("capacity overflow"),
+        };
+        let ptr = match self.buffer.alloc.allocate(layout) {
+            Ok(ptr) => ptr.cast::<T>(),
+            Err(_) => handle_alloc_error(layout),
+        };
+        // SAFETY: the buffer is full, so the values are the `head` slots
+        // from the end of the old buffer on, then the ones before it,
+        // and the new buffer is large enough for all of them
+        // the old buffer goes back to the allocator once they are moved
+        unsafe {
+            let back = self.capacity - self.head;
+            ptr.copy_from_nonoverlapping(self.ptr().add(self.head), back);
+            ptr.add(back)
+                .copy_from_nonoverlapping(self.ptr(), self.head);
+            self.buffer.free();
+        }
+        self.buffer.ptr = ptr.cast();
+        self.buffer.layout = layout;
+        self.capacity = capacity;
+        self.head = 0;
+    }
+}
+
+impl<A> Buffer<A>
+where
+    A: Allocator,
+{
+    /// gives the memory back to the allocator, unless there is none
+    ///
+    /// # Safety
+    /// the memory must not be used afterwards
+    unsafe fn free(&mut self) {
+        if self.layout.size() > 0 {
+            self.alloc.deallocate(self.ptr, self.layout);
+        }
+    }
+}
+
+impl<A> Drop for Buffer<A>
+where
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        // SAFETY: `self` is being dropped
+        unsafe { self.free() };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alloc::Counting;
+    use alloc_crate::vec::Vec;
+
+    #[test]
+    fn both_ends() {
+        let counting = Counting::default();
+        let mut deque = Deque::new_in(&counting);
+        for value in 0..10 {
+            deque.push_back(value);
+            deque.push_front(-value);
+        }
+        assert_eq!(deque.pop_front(), Some(-9));
+        assert_eq!(deque.pop_back(), Some(9));
+        let mut values = Vec::new();
+        while let Some(value) = deque.pop_front() {
+            values.push(value);
+            if let Some(value) = deque.pop_back() {
+                values.push(value);
+            }
+        }
+        assert_eq!(values.len(), 18);
+        assert_eq!(values[..4], [-8, 8, -7, 7]);
+        assert_eq!(deque.pop_back(), None);
+        assert_eq!(counting.allocated.get(), 4);
+        assert_eq!(counting.live.get(), 1);
+        drop(deque);
+        assert_eq!(counting.live.get(), 0);
+    }
+
+    #[test]
+    fn drops_values() {
+        let counting = Counting::default();
+        let mut deque = Deque::new_in(Global);
+        for value in 0..5 {
+            let mut inner = Deque::new_in(&counting);
+            inner.push_back(value);
+            deque.push_front(inner);
+        }
+        deque.pop_back();
+        assert_eq!(counting.live.get(), 4);
+        deque.clear();
+        assert_eq!(counting.live.get(), 0);
+
+        let mut zero_sized = Deque::new_in(&counting);
+        for _ in 0..10 {
+            zero_sized.push_front(());
+        }
+        assert_eq!(zero_sized.pop_back(), Some(()));
+        assert_eq!(counting.allocated.get(), 5);
+    }
+}
@@ -1,16 +1,184 @@
 //! an entry structure for maps
+use alloc_crate::boxed::Box;
+use core::fmt;
 
-#[derive(Debug, PartialEq)]
-pub struct Entry<K, V: ?Sized> {
+/// how an `Entry` stores a value of type `Self`
+/// sized values are stored inline, so an entry costs no allocation of its
+/// own; unsized values are stored behind a `Box`
+///
+/// this is implemented for every sized type, for `str` and for slices,
+/// and for trait objects of the closure traits, `Any`, `Error`, `Debug`
+/// and `Display`, also with `Send` and `Send + Sync`
+/// trait objects of other traits can opt in, for example for a trait
+/// `Handler`:
+///
+/// ```
+/// use terees::binary_search_tree::map::{BsTreeMap, Value};
+///
+/// trait Handler {
+///     fn handle(&self) -> u32;
+/// }
+///
+/// impl Value for dyn Handler {
+///     type Slot = Box<dyn Handler>;
+///
+///     fn from_box(value: Box<Self>) -> Self::Slot {
+///         value
+///     }
+///
+///     fn into_box(slot: Self::Slot) -> Box<Self> {
+///         slot
+///     }
+///
+///     fn get(slot: &Self::Slot) -> &Self {
+///         &**slot
+///     }
+///
+///     fn get_mut(slot: &mut Self::Slot) -> &mut Self {
+///         &mut **slot
+///     }
+/// }
+///
+/// struct Fixed(u32);
+///
+/// impl Handler for Fixed {
+///     fn handle(&self) -> u32 {
+///         self.0
+///     }
+/// }
+///
+/// let mut handlers: BsTreeMap<&str, dyn Handler> = BsTreeMap::new();
+/// handlers.insert_boxed("answer", Box::new(Fixed(42)));
+/// assert_eq!(handlers.get("answer").map(|handler| handler.handle()), Some(42));
+/// ```
+pub trait Value {
+    /// what an entry actually holds
+    type Slot;
+
+    fn from_box(value: Box<Self>) -> Self::Slot;
+
+    fn into_box(slot: Self::Slot) -> Box<Self>;
+
+    fn get(slot: &Self::Slot) -> &Self;
+
+    fn get_mut(slot: &mut Self::Slot) -> &mut Self;
+}
+
+impl<T> Value for T {
+    type Slot = T;
+
+    fn from_box(value: Box<T>) -> T {
+        *value
+    }
+
+    fn into_box(slot: T) -> Box<T> {
+        Box::new(slot)
+    }
+
+    fn get(slot: &T) -> &T {
+        slot
+    }
+
+    fn get_mut(slot: &mut T) -> &mut T {
+        slot
+    }
+}
+
+/// implements `Value` for unsized types, storing them behind a `Box`
+/// each type comes after the generic parameters it needs, in brackets
+macro_rules! boxed_value {
+    ($([$($param:tt),* $(,)?] $ty:ty;)*) => {$(
+        impl<$($param),*> Value for $ty {
+            type Slot = Box<Self>;
+
+            fn from_box(value: Box<Self>) -> Box<Self> {
+                value
+            }
+
+            fn into_box(slot: Box<Self>) -> Box<Self> {
+                slot
+            }
+
+            fn get(slot: &Box<Self>) -> &Self {
+                &**slot
+            }
+
+            fn get_mut(slot: &mut Box<Self>) -> &mut Self {
+                &mut **slot
+            }
+        }
+    )*};
+}
+
+/// implements `Value` for `dyn $trait`, on its own and with `Send` and
+/// `Send + Sync`
+macro_rules! boxed_dyn_value {
+    ($([$($param:tt),*] $trait:path;)*) => {$(
+        boxed_value! {
+            ['a, $($param),*] dyn $trait + 'a;
+            ['a, $($param),*] dyn $trait + Send + 'a;
+            ['a, $($param),*] dyn $trait + Send + Sync + 'a;
+        }
+    )*};
+}
+
+boxed_value! {
+    [] str;
+    [T] [T];
+}
+
+// trait objects of crate-local traits can implement `Value` themselves,
+// but the orphan rules keep downstream crates from doing so for traits
+// of the standard library, so the common ones are covered here
+boxed_dyn_value! {
+    [R] Fn() -> R;
+    [A, R] Fn(A) -> R;
+    [A, B, R] Fn(A, B) -> R;
+    [A, B, C, R] Fn(A, B, C) -> R;
+    [R] FnMut() -> R;
+    [A, R] FnMut(A) -> R;
+    [A, B, R] FnMut(A, B) -> R;
+    [A, B, C, R] FnMut(A, B, C) -> R;
+    [R] FnOnce() -> R;
+    [A, R] FnOnce(A) -> R;
+    [A, B, R] FnOnce(A, B) -> R;
+    [A, B, C, R] FnOnce(A, B, C) -> R;
+    [] core::any::Any;
+    [] core::error::Error;
+    [] fmt::Debug;
+    [] fmt::Display;
+}
+
+pub struct Entry<K, V>
+where
+    V: Value + ?Sized,
+{
     key: K,
-    pub value: Box<V>,
+    pub value: V::Slot,
 }
 
 impl<K, V> Entry<K, V> {
     pub fn new(key: K, value: V) -> Entry<K, V> {
+        Entry { key, value }
+    }
+
+    /// consumes `self`, returning the key and the value
+    pub fn into_pair(self) -> (K, V) {
+        (self.key, self.value)
+    }
+}
+
+impl<K, V> Entry<K, V>
+where
+    V: Value + ?Sized,
+{
+    /// creates an entry out of a boxed value
+    /// unlike `new`, this works for unsized values such as `str`,
+    /// slices and trait objects
+    pub fn from_box(key: K, value: Box<V>) -> Entry<K, V> {
         Entry {
             key,
-            value: Box::from(value),
+            value: V::from_box(value),
         }
     }
 
@@ -18,4 +186,92 @@ impl<K, V> Entry<K, V> {
     pub fn key(&self) -> &K {
         &self.key
     }
+
+    /// returns a reference to the entry's value
+    pub fn value(&self) -> &V {
+        V::get(&self.value)
+    }
+
+    /// returns a mutable reference to the entry's value
+    pub fn value_mut(&mut self) -> &mut V {
+        V::get_mut(&mut self.value)
+    }
+
+    /// returns references to the key and the value
+    pub fn split(&self) -> (&K, &V) {
+        (&self.key, V::get(&self.value))
+    }
+
+    /// returns a reference to the key and a mutable reference to the value
+    pub fn split_mut(&mut self) -> (&K, &mut V) {
+        (&self.key, V::get_mut(&mut self.value))
+    }
+
+    /// consumes `self`, returning the key and the boxed value
+    pub fn into_boxed_pair(self) -> (K, Box<V>) {
+        (self.key, V::into_box(self.value))
+    }
+}
+
+impl<K, V> Clone for Entry<K, V>
+where
+    K: Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Entry::new(self.key.clone(), self.value.clone())
+    }
+}
+
+impl<K, V> fmt::Debug for Entry<K, V>
+where
+    K: fmt::Debug,
+    V: Value + fmt::Debug + ?Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Entry")
+            .field("key", &self.key)
+            .field("value", &self.value())
+            .finish()
+    }
+}
+
+impl<K, V> PartialEq for Entry<K, V>
+where
+    K: PartialEq,
+    V: Value + PartialEq + ?Sized,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.value() == other.value()
+    }
+}
+
+/// a key for tests that counts how often it is compared
+#[cfg(test)]
+#[derive(Debug)]
+pub struct Counted<'a>(pub usize, pub &'a core::cell::Cell<usize>);
+
+#[cfg(test)]
+impl PartialEq for Counted<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == core::cmp::Ordering::Equal
+    }
+}
+
+#[cfg(test)]
+impl Eq for Counted<'_> {}
+
+#[cfg(test)]
+impl PartialOrd for Counted<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+impl Ord for Counted<'_> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.1.set(self.1.get() + 1);
+        self.0.cmp(&other.0)
+    }
 }
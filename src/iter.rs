@@ -0,0 +1,306 @@
+//! iterators shared by the tree-like containers
+//!
+//! each container exposes these through type aliases naming its own nodes
+use crate::alloc::{Allocator, Global};
+use crate::traversal::{Expand, Split, Traversal};
+use core::borrow::Borrow;
+use core::ops::RangeBounds;
+
+/// an iterator over every entry of a tree, in order of their keys
+/// the traversal keeps its pending steps in memory from `A`
+pub struct Iter<N, A = Global>
+where
+    N: Expand,
+    A: Allocator,
+{
+    traversal: Traversal<N, A>,
+    len: usize,
+}
+
+impl<N> Iter<N>
+where
+    N: Expand,
+{
+    /// creates an iterator over the `len` entries below `root`
+    pub fn new(root: Option<N>, len: usize) -> Iter<N> {
+        Iter::new_in(root, len, Global)
+    }
+}
+
+impl<N, A> Iter<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+    /// creates an iterator over the `len` entries below `root`,
+    /// whose traversal takes its memory from `alloc`
+    pub fn new_in(root: Option<N>, len: usize, alloc: A) -> Iter<N, A> {
+        Iter {
+            traversal: Traversal::new_in(root, alloc),
+            len,
+        }
+    }
+}
+
+impl<N, A> Iterator for Iter<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+    type Item = N::Entry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.traversal.next()?;
+        self.len -= 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<N, A> DoubleEndedIterator for Iter<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let entry = self.traversal.next_back()?;
+        self.len -= 1;
+        Some(entry)
+    }
+}
+
+impl<N, A> ExactSizeIterator for Iter<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+}
+
+/// an owning iterator over every entry of a tree, in order of their keys
+/// unlike `Iter`, it drops the entries it has not yielded when dropped
+pub struct IntoIter<N, A = Global>
+where
+    N: Expand,
+    A: Allocator,
+{
+    iter: Iter<N, A>,
+}
+
+impl<N> IntoIter<N>
+where
+    N: Expand,
+{
+    /// creates an iterator over the `len` entries below `root`
+    pub fn new(root: Option<N>, len: usize) -> IntoIter<N> {
+        IntoIter::new_in(root, len, Global)
+    }
+}
+
+impl<N, A> IntoIter<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+    /// creates an iterator over the `len` entries below `root`,
+    /// whose traversal takes its memory from `alloc`
+    pub fn new_in(root: Option<N>, len: usize, alloc: A) -> IntoIter<N, A> {
+        IntoIter {
+            iter: Iter::new_in(root, len, alloc),
+        }
+    }
+}
+
+impl<N, A> Iterator for IntoIter<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+    type Item = N::Entry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<N, A> DoubleEndedIterator for IntoIter<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<N, A> ExactSizeIterator for IntoIter<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+}
+
+impl<N, A> Drop for IntoIter<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        self.iter.traversal.clear();
+    }
+}
+
+/// an iterator over the entries of a tree whose keys lie within a range,
+/// in order of their keys
+/// the traversal keeps its pending steps in memory from `A`
+pub struct Range<N, A = Global>
+where
+    N: Expand,
+    A: Allocator,
+{
+    traversal: Traversal<N, A>,
+}
+
+impl<N> Range<N>
+where
+    N: Split,
+{
+    /// creates an iterator over the entries below `root`
+    /// whose keys lie within `range`
+    pub fn new<Q, R>(root: Option<N>, range: &R) -> Range<N>
+    where
+        N::Key: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Range::new_in(root, range, Global)
+    }
+}
+
+impl<N, A> Range<N, A>
+where
+    N: Split,
+    A: Allocator,
+{
+    /// creates an iterator over the entries below `root`
+    /// whose keys lie within `range`,
+    /// whose traversal takes its memory from `alloc`
+    pub fn new_in<Q, R>(root: Option<N>, range: &R, alloc: A) -> Range<N, A>
+    where
+        N::Key: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Range {
+            traversal: Traversal::range_in(root, range, alloc),
+        }
+    }
+}
+
+impl<N, A> Iterator for Range<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+    type Item = N::Entry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.traversal.next()
+    }
+}
+
+impl<N, A> DoubleEndedIterator for Range<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.traversal.next_back()
+    }
+}
+
+/// an iterator over the keys of an iterator over key-value pairs
+pub struct Keys<I> {
+    inner: I,
+}
+
+impl<I> Keys<I> {
+    pub fn new(inner: I) -> Keys<I> {
+        Keys { inner }
+    }
+}
+
+impl<I, K, V> Iterator for Keys<I>
+where
+    I: Iterator<Item = (K, V)>,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<I, K, V> DoubleEndedIterator for Keys<I>
+where
+    I: DoubleEndedIterator<Item = (K, V)>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, _)| key)
+    }
+}
+
+impl<I, K, V> ExactSizeIterator for Keys<I> where
+    I: ExactSizeIterator<Item = (K, V)>
+{
+}
+
+/// an iterator over the values of an iterator over key-value pairs
+pub struct Values<I> {
+    inner: I,
+}
+
+impl<I> Values<I> {
+    pub fn new(inner: I) -> Values<I> {
+        Values { inner }
+    }
+}
+
+impl<I, K, V> Iterator for Values<I>
+where
+    I: Iterator<Item = (K, V)>,
+{
+    type Item = V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<I, K, V> DoubleEndedIterator for Values<I>
+where
+    I: DoubleEndedIterator<Item = (K, V)>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<I, K, V> ExactSizeIterator for Values<I> where
+    I: ExactSizeIterator<Item = (K, V)>
+{
+}
@@ -1,4 +1,19 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
+// renamed since the crate's own `alloc` module takes the name, as in `std`
+extern crate alloc as alloc_crate;
+
+pub mod alloc;
+pub mod avl_tree;
 pub mod b_tree;
 pub mod binary_search_tree;
+mod boxed;
+mod deque;
 mod entry;
+mod iter;
 pub mod linked_list;
+mod merge;
+pub mod red_black_tree;
+mod set_ops;
+mod traversal;
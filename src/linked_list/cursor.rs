@@ -0,0 +1,317 @@
+//! cursors that sit on an element of a `LinkedList`
+//! besides the elements, a cursor can sit on a "ghost" position that lies
+//! after the last element and before the first one, so it can wrap around
+
+use super::node::Link;
+use super::{shares_memory, LinkedList};
+use crate::alloc::{Allocator, Global};
+use core::mem;
+
+/// a cursor over a `LinkedList` that can only read it
+/// `index` is the length of the list while on the ghost position
+pub struct Cursor<'a, T, A = Global>
+where
+    A: Allocator,
+{
+    current: Link<T>,
+    index: usize,
+    list: &'a LinkedList<T, A>,
+}
+
+/// a cursor over a `LinkedList` that can edit it
+/// `index` is the length of the list while on the ghost position
+pub struct CursorMut<'a, T, A = Global>
+where
+    A: Allocator,
+{
+    current: Link<T>,
+    index: usize,
+    list: &'a mut LinkedList<T, A>,
+}
+
+impl<'a, T, A> Cursor<'a, T, A>
+where
+    A: Allocator,
+{
+    pub(super) fn new(
+        list: &'a LinkedList<T, A>,
+        current: Link<T>,
+        index: usize,
+    ) -> Cursor<'a, T, A> {
+        Cursor {
+            current,
+            index,
+            list,
+        }
+    }
+
+    /// returns the index of the current element,
+    /// or `None` on the ghost position
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    /// returns an optional reference to the current element
+    pub fn current(&self) -> Option<&'a T> {
+        // SAFETY: `current` is a node of `list`, which is borrowed for `'a`
+        self.current.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// returns an optional reference to the element after the current one
+    pub fn peek_next(&self) -> Option<&'a T> {
+        // SAFETY: every link reachable from `list` is a node of `list`
+        unsafe {
+            let next = match self.current {
+                Some(node) => node.as_ref().next,
+                None => self.list.head,
+            };
+            next.map(|node| &(*node.as_ptr()).value)
+        }
+    }
+
+    /// returns an optional reference to the element before the current one
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        // SAFETY: every link reachable from `list` is a node of `list`
+        unsafe {
+            let prev = match self.current {
+                Some(node) => node.as_ref().prev,
+                None => self.list.tail,
+            };
+            prev.map(|node| &(*node.as_ptr()).value)
+        }
+    }
+
+    /// moves to the next element
+    /// after the last element comes the ghost position,
+    /// and after that the first element again
+    pub fn move_next(&mut self) {
+        let (current, index) = move_next(self.current, self.index, self.list);
+        self.current = current;
+        self.index = index;
+    }
+
+    /// moves to the previous element
+    /// before the first element comes the ghost position,
+    /// and before that the last element again
+    pub fn move_prev(&mut self) {
+        let (current, index) = move_prev(self.current, self.index, self.list);
+        self.current = current;
+        self.index = index;
+    }
+}
+
+impl<T, A> Clone for Cursor<'_, T, A>
+where
+    A: Allocator,
+{
+    fn clone(&self) -> Self {
+        Cursor::new(self.list, self.current, self.index)
+    }
+}
+
+impl<'a, T, A> CursorMut<'a, T, A>
+where
+    A: Allocator,
+{
+    pub(super) fn new(
+        list: &'a mut LinkedList<T, A>,
+        current: Link<T>,
+        index: usize,
+    ) -> CursorMut<'a, T, A> {
+        CursorMut {
+            current,
+            index,
+            list,
+        }
+    }
+
+    /// returns the index of the current element,
+    /// or `None` on the ghost position
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    /// returns an optional mutable reference to the current element
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: `current` is a node of `list`, which is borrowed mutably
+        self.current
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    /// returns a read-only cursor at the same position as `self`
+    pub fn as_cursor(&self) -> Cursor<'_, T, A> {
+        Cursor::new(self.list, self.current, self.index)
+    }
+
+    /// moves to the next element
+    /// after the last element comes the ghost position,
+    /// and after that the first element again
+    pub fn move_next(&mut self) {
+        let (current, index) = move_next(self.current, self.index, self.list);
+        self.current = current;
+        self.index = index;
+    }
+
+    /// moves to the previous element
+    /// before the first element comes the ghost position,
+    /// and before that the last element again
+    pub fn move_prev(&mut self) {
+        let (current, index) = move_prev(self.current, self.index, self.list);
+        self.current = current;
+        self.index = index;
+    }
+
+    /// inserts `value` right after the current element
+    /// on the ghost position, `value` becomes the first element
+    pub fn insert_after(&mut self, value: T) {
+        // SAFETY: `current` and the node after it are adjacent nodes of `list`
+        unsafe {
+            let next = match self.current {
+                Some(node) => node.as_ref().next,
+                None => self.list.head,
+            };
+            self.list.link(self.current, next, value);
+        }
+        if self.current.is_none() {
+            self.index += 1;
+        }
+    }
+
+    /// inserts `value` right before the current element
+    /// on the ghost position, `value` becomes the last element
+    pub fn insert_before(&mut self, value: T) {
+        // SAFETY: `current` and the node before it are adjacent nodes
+        // of `list`
+        unsafe {
+            let prev = match self.current {
+                Some(node) => node.as_ref().prev,
+                None => self.list.tail,
+            };
+            self.list.link(prev, self.current, value);
+        }
+        self.index += 1;
+    }
+
+    /// removes the current element and returns it,
+    /// moving on to the element after it
+    /// on the ghost position, nothing is removed
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        // SAFETY: `current` is a node of `list`, and is replaced right away
+        unsafe {
+            self.current = node.as_ref().next;
+            Some(self.list.unlink(node))
+        }
+    }
+}
+
+impl<T, A> CursorMut<'_, T, A>
+where
+    A: Allocator + Clone,
+{
+    /// splits the list after the current element,
+    /// returning everything after it as a new list
+    /// on the ghost position, the whole list is returned
+    pub fn split_after(&mut self) -> LinkedList<T, A> {
+        let alloc = self.list.alloc.clone();
+        let Some(node) = self.current else {
+            self.index = 0;
+            return mem::replace(self.list, LinkedList::new_in(alloc));
+        };
+        let mut after = LinkedList::new_in(alloc);
+        // SAFETY: `current` is a node of `list`, and the nodes after it are
+        // handed over to `after`
+        unsafe {
+            if let Some(next) = (*node.as_ptr()).next.take() {
+                (*next.as_ptr()).prev = None;
+                after.head = Some(next);
+                after.tail = self.list.tail.replace(node);
+                after.len = self.list.len - self.index - 1;
+                self.list.len = self.index + 1;
+            }
+        }
+        after
+    }
+}
+
+impl<T, A> CursorMut<'_, T, A>
+where
+    A: Allocator,
+{
+    /// moves all elements of `other` in between the current element
+    /// and the one after it
+    /// on the ghost position, `other` is put in front of the list
+    /// like `LinkedList::concat`, this takes O(1) time if `A` holds no
+    /// state, and moves the elements into new nodes otherwise
+    pub fn splice_after(&mut self, mut other: LinkedList<T, A>) {
+        if !shares_memory::<A>() {
+            while let Some(value) = other.pop_back() {
+                self.insert_after(value);
+            }
+            return;
+        }
+        let (Some(first), Some(last)) = (other.head.take(), other.tail.take())
+        else {
+            return;
+        };
+        let len = mem::take(&mut other.len);
+        // SAFETY: `current` and `next` are adjacent nodes of `list`,
+        // and the nodes of `other` are handed over to `list`, whose
+        // allocator can free them
+        unsafe {
+            let next = match self.current {
+                Some(node) => node.as_ref().next,
+                None => self.list.head,
+            };
+            match self.current {
+                Some(node) => (*node.as_ptr()).next = Some(first),
+                None => self.list.head = Some(first),
+            }
+            match next {
+                Some(node) => (*node.as_ptr()).prev = Some(last),
+                None => self.list.tail = Some(last),
+            }
+            (*first.as_ptr()).prev = self.current;
+            (*last.as_ptr()).next = next;
+        }
+        self.list.len += len;
+        if self.current.is_none() {
+            self.index += len;
+        }
+    }
+}
+
+/// returns the position after `current`, which sits at index `index`
+fn move_next<T, A>(
+    current: Link<T>,
+    index: usize,
+    list: &LinkedList<T, A>,
+) -> (Link<T>, usize)
+where
+    A: Allocator,
+{
+    match current {
+        // SAFETY: `node` is a node of `list`
+        Some(node) => (unsafe { node.as_ref().next }, index + 1),
+        None => (list.head, 0),
+    }
+}
+
+/// returns the position before `current`, which sits at index `index`
+fn move_prev<T, A>(
+    current: Link<T>,
+    index: usize,
+    list: &LinkedList<T, A>,
+) -> (Link<T>, usize)
+where
+    A: Allocator,
+{
+    match current {
+        // SAFETY: `node` is a node of `list`
+        Some(node) => match unsafe { node.as_ref().prev } {
+            Some(prev) => (Some(prev), index - 1),
+            None => (None, list.len),
+        },
+        None => (list.tail, list.len.saturating_sub(1)),
+    }
+}
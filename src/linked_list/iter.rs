@@ -1 +1,226 @@
-//! TODO
+//! iterators over the elements of a `LinkedList`, from front to back
+use super::node::{Link, Node};
+use super::{CursorMut, LinkedList};
+use crate::alloc::{Allocator, Global};
+use core::marker::PhantomData;
+
+/// an iterator over references to the elements of a `LinkedList`
+/// `head` and `tail` are the next elements to be returned from either end,
+/// and `len` counts the elements left between them
+pub struct Iter<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+/// an iterator over mutable references to the elements of a `LinkedList`
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a mut Node<T>>,
+}
+
+/// an iterator that moves the elements out of a `LinkedList`
+pub struct IntoIter<T, A = Global>
+where
+    A: Allocator,
+{
+    list: LinkedList<T, A>,
+}
+
+/// an iterator that removes the elements of a `LinkedList` matching a
+/// predicate as it goes, and returns them
+/// elements it has not reached when dropped stay in the list
+pub struct ExtractIf<'a, T, F, A = Global>
+where
+    A: Allocator,
+{
+    cursor: CursorMut<'a, T, A>,
+    pred: F,
+}
+
+// SAFETY: `Iter` only hands out shared references, like `&LinkedList` would
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+// SAFETY: `IterMut` only hands out unique references, like `&mut LinkedList`
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+impl<'a, T> Iter<'a, T> {
+    pub(super) fn new<A>(list: &'a LinkedList<T, A>) -> Iter<'a, T>
+    where
+        A: Allocator,
+    {
+        Iter {
+            head: list.head,
+            tail: list.tail,
+            len: list.len,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: `head` is a node of the list, which is borrowed for `'a`
+        self.head.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.len -= 1;
+            self.head = node.next;
+            &node.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: `tail` is a node of the list, which is borrowed for `'a`
+        self.tail.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.len -= 1;
+            self.tail = node.prev;
+            &node.value
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'a, T> IterMut<'a, T> {
+    pub(super) fn new<A>(list: &'a mut LinkedList<T, A>) -> IterMut<'a, T>
+    where
+        A: Allocator,
+    {
+        IterMut {
+            head: list.head,
+            tail: list.tail,
+            len: list.len,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: `head` is a node of the list, which is borrowed mutably
+        // for `'a`, and every node is returned at most once
+        self.head.map(|node| unsafe {
+            let node = &mut *node.as_ptr();
+            self.len -= 1;
+            self.head = node.next;
+            &mut node.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: `tail` is a node of the list, which is borrowed mutably
+        // for `'a`, and every node is returned at most once
+        self.tail.map(|node| unsafe {
+            let node = &mut *node.as_ptr();
+            self.len -= 1;
+            self.tail = node.prev;
+            &mut node.value
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T, A> IntoIter<T, A>
+where
+    A: Allocator,
+{
+    pub(super) fn new(list: LinkedList<T, A>) -> IntoIter<T, A> {
+        IntoIter { list }
+    }
+}
+
+impl<T, A> Iterator for IntoIter<T, A>
+where
+    A: Allocator,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T, A> DoubleEndedIterator for IntoIter<T, A>
+where
+    A: Allocator,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T, A> ExactSizeIterator for IntoIter<T, A> where A: Allocator {}
+
+impl<'a, T, F, A> ExtractIf<'a, T, F, A>
+where
+    A: Allocator,
+{
+    pub(super) fn new(
+        cursor: CursorMut<'a, T, A>,
+        pred: F,
+    ) -> ExtractIf<'a, T, F, A> {
+        ExtractIf { cursor, pred }
+    }
+}
+
+impl<T, F, A> Iterator for ExtractIf<'_, T, F, A>
+where
+    F: FnMut(&mut T) -> bool,
+    A: Allocator,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(value) = self.cursor.current() {
+            if (self.pred)(value) {
+                return self.cursor.remove_current();
+            }
+            self.cursor.move_next();
+        }
+        None
+    }
+}
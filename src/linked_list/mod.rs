@@ -1,95 +1,233 @@
-use std::ops::{Index, IndexMut};
+//! a doubly linked list
+//! every element can be added or removed at either end in constant time
+use crate::alloc::{Allocator, Global};
+use alloc_crate::boxed::Box;
+use alloc_crate::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::mem;
+use core::ops::{Index, IndexMut};
+use core::ptr::{self, NonNull};
+mod cursor;
 mod iter;
 mod node;
-use node::Node;
-pub struct LinkedList<T> {
-    head: Option<Box<Node<T>>>,
+pub use cursor::{Cursor, CursorMut};
+pub use iter::{ExtractIf, IntoIter, Iter, IterMut};
+use node::{Link, Node};
+
+/// `head` and `tail` are the first and last nodes
+/// every node is owned by the list, allocated from `alloc`,
+/// and freed when it is unlinked
+pub struct LinkedList<T, A = Global>
+where
+    A: Allocator,
+{
+    head: Link<T>,
+    tail: Link<T>,
     len: usize,
+    alloc: A,
+    marker: PhantomData<Box<Node<T>>>,
 }
 
+// SAFETY: `LinkedList` owns its nodes just like a `Box` would
+unsafe impl<T: Send, A: Allocator + Send> Send for LinkedList<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for LinkedList<T, A> {}
+
 impl<T> LinkedList<T> {
     /// creates an empty `LinkedList`
     #[inline]
     pub const fn new() -> Self {
-        LinkedList { head: None, len: 0 }
+        LinkedList::new_in(Global)
+    }
+}
+
+impl<T, A> LinkedList<T, A>
+where
+    A: Allocator,
+{
+    /// creates an empty `LinkedList` whose nodes come from `alloc`
+    #[inline]
+    pub const fn new_in(alloc: A) -> Self {
+        LinkedList {
+            head: None,
+            tail: None,
+            len: 0,
+            alloc,
+            marker: PhantomData,
+        }
+    }
+
+    /// returns a reference to the allocator of `self`
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// appends `other` to `self`
+    /// if `A` holds no state, like `Global`, the nodes of `other` are taken
+    /// over as they are, in O(1) time
+    /// otherwise, two allocators need not share their memory, so the
+    /// elements are moved into new nodes from the allocator of `self`
+    pub fn concat(&mut self, mut other: LinkedList<T, A>) {
+        if !shares_memory::<A>() {
+            return self.extend(other);
+        }
+        let Some(tail) = self.tail else {
+            return mem::swap(self, &mut other);
+        };
+        if let Some(other_head) = other.head.take() {
+            // SAFETY: `tail` belongs to `self` and `other_head` to `other`,
+            // whose nodes are all handed over to `self`, and can be freed
+            // by its allocator
+            unsafe {
+                (*tail.as_ptr()).next = Some(other_head);
+                (*other_head.as_ptr()).prev = Some(tail);
+            }
+            self.tail = other.tail.take();
+            self.len += mem::take(&mut other.len);
+        }
+    }
+
+    /// adds a new element `value` to the front of `self`
+    pub fn push_front(&mut self, value: T) {
+        // SAFETY: there is nothing before `head`
+        unsafe { self.link(None, self.head, value) };
+    }
+
+    /// appends a new element `value` to the back of `self`
+    pub fn push_back(&mut self, value: T) {
+        // SAFETY: there is nothing after `tail`
+        unsafe { self.link(self.tail, None, value) };
+    }
+
+    /// removes the first element, and returns it
+    pub fn pop_front(&mut self) -> Option<T> {
+        // SAFETY: `head` is a node of `self`
+        self.head.map(|node| unsafe { self.unlink(node) })
+    }
+
+    /// removes the last element, and returns it
+    pub fn pop_back(&mut self) -> Option<T> {
+        // SAFETY: `tail` is a node of `self`
+        self.tail.map(|node| unsafe { self.unlink(node) })
+    }
+
+    /// returns an optional reference to the first element
+    pub fn front(&self) -> Option<&T> {
+        // SAFETY: `head` is a node of `self`, which is borrowed
+        self.head.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// returns an optional mutable reference to the first element
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: `head` is a node of `self`, which is borrowed mutably
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    /// returns an optional reference to the last element
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: `tail` is a node of `self`, which is borrowed
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// returns an optional mutable reference to the last element
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: `tail` is a node of `self`, which is borrowed mutably
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).value })
     }
 
     /// appends a new element `value` to `self`
+    #[inline]
     pub fn push(&mut self, value: T) {
-        match self.head {
-            Some(ref mut node) => node.push(value),
-            None => self.head = Some(Box::from(Node::new(value))),
-        };
-        self.len += 1;
+        self.push_back(value);
+    }
+
+    /// adds a new element `value` to the front of `self`
+    #[inline]
+    pub fn add(&mut self, value: T) {
+        self.push_front(value);
+    }
+
+    /// removes the last element, and returns it
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        self.pop_back()
     }
 
     /// returns an optional reference to the element at index `index`
     #[inline]
     pub fn get(&self, index: usize) -> Option<&T> {
-        match self.head {
-            Some(ref node) => node.get(index),
-            None => None,
-        }
+        // SAFETY: the node belongs to `self`, which is borrowed
+        self.node_at(index)
+            .map(|node| unsafe { &(*node.as_ptr()).value })
     }
 
     /// returns an optional mutable reference to the element at index `index`
     #[inline]
     pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
-        match self.head {
-            Some(ref mut node) => node.get_mut(index),
-            None => None,
-        }
+        // SAFETY: the node belongs to `self`, which is borrowed mutably
+        self.node_at(index)
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
     }
 
     /// inserts `value` at index `index`
-    #[inline]
+    /// if `index` is greater than the length of `self`, nothing happens
     pub fn insert(&mut self, index: usize, value: T) {
-        if index == 0 {
-            return self.add(value);
+        if index > self.len {
+            return;
         }
-        match self.head {
-            Some(ref mut node) => node.insert(index, value),
-            None => self.head = Some(Box::from(Node::new(value))),
+        let next = self.node_at(index);
+        // SAFETY: `next` belongs to `self`,
+        // and `prev` is the node right before it
+        unsafe {
+            let prev = match next {
+                Some(node) => node.as_ref().prev,
+                None => self.tail,
+            };
+            self.link(prev, next, value);
         }
-        self.len += 1;
     }
 
-    /// removes the last element, and returns it
+    /// removes all elements at index `index` and after
     #[inline]
-    pub fn pop(&mut self) -> Option<T> {
-        match self.head {
-            Some(ref mut node) => {
-                self.len -= 1;
-                if self.len == 0 {
-                    // let temp = node.value;
-                    // self.head = None;
-                    // return Some(temp);
-                    todo!();
-                }
-                Some(node.pop())
-            }
-            None => None,
+    pub fn trunc(&mut self, index: usize) {
+        while self.len > index {
+            self.pop_back();
         }
     }
 
-    /// removes all elements at index `index` and after
+    /// removes all elements
     #[inline]
-    pub fn trunc(&mut self, index: usize) {
-        if let Some(ref mut node) = self.head {
-            self.len = index;
-            if self.len == 0 {
-                return self.head = None;
-            }
-            node.trunc(index - 1);
-        }
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// keeps only the elements for which `f` returns `true`
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.extract_if(|value| !f(value)).for_each(drop);
+    }
+
+    /// returns an iterator that removes the elements for which `pred`
+    /// returns `true`, from front to back, and yields them
+    /// if the iterator is dropped early, the remaining elements are kept
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F, A>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        ExtractIf::new(self.cursor_front_mut(), pred)
     }
 
     /// sets the element at index `index` to `value`
     /// if no such element exists, nothing happens
     #[inline]
     pub fn set(&mut self, index: usize, value: T) {
-        if let Some(ref mut node) = self.head {
-            node.set(index, value);
+        if let Some(element) = self.get_mut(index) {
+            *element = value;
         }
     }
 
@@ -97,8 +235,17 @@ impl<T> LinkedList<T> {
     /// if no such element exists, nothing happens
     #[inline]
     pub fn swap(&mut self, first_index: usize, second_index: usize) {
-        if let Some(ref mut node) = self.head {
-            node.swap(first_index, second_index);
+        let first = self.node_at(first_index);
+        let second = self.node_at(second_index);
+        if let (Some(first), Some(second)) = (first, second) {
+            // SAFETY: both nodes belong to `self`, which is borrowed mutably,
+            // and `ptr::swap` allows them to be the same node
+            unsafe {
+                ptr::swap(
+                    &raw mut (*first.as_ptr()).value,
+                    &raw mut (*second.as_ptr()).value,
+                );
+            }
         }
     }
 
@@ -108,103 +255,355 @@ impl<T> LinkedList<T> {
         self.len
     }
 
-    #[inline]
-    pub fn add(&mut self, value: T) {
-        self.head = Some(Box::from(Node {
-            value,
-            next: self.head.take(),
-        }));
-        self.len += 1;
+    /// returns an iterator over the elements of `self`, from front to back
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self)
     }
 
-    /// appends `other` to `self`
-    #[inline]
-    pub fn concat(&mut self, other: LinkedList<T>) {
-        if let Some(other_node) = other.head {
-            self.len += other.len;
-            match self.head {
-                Some(ref mut node) => node.concat(*other_node),
-                None => self.head = Some(other_node),
+    /// returns an iterator over mutable references to the elements of `self`,
+    /// from front to back
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut::new(self)
+    }
+
+    /// returns a cursor on the first element
+    /// if `self` is empty, the cursor is on the ghost position
+    pub fn cursor_front(&self) -> Cursor<'_, T, A> {
+        Cursor::new(self, self.head, 0)
+    }
+
+    /// returns a cursor on the last element
+    /// if `self` is empty, the cursor is on the ghost position
+    pub fn cursor_back(&self) -> Cursor<'_, T, A> {
+        Cursor::new(self, self.tail, self.len.saturating_sub(1))
+    }
+
+    /// returns a cursor on the first element that can edit `self`
+    /// if `self` is empty, the cursor is on the ghost position
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T, A> {
+        let head = self.head;
+        CursorMut::new(self, head, 0)
+    }
+
+    /// returns a cursor on the last element that can edit `self`
+    /// if `self` is empty, the cursor is on the ghost position
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T, A> {
+        let (tail, index) = (self.tail, self.len.saturating_sub(1));
+        CursorMut::new(self, tail, index)
+    }
+
+    /// returns the node at index `index`, walking from whichever end of
+    /// `self` is closer
+    fn node_at(&self, index: usize) -> Link<T> {
+        if index >= self.len {
+            return None;
+        }
+        // SAFETY: every link reachable from `head` or `tail` is a node
+        // of `self`
+        unsafe {
+            if index < self.len / 2 {
+                let mut node = self.head?;
+                for _ in 0..index {
+                    node = node.as_ref().next?;
+                }
+                Some(node)
+            } else {
+                let mut node = self.tail?;
+                for _ in index + 1..self.len {
+                    node = node.as_ref().prev?;
+                }
+                Some(node)
             }
         }
     }
+
+    /// creates a new node with value `value` between `prev` and `next`,
+    /// and returns it
+    /// a `prev` or `next` of `None` stands for the front or back of `self`
+    ///
+    /// # Safety
+    /// `prev` and `next` must be adjacent nodes of `self`
+    unsafe fn link(
+        &mut self,
+        prev: Link<T>,
+        next: Link<T>,
+        value: T,
+    ) -> NonNull<Node<T>> {
+        let node = Node::alloc(value, prev, next, &self.alloc);
+        match prev {
+            Some(prev) => (*prev.as_ptr()).next = Some(node),
+            None => self.head = Some(node),
+        }
+        match next {
+            Some(next) => (*next.as_ptr()).prev = Some(node),
+            None => self.tail = Some(node),
+        }
+        self.len += 1;
+        node
+    }
+
+    /// detaches `node` from its neighbours, frees it, and returns its value
+    ///
+    /// # Safety
+    /// `node` must be a node of `self`, and must not be used afterwards
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        let node = Node::free(node, &self.alloc);
+        match node.prev {
+            Some(prev) => (*prev.as_ptr()).next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(next) => (*next.as_ptr()).prev = node.prev,
+            None => self.tail = node.prev,
+        }
+        self.len -= 1;
+        node.value
+    }
 }
 
-impl<T> LinkedList<T>
+impl<T, A> LinkedList<T, A>
 where
     T: PartialEq,
+    A: Allocator,
 {
     #[inline]
     pub fn contains(&self, value: T) -> bool {
-        match self.head {
-            Some(ref node) => node.contains(value),
-            None => false,
-        }
+        self.position(&value).is_some()
     }
 
     /// removes the node with value `value` from the list
     #[inline]
     pub fn remove(&mut self, value: T) -> bool {
-        match self.head {
-            Some(ref mut node) => {
-                if node.value == value {
-                    self.head = node.next.take();
-                    self.len -= 1;
-                    return true;
-                }
-                match node.remove(value) {
-                    true => {
-                        self.len -= 1;
-                        true
-                    },
-                    false => false,
-                }
-            },
+        match self.position(&value) {
+            Some((_, node)) => {
+                // SAFETY: `position` only returns nodes of `self`
+                unsafe { self.unlink(node) };
+                true
+            }
             None => false,
         }
     }
 
     #[inline]
     pub fn find(&self, value: T) -> Option<usize> {
-        match self.head {
-            Some(ref node) => node.find(value),
-            None => None,
+        self.position(&value).map(|(index, _)| index)
+    }
+
+    /// returns the index of the first node with value `value`,
+    /// and the node itself
+    fn position(&self, value: &T) -> Option<(usize, NonNull<Node<T>>)> {
+        let mut link = self.head;
+        let mut index = 0;
+        while let Some(node) = link {
+            // SAFETY: every link reachable from `head` is a node of `self`
+            let node_ref = unsafe { node.as_ref() };
+            if node_ref.value == *value {
+                return Some((index, node));
+            }
+            link = node_ref.next;
+            index += 1;
         }
+        None
     }
 }
 
-impl<T> From<Node<T>> for LinkedList<T> {
-    fn from(value: Node<T>) -> Self {
-        let len = value.length();
-        LinkedList {
-            head: Some(Box::from(value)),
-            len,
+impl<T, A> IntoIterator for LinkedList<T, A>
+where
+    A: Allocator,
+{
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+impl<'a, T, A> IntoIterator for &'a LinkedList<T, A>
+where
+    A: Allocator,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, A> IntoIterator for &'a mut LinkedList<T, A>
+where
+    A: Allocator,
+{
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = LinkedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T, A> Extend<T> for LinkedList<T, A>
+where
+    A: Allocator,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
         }
     }
 }
 
+impl<'a, T, A> Extend<&'a T> for LinkedList<T, A>
+where
+    T: Copy,
+    A: Allocator,
+{
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for LinkedList<T> {
+    fn from(array: [T; N]) -> Self {
+        LinkedList::from_iter(array)
+    }
+}
+
+impl<T> From<Vec<T>> for LinkedList<T> {
+    fn from(vec: Vec<T>) -> Self {
+        LinkedList::from_iter(vec)
+    }
+}
+
+impl<T, A> Clone for LinkedList<T, A>
+where
+    T: Clone,
+    A: Allocator + Clone,
+{
+    fn clone(&self) -> Self {
+        let mut list = LinkedList::new_in(self.alloc.clone());
+        list.extend(self.iter().cloned());
+        list
+    }
+}
+
+impl<T, A> fmt::Debug for LinkedList<T, A>
+where
+    T: fmt::Debug,
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T, A> PartialEq for LinkedList<T, A>
+where
+    T: PartialEq,
+    A: Allocator,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T, A> Eq for LinkedList<T, A>
+where
+    T: Eq,
+    A: Allocator,
+{
+}
+
+impl<T, A> Hash for LinkedList<T, A>
+where
+    T: Hash,
+    A: Allocator,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        for value in self {
+            value.hash(state);
+        }
+    }
+}
+
+impl<T, A> PartialOrd for LinkedList<T, A>
+where
+    T: PartialOrd,
+    A: Allocator,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T, A> Ord for LinkedList<T, A>
+where
+    T: Ord,
+    A: Allocator,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T, A> Drop for LinkedList<T, A>
+where
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
 impl<T> Default for LinkedList<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<T> Index<usize> for LinkedList<T> {
+impl<T, A> Index<usize> for LinkedList<T, A>
+where
+    A: Allocator,
+{
     type Output = T;
     fn index(&self, index: usize) -> &Self::Output {
-        self.head.as_ref().unwrap().get(index).unwrap()
+        self.get(index).unwrap()
     }
 }
 
-impl<T> IndexMut<usize> for LinkedList<T> {
+impl<T, A> IndexMut<usize> for LinkedList<T, A>
+where
+    A: Allocator,
+{
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        self.head.as_mut().unwrap().get_mut(index).unwrap()
+        self.get_mut(index).unwrap()
     }
 }
 
+/// returns `true` if every value of type `A` can free the memory of every
+/// other one, which holds for allocators without any state
+fn shares_memory<A>() -> bool
+where
+    A: Allocator,
+{
+    mem::size_of::<A>() == 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::alloc::Counting;
+    use alloc_crate::{format, vec};
 
     #[test]
     fn add() {
@@ -213,7 +612,7 @@ mod tests {
         linked_list.add(3);
 
         assert_eq!(linked_list.size(), 2);
-        assert_eq!(linked_list.head.unwrap().value, 3);
+        assert_eq!(linked_list.front(), Some(&3));
     }
 
     #[test]
@@ -237,6 +636,201 @@ mod tests {
         assert_eq!(linked_list.get(1), Some(3).as_ref());
     }
 
+    #[test]
+    fn push_and_pop_both_ends() {
+        let mut linked_list = LinkedList::new();
+        linked_list.push_back(2);
+        linked_list.push_front(1);
+        linked_list.push_back(3);
+
+        assert_eq!(linked_list.front(), Some(&1));
+        assert_eq!(linked_list.back(), Some(&3));
+        if let Some(back) = linked_list.back_mut() {
+            *back = 4;
+        }
+        assert_eq!(linked_list.pop_back(), Some(4));
+        assert_eq!(linked_list.pop_front(), Some(1));
+        assert_eq!(linked_list.pop(), Some(2));
+        assert_eq!(linked_list.pop_front(), None);
+        assert_eq!(linked_list.back(), None);
+        assert_eq!(linked_list.size(), 0);
+    }
+
+    #[test]
+    fn insert_and_swap() {
+        let mut linked_list = LinkedList::new();
+        for value in [1, 2, 4, 5] {
+            linked_list.push(value);
+        }
+        linked_list.insert(2, 3);
+        linked_list.insert(5, 6);
+        linked_list.insert(0, 0);
+        linked_list.insert(10, 10);
+        assert!((0..7).all(|index| linked_list[index] == index));
+
+        linked_list.swap(1, 5);
+        assert_eq!(linked_list.get(1), Some(&5));
+        assert_eq!(linked_list.get(5), Some(&1));
+        linked_list.trunc(2);
+        assert_eq!(linked_list.size(), 2);
+        assert_eq!(linked_list.back(), Some(&5));
+    }
+
+    #[test]
+    fn concat() {
+        let mut first = LinkedList::new();
+        let mut second = LinkedList::new();
+        second.push(1);
+        second.push(2);
+        first.concat(second);
+        let mut third = LinkedList::new();
+        third.push(3);
+        first.concat(third);
+        first.concat(LinkedList::new());
+
+        assert_eq!(first.size(), 3);
+        assert_eq!(first.back(), Some(&3));
+        assert_eq!(first.pop_back(), Some(3));
+        assert_eq!(first.back(), Some(&2));
+    }
+
+    #[test]
+    fn long_list() {
+        let mut linked_list = LinkedList::new();
+        for value in 0..1_000_000 {
+            linked_list.push_back(value);
+        }
+        assert_eq!(linked_list.get(999_999), Some(&999_999));
+        assert_eq!(linked_list.find(500_000), Some(500_000));
+    }
+
+    #[test]
+    fn cursor() {
+        let mut linked_list = LinkedList::new();
+        for value in 0..5 {
+            linked_list.push(value);
+        }
+        let mut cursor = linked_list.cursor_front();
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.peek_prev(), Some(&0));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&0));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&4));
+        assert_eq!(cursor.index(), Some(4));
+        assert_eq!(linked_list.cursor_back().index(), Some(4));
+    }
+
+    #[test]
+    fn cursor_mut() {
+        let mut linked_list = LinkedList::new();
+        for value in [1, 2, 4] {
+            linked_list.push(value);
+        }
+        let mut cursor = linked_list.cursor_front_mut();
+        cursor.move_next();
+        cursor.insert_after(3);
+        cursor.insert_before(0);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(0));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.index(), None);
+        cursor.insert_after(0);
+        cursor.insert_before(5);
+        assert_eq!(cursor.remove_current(), None);
+        let values: Vec<usize> =
+            (0..linked_list.size()).map(|i| linked_list[i]).collect();
+        assert_eq!(values, [0, 1, 3, 4, 5]);
+        assert_eq!(linked_list.back(), Some(&5));
+    }
+
+    #[test]
+    fn split_and_splice() {
+        let mut linked_list = LinkedList::new();
+        for value in 0..6 {
+            linked_list.push(value);
+        }
+        let mut cursor = linked_list.cursor_front_mut();
+        cursor.move_next();
+        let mut tail = cursor.split_after();
+        assert_eq!(linked_list.size(), 2);
+        assert_eq!(linked_list.back(), Some(&1));
+        assert_eq!(tail.size(), 4);
+        assert_eq!(tail.front(), Some(&2));
+
+        let mut cursor = tail.cursor_back_mut();
+        cursor.move_prev();
+        cursor.splice_after(linked_list);
+        cursor.splice_after(LinkedList::new());
+        let mut cursor = tail.cursor_back_mut();
+        cursor.move_next();
+        let mut front = LinkedList::new();
+        front.push(6);
+        cursor.splice_after(front);
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(0));
+
+        let values: Vec<i32> = (0..tail.size()).map(|i| tail[i]).collect();
+        assert_eq!(values, [6, 2, 3, 4, 0, 1, 5]);
+        assert_eq!(tail.back(), Some(&5));
+        assert_eq!(tail.pop_back(), Some(5));
+        assert_eq!(tail.back(), Some(&1));
+
+        let mut cursor = tail.cursor_back_mut();
+        cursor.move_next();
+        let whole = cursor.split_after();
+        assert_eq!(whole.size(), 6);
+        assert_eq!(tail.size(), 0);
+        assert_eq!(tail.front(), None);
+    }
+
+    #[test]
+    fn iter() {
+        let mut linked_list = LinkedList::from([1, 2, 3, 4]);
+        let mut iter = linked_list.iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.len(), 2);
+        assert!(iter.rev().eq([&3, &2]));
+
+        for value in &mut linked_list {
+            *value *= 10;
+        }
+        assert!(linked_list
+            .iter_mut()
+            .rev()
+            .eq([&mut 40, &mut 30, &mut 20, &mut 10]));
+        let mut iter = linked_list.into_iter();
+        assert_eq!(iter.next_back(), Some(40));
+        assert!(iter.eq([10, 20, 30]));
+    }
+
+    #[test]
+    fn std_traits() {
+        let linked_list: LinkedList<i32> = (0..4).collect();
+        let mut other = LinkedList::from(vec![0, 1]);
+        other.extend([2, 3]);
+        assert_eq!(linked_list, other);
+        assert_eq!(linked_list.clone(), linked_list);
+        assert_eq!(format!("{linked_list:?}"), "[0, 1, 2, 3]");
+
+        other.extend(&[4]);
+        assert!(linked_list < other);
+        assert_eq!(linked_list.cmp(&other), Ordering::Less);
+        other.pop_front();
+        assert!(linked_list < other);
+        assert_ne!(linked_list, LinkedList::new());
+    }
+
     #[test]
     fn remove() {
         let mut linked_list = LinkedList::new();
@@ -252,6 +846,28 @@ mod tests {
         assert_eq!(linked_list.get(0), Some(4).as_ref());
     }
 
+    #[test]
+    fn retain_extract_and_clear() {
+        let mut linked_list: LinkedList<_> = (0..10).collect();
+        linked_list.retain(|value| value % 3 != 0);
+        assert_eq!(linked_list, LinkedList::from(vec![1, 2, 4, 5, 7, 8]));
+
+        let odd: Vec<_> =
+            linked_list.extract_if(|value| *value % 2 == 1).collect();
+        assert_eq!(odd, [1, 5, 7]);
+        assert_eq!(linked_list, LinkedList::from(vec![2, 4, 8]));
+        assert_eq!(linked_list.back(), Some(&8));
+
+        assert_eq!(linked_list.extract_if(|value| *value > 2).next(), Some(4));
+        assert_eq!(linked_list, LinkedList::from(vec![2, 8]));
+
+        linked_list.clear();
+        assert_eq!(linked_list.size(), 0);
+        assert_eq!(linked_list.front(), None);
+        linked_list.push_back(1);
+        assert_eq!(linked_list, LinkedList::from(vec![1]));
+    }
+
     #[test]
     fn contains() {
         let mut linked_list = LinkedList::new();
@@ -262,4 +878,39 @@ mod tests {
         assert!(linked_list.contains(3));
         assert!(!linked_list.contains(8));
     }
+
+    #[test]
+    fn custom_allocator() {
+        let counting = Counting::default();
+        let mut linked_list = LinkedList::new_in(&counting);
+        linked_list.extend([1, 2, 3, 4]);
+        linked_list.pop_front();
+        assert_eq!(counting.allocated.get(), 4);
+        assert_eq!(counting.live.get(), 3);
+
+        let mut copy = linked_list.clone();
+        let after = copy.cursor_front_mut().split_after();
+        assert_eq!(after.iter().copied().collect::<Vec<_>>(), [3, 4]);
+        assert_eq!(counting.live.get(), 6);
+
+        drop(after);
+        drop(copy);
+        assert_eq!(counting.live.get(), 3);
+
+        let other = Counting::default();
+        let mut front = LinkedList::new_in(&other);
+        front.extend([0, 1]);
+        let mut cursor = linked_list.cursor_front_mut();
+        cursor.move_prev();
+        cursor.splice_after(front);
+        let mut back = LinkedList::new_in(&other);
+        back.extend([5, 6]);
+        linked_list.concat(back);
+        assert_eq!(other.live.get(), 0);
+        assert_eq!(counting.live.get(), 7);
+        assert!(linked_list.iter().copied().eq([0, 1, 2, 3, 4, 5, 6]));
+
+        assert_eq!(linked_list.into_iter().count(), 7);
+        assert_eq!(counting.live.get(), 0);
+    }
 }
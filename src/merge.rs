@@ -0,0 +1,147 @@
+//! policies for resolving duplicate keys when merging two maps
+use alloc_crate::vec::Vec;
+use core::cmp::Ordering;
+use core::iter::Peekable;
+
+/// decides which value to keep when both maps have an entry with `key`
+/// `left` is the value from the map being merged into,
+/// `right` the value from the map being merged in
+///
+/// any closure taking the key and both values implements this,
+/// so values can be combined instead of discarded
+pub trait MergePolicy<K, V> {
+    fn resolve(&mut self, key: &K, left: V, right: V) -> V;
+}
+
+/// keeps the value that was already in the map
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeepLeft;
+
+/// replaces the value that was already in the map
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeepRight;
+
+impl<K, V> MergePolicy<K, V> for KeepLeft {
+    fn resolve(&mut self, _: &K, left: V, _: V) -> V {
+        left
+    }
+}
+
+impl<K, V> MergePolicy<K, V> for KeepRight {
+    fn resolve(&mut self, _: &K, _: V, right: V) -> V {
+        right
+    }
+}
+
+impl<K, V, F> MergePolicy<K, V> for F
+where
+    F: FnMut(&K, V, V) -> V,
+{
+    fn resolve(&mut self, key: &K, left: V, right: V) -> V {
+        self(key, left, right)
+    }
+}
+
+/// merges two iterators of entries sorted by key into one sorted `Vec`,
+/// resolving duplicate keys with `policy`
+/// on a duplicate, the key from `left` is kept
+pub fn merge_sorted<K, V, P>(
+    left: impl Iterator<Item = (K, V)>,
+    right: impl Iterator<Item = (K, V)>,
+    policy: P,
+) -> Vec<(K, V)>
+where
+    K: Ord,
+    P: MergePolicy<K, V>,
+{
+    MergeSorted::new(left, right, policy).collect()
+}
+
+/// an iterator merging two iterators of entries sorted by key,
+/// resolving duplicate keys with `policy`
+/// on a duplicate, the key from `left` is kept
+pub struct MergeSorted<L, R, P>
+where
+    L: Iterator,
+    R: Iterator,
+{
+    left: Peekable<L>,
+    right: Peekable<R>,
+    policy: P,
+}
+
+impl<K, V, L, R, P> MergeSorted<L, R, P>
+where
+    K: Ord,
+    L: Iterator<Item = (K, V)>,
+    R: Iterator<Item = (K, V)>,
+    P: MergePolicy<K, V>,
+{
+    pub fn new(left: L, right: R, policy: P) -> MergeSorted<L, R, P> {
+        MergeSorted {
+            left: left.peekable(),
+            right: right.peekable(),
+            policy,
+        }
+    }
+}
+
+impl<K, V, L, R, P> Iterator for MergeSorted<L, R, P>
+where
+    K: Ord,
+    L: Iterator<Item = (K, V)>,
+    R: Iterator<Item = (K, V)>,
+    P: MergePolicy<K, V>,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        let ordering = match (self.left.peek(), self.right.peek()) {
+            (Some((left_key, _)), Some((right_key, _))) => {
+                left_key.cmp(right_key)
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return None,
+        };
+        match ordering {
+            Ordering::Less => self.left.next(),
+            Ordering::Greater => self.right.next(),
+            Ordering::Equal => self.left.next().zip(self.right.next()).map(
+                |((key, left), (_, right))| {
+                    let value = self.policy.resolve(&key, left, right);
+                    (key, value)
+                },
+            ),
+        }
+    }
+}
+
+/// counts the keys that two iterators of keys, both sorted, have in common
+pub fn count_common<'a, K>(
+    left: impl Iterator<Item = &'a K>,
+    right: impl Iterator<Item = &'a K>,
+) -> usize
+where
+    K: Ord + 'a,
+{
+    let mut left = left.peekable();
+    let mut right = right.peekable();
+    let mut common = 0;
+    while let (Some(left_key), Some(right_key)) = (left.peek(), right.peek()) {
+        match left_key.cmp(right_key) {
+            Ordering::Less => {
+                left.next();
+            }
+            Ordering::Greater => {
+                right.next();
+            }
+            Ordering::Equal => {
+                common += 1;
+                left.next();
+                right.next();
+            }
+        }
+    }
+    common
+}
@@ -0,0 +1,202 @@
+//! a view into a single entry of an `RbTreeMap`, which may or may not exist
+//!
+//! inserting or removing an entry may rotate any node on the path from the
+//! root, so unlike a `BsTreeMap` entry, these hold on to the whole map
+//! an occupied entry also keeps the entry it found, and its rank, which
+//! the rotations never change
+use super::map::RbTreeMap;
+use crate::entry::Entry;
+use core::mem;
+use core::ptr::NonNull;
+
+/// an entry of an `RbTreeMap`, as returned by `RbTreeMap::entry`
+pub enum MapEntry<'a, K, V>
+where
+    K: Ord,
+{
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+/// an entry that exists in the map
+/// `entry` is where it is kept, and `rank` the number of smaller keys
+pub struct OccupiedEntry<'a, K, V>
+where
+    K: Ord,
+{
+    entry: NonNull<Entry<K, V>>,
+    rank: usize,
+    map: &'a mut RbTreeMap<K, V>,
+}
+
+// SAFETY: the entry has exclusive access to the map, like the
+// `&mut RbTreeMap` it comes from
+unsafe impl<K, V> Send for OccupiedEntry<'_, K, V>
+where
+    K: Ord + Send,
+    V: Send,
+{
+}
+
+unsafe impl<K, V> Sync for OccupiedEntry<'_, K, V>
+where
+    K: Ord + Sync,
+    V: Sync,
+{
+}
+
+/// an entry that does not exist in the map yet
+pub struct VacantEntry<'a, K, V>
+where
+    K: Ord,
+{
+    key: K,
+    map: &'a mut RbTreeMap<K, V>,
+}
+
+impl<'a, K, V> MapEntry<'a, K, V>
+where
+    K: Ord,
+{
+    /// returns a reference to the key of the entry
+    pub fn key(&self) -> &K {
+        match self {
+            MapEntry::Occupied(entry) => entry.key(),
+            MapEntry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// inserts `default` if the entry is vacant
+    /// returns a mutable reference to the value
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// inserts the result of `default` if the entry is vacant
+    /// returns a mutable reference to the value
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            MapEntry::Occupied(entry) => entry.into_mut(),
+            MapEntry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// calls `f` on the value if the entry is occupied
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let MapEntry::Occupied(ref mut entry) = self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V> MapEntry<'a, K, V>
+where
+    K: Ord,
+    V: Default,
+{
+    /// inserts the default value if the entry is vacant
+    /// returns a mutable reference to the value
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V>
+where
+    K: Ord,
+{
+    pub(super) fn new(
+        entry: NonNull<Entry<K, V>>,
+        rank: usize,
+        map: &'a mut RbTreeMap<K, V>,
+    ) -> OccupiedEntry<'a, K, V> {
+        OccupiedEntry { entry, rank, map }
+    }
+
+    fn entry(&self) -> &Entry<K, V> {
+        // SAFETY: `entry` was found in the map, which nothing but `self`
+        // can change, and `self` only changes values until it is consumed
+        unsafe { self.entry.as_ref() }
+    }
+
+    /// returns a reference to the key of the entry
+    pub fn key(&self) -> &K {
+        self.entry().key()
+    }
+
+    /// returns a reference to the value of the entry
+    pub fn get(&self) -> &V {
+        &self.entry().value
+    }
+
+    /// returns a mutable reference to the value of the entry
+    pub fn get_mut(&mut self) -> &mut V {
+        // SAFETY: as in `entry`, and `self` is borrowed mutably
+        &mut unsafe { self.entry.as_mut() }.value
+    }
+
+    /// turns the entry into a mutable reference to its value
+    pub fn into_mut(mut self) -> &'a mut V {
+        // SAFETY: as in `entry`, and the map is borrowed mutably for `'a`,
+        // which `self` gives up
+        &mut unsafe { self.entry.as_mut() }.value
+    }
+
+    /// sets the value of the entry to `value`, and returns the old value
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// removes the entry from the map and returns it
+    pub fn remove_entry(self) -> Entry<K, V> {
+        let entry = self.map.remove_nth(self.rank);
+        // SAFETY: `rank` is the rank of `entry`, which is still in the map,
+        // so there is an entry to remove
+        unsafe { entry.unwrap_unchecked() }
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V>
+where
+    K: Ord,
+{
+    pub(super) fn new(
+        key: K,
+        map: &'a mut RbTreeMap<K, V>,
+    ) -> VacantEntry<'a, K, V> {
+        VacantEntry { key, map }
+    }
+
+    /// returns a reference to the key that would be used for the entry
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// takes back ownership of the key
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// inserts the entry into the map with value `value`
+    /// returns a mutable reference to the value
+    pub fn insert(self, value: V) -> &'a mut V {
+        // the repairs after inserting may move the new node anywhere on
+        // its path, so it is found again by the rank it was inserted at,
+        // without comparing any keys
+        let map = self.map;
+        let entry = map
+            .insert_ranked(self.key, value)
+            .and_then(|rank| map.nth_mut(rank));
+        // SAFETY: the key of a vacant entry is not in the map, so it was
+        // inserted, and is found at its rank
+        let (_, value) = unsafe { entry.unwrap_unchecked() };
+        value
+    }
+}
@@ -0,0 +1,33 @@
+//! in-order iterators over `RbTreeMap`
+use super::node::Node;
+use crate::iter;
+use alloc_crate::boxed::Box;
+
+/// an iterator over the entries of an `RbTreeMap`, in order of their keys
+pub type Iter<'a, K, V> = iter::Iter<&'a Node<K, V>>;
+
+/// a mutable iterator over the entries of an `RbTreeMap`,
+/// in order of their keys
+pub type IterMut<'a, K, V> = iter::Iter<&'a mut Node<K, V>>;
+
+/// an owning iterator over the entries of an `RbTreeMap`,
+/// in order of their keys
+pub type IntoIter<K, V> = iter::IntoIter<Box<Node<K, V>>>;
+
+/// an iterator over the keys of an `RbTreeMap`, in order
+pub type Keys<'a, K, V> = iter::Keys<Iter<'a, K, V>>;
+
+/// an iterator over the values of an `RbTreeMap`, in order of their keys
+pub type Values<'a, K, V> = iter::Values<Iter<'a, K, V>>;
+
+/// a mutable iterator over the values of an `RbTreeMap`,
+/// in order of their keys
+pub type ValuesMut<'a, K, V> = iter::Values<IterMut<'a, K, V>>;
+
+/// an iterator over the entries of an `RbTreeMap` whose keys lie within
+/// a range, in order of their keys
+pub type Range<'a, K, V> = iter::Range<&'a Node<K, V>>;
+
+/// a mutable iterator over the entries of an `RbTreeMap` whose keys lie
+/// within a range, in order of their keys
+pub type RangeMut<'a, K, V> = iter::Range<&'a mut Node<K, V>>;
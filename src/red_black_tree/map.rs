@@ -0,0 +1,847 @@
+//! a self-balancing binary search tree with keys and values
+//! every node is either red or black, a red node never has a red child, and
+//! every path from the root down to an empty link passes through the same
+//! number of black nodes, so no path is more than twice as long as another
+//!
+//! compared to an `AvlTreeMap`, lookups may take slightly longer,
+//! but insertions and removals rotate less
+
+use super::node::Node;
+use crate::entry::{Entry, Value};
+use crate::merge::merge_sorted;
+use alloc_crate::boxed::Box;
+use alloc_crate::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::mem;
+use core::ops::RangeBounds;
+use core::ptr::NonNull;
+
+pub use super::entry::{MapEntry, OccupiedEntry, VacantEntry};
+pub use super::iter::{
+    IntoIter, Iter, IterMut, Keys, Range, RangeMut, Values, ValuesMut,
+};
+pub use crate::merge::{KeepLeft, KeepRight, MergePolicy};
+
+pub struct RbTreeMap<K, V>
+where
+    K: Ord,
+    V: Value + ?Sized,
+{
+    root: Option<Box<Node<K, V>>>,
+    size: usize,
+}
+
+impl<K, V> RbTreeMap<K, V>
+where
+    K: Ord,
+{
+    /// creates an empty `RbTreeMap`
+    #[inline]
+    pub const fn new() -> RbTreeMap<K, V> {
+        RbTreeMap {
+            root: None,
+            size: 0,
+        }
+    }
+
+    /// returns the number of entries in `self`
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// returns `true` if `self` contains an entry with key `key`
+    /// otherwise returns `false`
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.root {
+            Some(ref node) => node.contains(key),
+            None => false,
+        }
+    }
+
+    /// moves all entries of `other` into `self`
+    /// if both contain an entry with the same key, `policy` decides on
+    /// its value: `KeepLeft` keeps the value in `self`, `KeepRight` takes
+    /// the one from `other`, and a closure can combine the two
+    ///
+    /// runs in linear time
+    pub fn merge<P>(&mut self, other: RbTreeMap<K, V>, policy: P)
+    where
+        P: MergePolicy<K, V>,
+    {
+        let this = mem::take(self);
+        let merged = merge_sorted(this.into_iter(), other.into_iter(), policy);
+        *self = RbTreeMap::from_sorted(merged);
+    }
+
+    /// moves every entry with a key of at least `key` into a new map
+    /// and returns it
+    pub fn split_off<Q>(&mut self, key: &Q) -> RbTreeMap<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut entries: Vec<(K, V)> = mem::take(self).into_iter().collect();
+        let at = entries.partition_point(|(left, _)| left.borrow() < key);
+        let right = entries.split_off(at);
+        *self = RbTreeMap::from_sorted(entries);
+        RbTreeMap::from_sorted(right)
+    }
+
+    /// moves all entries of `other` into `self`, leaving `other` empty
+    /// if both contain an entry with the same key, the value from `other`
+    /// is kept
+    pub fn append(&mut self, other: &mut RbTreeMap<K, V>) {
+        self.merge(mem::take(other), KeepRight);
+    }
+
+    /// builds a tree out of `entries`, which must be sorted by key
+    /// and free of duplicates
+    fn from_sorted(entries: Vec<(K, V)>) -> RbTreeMap<K, V> {
+        RbTreeMap {
+            size: entries.len(),
+            root: Node::from_sorted(entries.len(), &mut entries.into_iter()),
+        }
+    }
+
+    /// sets the value of the key with key `key` to `value`
+    /// if `key` already exists, the value is overridden
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) {
+        if Node::insert(&mut self.root, key, value).is_some() {
+            self.size += 1;
+        }
+        Node::blacken(&mut self.root);
+    }
+
+    /// inserts a new entry with key `key` and value `value`
+    /// if `key` already exists, the old value is kept
+    #[inline]
+    pub fn try_insert(&mut self, key: K, value: V) {
+        if Node::try_insert(&mut self.root, key, value).is_some() {
+            self.size += 1;
+        }
+        Node::blacken(&mut self.root);
+    }
+
+    /// removes the entry with key `key` and returns it
+    /// returns `None` if no such entry exists
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let entry = Node::remove(&mut self.root, key)?;
+        Node::blacken(&mut self.root);
+        self.size -= 1;
+        Some(entry)
+    }
+
+    /// inserts a new entry with key `key` and value `value`, like
+    /// `try_insert`, and returns its rank
+    /// returns `None` if `key` already exists
+    pub(super) fn insert_ranked(&mut self, key: K, value: V) -> Option<usize> {
+        let rank = Node::try_insert(&mut self.root, key, value);
+        Node::blacken(&mut self.root);
+        self.size += rank.is_some() as usize;
+        rank
+    }
+
+    /// removes the entry with `index` smaller entries and returns it
+    /// returns `None` if `index` is out of bounds
+    pub(super) fn remove_nth(&mut self, index: usize) -> Option<Entry<K, V>> {
+        let entry = Node::remove_nth(&mut self.root, index)?;
+        Node::blacken(&mut self.root);
+        self.size -= 1;
+        Some(entry)
+    }
+
+    /// returns an optional reference to the `value` with key `key`
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.root {
+            Some(ref node) => node.get(key),
+            None => None,
+        }
+    }
+
+    /// returns an optional mutable reference to the `value` with key `key`
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.root {
+            Some(ref mut node) => node.get_mut(key),
+            None => None,
+        }
+    }
+
+    /// returns an optional reference to the smallest entry
+    pub fn smallest(&self) -> Option<&Entry<K, V>> {
+        self.root.as_ref().map(|node| node.smallest())
+    }
+
+    /// returns an optional mutable reference to the smallest entry
+    pub fn smallest_mut(&mut self) -> Option<&mut Entry<K, V>> {
+        self.root.as_mut().map(|node| node.smallest_mut())
+    }
+
+    /// returns an optional reference to the largest entry
+    pub fn largest(&self) -> Option<&Entry<K, V>> {
+        self.root.as_ref().map(|node| node.largest())
+    }
+
+    /// returns an optional mutable reference to the largest entry
+    pub fn largest_mut(&mut self) -> Option<&mut Entry<K, V>> {
+        self.root.as_mut().map(|node| node.largest_mut())
+    }
+
+    /// returns the entry with `index` smaller entries,
+    /// or `None` if `index` is out of bounds
+    /// takes time proportional to the height of `self`
+    pub fn nth(&self, index: usize) -> Option<(&K, &V)> {
+        self.root.as_ref()?.nth(index).map(Entry::split)
+    }
+
+    /// returns the entry with `index` smaller entries,
+    /// with a mutable reference to its value,
+    /// or `None` if `index` is out of bounds
+    pub fn nth_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.root.as_mut()?.nth_mut(index).map(Entry::split_mut)
+    }
+
+    /// returns the number of entries with keys smaller than `key`,
+    /// whether or not `self` contains `key`
+    /// takes time proportional to the height of `self`
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.root.as_ref().map_or(0, |node| node.rank(key))
+    }
+
+    /// returns the key that has `rank` smaller keys,
+    /// the inverse of `rank` for keys in `self`
+    pub fn select(&self, rank: usize) -> Option<&K> {
+        self.nth(rank).map(|(key, _)| key)
+    }
+
+    /// returns the entry with key `key`, which can be filled in if it is
+    /// vacant, or read, changed and removed if it is occupied
+    /// the tree is only descended once to find it
+    pub fn entry(&mut self, key: K) -> MapEntry<'_, K, V> {
+        let (rank, entry) = match self.root {
+            Some(ref mut node) => node.search(&key),
+            None => (0, None),
+        };
+        match entry.map(NonNull::from) {
+            Some(entry) => {
+                MapEntry::Occupied(OccupiedEntry::new(entry, rank, self))
+            }
+            None => MapEntry::Vacant(VacantEntry::new(key, self)),
+        }
+    }
+
+    /// returns an optional reference to the `Entry` with key `key`
+    pub fn get_entry<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.root {
+            Some(ref node) => node.entry(key),
+            None => None,
+        }
+    }
+
+    /// returns an iterator over the entries of `self`, in order of their keys
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(self.root.as_deref(), self.size)
+    }
+
+    /// returns an iterator over the entries of `self`, in order of their keys,
+    /// with mutable references to the values
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut::new(self.root.as_deref_mut(), self.size)
+    }
+
+    /// returns an iterator over the keys of `self`, in order
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys::new(self.iter())
+    }
+
+    /// returns an iterator over the values of `self`, in order of their keys
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values::new(self.iter())
+    }
+
+    /// returns an iterator over mutable references to the values of `self`,
+    /// in order of their keys
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut::new(self.iter_mut())
+    }
+
+    /// returns an iterator over the entries of `self` whose keys lie within
+    /// `range`, in order of their keys
+    /// if the start of `range` is after its end, the iterator is empty
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Range::new(self.root.as_deref(), &range)
+    }
+
+    /// returns an iterator over the entries of `self` whose keys lie within
+    /// `range`, in order of their keys, with mutable references to the values
+    /// if the start of `range` is after its end, the iterator is empty
+    pub fn range_mut<Q, R>(&mut self, range: R) -> RangeMut<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        RangeMut::new(self.root.as_deref_mut(), &range)
+    }
+}
+
+impl<K, V> IntoIterator for RbTreeMap<K, V>
+where
+    K: Ord,
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self.root, self.size)
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a RbTreeMap<K, V>
+where
+    K: Ord,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut RbTreeMap<K, V>
+where
+    K: Ord,
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> Default for RbTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// sorts the entries and builds a tree out of them in one go
+/// if a key appears more than once, its last value is kept
+impl<K, V> FromIterator<(K, V)> for RbTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut entries: Vec<(K, V)> = iter.into_iter().collect();
+        entries.sort_by(|(left, _), (right, _)| left.cmp(right));
+        entries.dedup_by(|next, kept| {
+            let duplicate = next.0 == kept.0;
+            if duplicate {
+                mem::swap(&mut next.1, &mut kept.1);
+            }
+            duplicate
+        });
+        RbTreeMap::from_sorted(entries)
+    }
+}
+
+impl<K, V> Extend<(K, V)> for RbTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V> Extend<(&'a K, &'a V)> for RbTreeMap<K, V>
+where
+    K: Ord + Copy,
+    V: Copy,
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(&key, &value)| (key, value)));
+    }
+}
+
+impl<K, V, const N: usize> From<[(K, V); N]> for RbTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from(array: [(K, V); N]) -> Self {
+        Self::from_iter(array)
+    }
+}
+
+impl<K, V> From<Vec<(K, V)>> for RbTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from(vec: Vec<(K, V)>) -> Self {
+        Self::from_iter(vec)
+    }
+}
+
+impl<K, V> Clone for RbTreeMap<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        self.iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+impl<K, V> fmt::Debug for RbTreeMap<K, V>
+where
+    K: Ord + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K, V> PartialEq for RbTreeMap<K, V>
+where
+    K: Ord,
+    V: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.size() == other.size() && self.iter().eq(other)
+    }
+}
+
+impl<K, V> Eq for RbTreeMap<K, V>
+where
+    K: Ord,
+    V: Eq,
+{
+}
+
+impl<K, V> Hash for RbTreeMap<K, V>
+where
+    K: Ord + Hash,
+    V: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.size());
+        for entry in self {
+            entry.hash(state);
+        }
+    }
+}
+
+impl<K, V> PartialOrd for RbTreeMap<K, V>
+where
+    K: Ord,
+    V: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<K, V> Ord for RbTreeMap<K, V>
+where
+    K: Ord,
+    V: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::node::Color;
+    use super::*;
+    use crate::entry::Counted;
+    use alloc_crate::string::ToString;
+    use alloc_crate::{format, vec};
+    use core::cell::Cell;
+
+    // set up a tree to apply tests to
+    fn basic_tree() -> RbTreeMap<usize, &'static str> {
+        let mut tree = RbTreeMap::new();
+        tree.insert(5, " , ");
+        tree.insert(3, "hello");
+        tree.insert(6, "world");
+        tree.insert(7, "this is the largest entry");
+        tree.insert(1, "this is the smallest entry");
+        tree.insert(4, "hmmm");
+
+        tree
+    }
+
+    // checks the ordering and colors of every node,
+    // returning the number of black nodes on every path down from `link`
+    fn check_node<V>(
+        link: &Option<Box<Node<usize, V>>>,
+        low: Option<usize>,
+        high: Option<usize>,
+    ) -> usize {
+        let Some(node) = link else {
+            return 0;
+        };
+        let key = *node.key();
+        assert!(low.is_none_or(|low| low < key));
+        assert!(high.is_none_or(|high| key < high));
+        if node.color() == Color::Red {
+            assert!(!Node::is_red(&node.left), "red child of red {key}");
+            assert!(!Node::is_red(&node.right), "red child of red {key}");
+        }
+        let left = check_node(&node.left, low, Some(key));
+        let right = check_node(&node.right, Some(key), high);
+        assert_eq!(left, right, "black heights differ at {key}");
+        assert_eq!(
+            node.size(),
+            1 + Node::size_of(&node.left) + Node::size_of(&node.right)
+        );
+        left + usize::from(node.color() == Color::Black)
+    }
+
+    fn check<V>(tree: &RbTreeMap<usize, V>) {
+        assert!(!Node::is_red(&tree.root), "red root");
+        check_node(&tree.root, None, None);
+        assert_eq!(tree.iter().count(), tree.size());
+        assert_eq!(Node::size_of(&tree.root), tree.size());
+    }
+
+    #[test]
+    fn insert() {
+        let mut tree = RbTreeMap::new();
+        tree.insert(5, " , ");
+        tree.insert(3, "hello");
+        tree.insert(7, "haha get replaced");
+        tree.insert(6, "world");
+        tree.insert(7, "hii");
+
+        assert_eq!(tree.size(), 4);
+        assert_eq!(tree.get(&7), Some(&"hii"));
+        assert_eq!(tree.get(&8), None);
+        check(&tree);
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut tree = basic_tree();
+        tree.try_insert(7, "already exists!");
+        assert_eq!(tree.size(), 6);
+        assert_eq!(tree.get(&7), Some(&"this is the largest entry"));
+        tree.try_insert(9, "does not exist yet");
+        assert_eq!(tree.size(), 7);
+        check(&tree);
+    }
+
+    #[test]
+    fn sorted_insert_stays_balanced() {
+        let mut tree = RbTreeMap::new();
+        for key in 0..1000 {
+            tree.insert(key, key);
+            check(&tree);
+        }
+        for key in (0..1000).rev() {
+            tree.insert(key + 1000, key);
+            check(&tree);
+        }
+        assert_eq!(tree.size(), 2000);
+    }
+
+    #[test]
+    fn remove() {
+        let mut tree = basic_tree();
+        assert_eq!(tree.remove(&5), Some(Entry::new(5, " , ")));
+        assert!(!tree.contains(&5));
+        assert_eq!(tree.size(), 5);
+
+        assert_eq!(tree.remove(&3), Some(Entry::new(3, "hello")));
+        assert_eq!(tree.remove(&3), None);
+        assert_eq!(tree.size(), 4);
+        check(&tree);
+
+        let keys: Vec<&usize> = tree.keys().collect();
+        assert_eq!(keys, [&1, &4, &6, &7]);
+    }
+
+    #[test]
+    fn remove_stays_balanced() {
+        let mut tree = RbTreeMap::new();
+        for key in 0..500 {
+            tree.insert((key * 7919) % 500, key);
+        }
+        for key in (0..500).step_by(3) {
+            assert!(tree.remove(&key).is_some());
+            check(&tree);
+        }
+        for key in 0..500 {
+            assert_eq!(tree.contains(&key), key % 3 != 0);
+        }
+        while let Some(key) = tree.largest().map(|entry| *entry.key()) {
+            tree.remove(&key);
+            check(&tree);
+        }
+        assert_eq!(tree.size(), 0);
+        assert!(tree.root.is_none());
+    }
+
+    #[test]
+    fn entry() {
+        let mut tree = RbTreeMap::new();
+        for word in "the cat saw the dog and the bird".split(' ') {
+            tree.entry(word)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+        assert_eq!(tree.size(), 6);
+        assert_eq!(tree.get(&"the"), Some(&3));
+        assert_eq!(tree.get(&"cat"), Some(&1));
+
+        *tree.entry("dog").or_default() += 10;
+        *tree.entry("fox").or_default() += 10;
+        assert_eq!(tree.get(&"dog"), Some(&11));
+        assert_eq!(tree.get(&"fox"), Some(&10));
+        assert_eq!(*tree.entry("cow").or_insert_with(|| 5), 5);
+        assert_eq!(tree.entry("cow").key(), &"cow");
+        assert_eq!(tree.size(), 8);
+
+        let mut tree = basic_tree();
+        if let MapEntry::Occupied(mut entry) = tree.entry(3) {
+            assert_eq!(entry.insert("goodbye"), "hello");
+            assert_eq!(entry.remove_entry(), Entry::new(3, "goodbye"));
+        }
+        for key in 10..100 {
+            if let MapEntry::Vacant(entry) = tree.entry(key) {
+                *entry.insert("new") = "replaced";
+            }
+        }
+        assert_eq!(tree.size(), 95);
+        assert!((10..100).all(|key| tree.get(&key) == Some(&"replaced")));
+        check(&tree);
+    }
+
+    #[test]
+    fn entries_find_their_entry_once() {
+        let compared = Cell::new(0);
+        let mut tree = RbTreeMap::new();
+        for key in 0..100 {
+            tree.insert(Counted(key, &compared), key);
+        }
+
+        // a lookup costs as many comparisons as the key is deep
+        compared.set(0);
+        assert!(tree.contains(&Counted(37, &compared)));
+        let depth = compared.get();
+
+        compared.set(0);
+        match tree.entry(Counted(37, &compared)) {
+            MapEntry::Occupied(mut entry) => {
+                assert_eq!(entry.key().0, 37);
+                *entry.get_mut() += 1;
+                assert_eq!(entry.insert(0), 38);
+                assert_eq!(entry.remove_entry().into_pair().1, 0);
+            }
+            MapEntry::Vacant(_) => panic!("37 is in the tree"),
+        }
+        assert_eq!(compared.get(), depth);
+
+        // one descent to find the entry vacant, and one to insert it
+        compared.set(0);
+        assert!(!tree.contains(&Counted(37, &compared)));
+        let depth = compared.get();
+        compared.set(0);
+        match tree.entry(Counted(37, &compared)) {
+            MapEntry::Vacant(entry) => *entry.insert(0) = 37,
+            MapEntry::Occupied(_) => panic!("37 is not in the tree"),
+        }
+        assert_eq!(compared.get(), 2 * depth);
+
+        assert_eq!(tree.size(), 100);
+        let values: Vec<usize> = tree.values().copied().collect();
+        assert_eq!(values, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn borrowed_lookups() {
+        let mut tree = RbTreeMap::new();
+        for word in ["pear", "apple", "plum", "fig"] {
+            tree.insert(word.to_string(), word.len());
+        }
+        assert!(tree.contains("fig"));
+        assert_eq!(tree.get("plum"), Some(&4));
+        assert_eq!(tree.remove("pear"), Some(Entry::new("pear".into(), 4)));
+        assert_eq!(tree.remove("kiwi"), None);
+    }
+
+    #[test]
+    fn merge() {
+        let mut left = RbTreeMap::new();
+        let mut right = RbTreeMap::new();
+        for key in 0..100 {
+            left.insert(key, "left");
+            right.insert(key + 50, "right");
+        }
+        let mut tree = left;
+        tree.merge(right, KeepLeft);
+        assert_eq!(tree.size(), 150);
+        assert!(tree.keys().copied().eq(0..150));
+        assert_eq!(tree.get(&70), Some(&"left"));
+        assert_eq!(tree.get(&120), Some(&"right"));
+        check(&tree);
+
+        for size in 0..70 {
+            let tree: RbTreeMap<_, _> =
+                (0..size).map(|key| (key, ())).collect();
+            check(&tree);
+        }
+    }
+
+    #[test]
+    fn iter() {
+        let mut tree = basic_tree();
+        let keys: Vec<usize> = tree.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys, [1, 3, 4, 5, 6, 7]);
+
+        for value in tree.values_mut().rev().take(1) {
+            *value = "replaced";
+        }
+        assert_eq!(tree.get(&7), Some(&"replaced"));
+        let range: Vec<&usize> = tree.range(2..6).map(|(key, _)| key).collect();
+        assert_eq!(range, [&3, &4, &5]);
+
+        let mut iter = tree.into_iter();
+        assert_eq!(iter.len(), 6);
+        assert_eq!(iter.next(), Some((1, "this is the smallest entry")));
+        assert_eq!(iter.next_back(), Some((7, "replaced")));
+    }
+
+    #[test]
+    fn smallest_and_largest() {
+        let tree = basic_tree();
+        assert_eq!(
+            tree.smallest(),
+            Some(&Entry::new(1, "this is the smallest entry"))
+        );
+        assert_eq!(
+            tree.largest(),
+            Some(&Entry::new(7, "this is the largest entry"))
+        );
+    }
+
+    #[test]
+    fn order_statistics() {
+        let mut tree = RbTreeMap::new();
+        for key in 0..200 {
+            tree.insert((key * 7919) % 200, key);
+        }
+        for key in (0..200).step_by(3) {
+            tree.remove(&key);
+        }
+        let keys: Vec<usize> = tree.keys().copied().collect();
+        for (index, key) in keys.iter().enumerate() {
+            assert_eq!(tree.nth(index).map(|(key, _)| key), Some(key));
+            assert_eq!(tree.select(index), Some(key));
+            assert_eq!(tree.rank(key), index);
+            assert_eq!(tree.rank(&(key + 1)), index + 1);
+        }
+        assert_eq!(tree.nth(keys.len()), None);
+        assert_eq!(tree.rank(&1000), keys.len());
+        if let Some((_, value)) = tree.nth_mut(0) {
+            *value = 1000;
+        }
+        assert_eq!(tree.get(&keys[0]), Some(&1000));
+        assert_eq!(RbTreeMap::<usize, usize>::new().nth(0), None);
+    }
+
+    #[test]
+    fn split_off_and_append() {
+        let mut tree: RbTreeMap<usize, usize> =
+            (0..100).map(|key| (key, key)).collect();
+        for at in [50, 0, 100] {
+            let mut right = tree.split_off(&at);
+            assert!(tree.keys().copied().eq(0..at));
+            assert!(right.keys().copied().eq(at..100));
+            assert_eq!(tree.size() + right.size(), 100);
+            check(&tree);
+            check(&right);
+            tree.append(&mut right);
+            assert_eq!(right.size(), 0);
+            assert_eq!(tree.size(), 100);
+        }
+        let mut other: RbTreeMap<_, _> =
+            (90..110).map(|key| (key, 0)).collect();
+        tree.append(&mut other);
+        assert_eq!(tree.size(), 110);
+        assert_eq!(tree.get(&95), Some(&0));
+        assert_eq!(tree.get(&5), Some(&5));
+        assert!(tree.split_off(&200).iter().next().is_none());
+        assert_eq!(tree.size(), 110);
+        check(&tree);
+    }
+
+    #[test]
+    fn std_traits() {
+        let tree: RbTreeMap<usize, usize> =
+            (0..100).rev().map(|key| (key, key)).collect();
+        assert_eq!(tree.size(), 100);
+        assert!(tree.keys().copied().eq(0..100));
+        check(&tree);
+        let mut other = tree.clone();
+        assert_eq!(other, tree);
+        other.extend([(100, 100), (0, 1)]);
+        other.extend([(&101, &101)]);
+        assert_eq!(other.size(), 102);
+        assert_eq!(other.get(&0), Some(&1));
+        assert!(tree < other);
+        check(&other);
+
+        let small = RbTreeMap::from([(2, "b"), (1, "a"), (2, "c")]);
+        assert_eq!(format!("{small:?}"), r#"{1: "a", 2: "c"}"#);
+        assert_eq!(small, RbTreeMap::from(vec![(1, "a"), (2, "c")]));
+        assert_ne!(small, RbTreeMap::from([(1, "a")]));
+    }
+}
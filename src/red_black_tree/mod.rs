@@ -0,0 +1,5 @@
+mod entry;
+mod iter;
+pub mod map;
+mod node;
+pub mod set;
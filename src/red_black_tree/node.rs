@@ -0,0 +1,762 @@
+//! a node in `RbTreeMap`
+//! nodes do not know their parents, so insertion and removal recurse
+//! down to the affected node and repair the tree on the way back up
+use crate::entry::{Entry, Value};
+use crate::traversal::{Expand, Split, Step};
+use alloc_crate::boxed::Box;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Black,
+}
+
+/// `left` represents entries that have smaller `key`s than `self.key`
+/// `right` represents entries that have greater `key`s than `self.key`
+/// `size` is the number of entries in the subtree rooted at `self`,
+/// including `self`
+///
+/// a red node never has a red child, and every path from a node down to
+/// an empty link passes through the same number of black nodes
+pub struct Node<K, V>
+where
+    K: Ord,
+    V: Value + ?Sized,
+{
+    entry: Entry<K, V>,
+    color: Color,
+    size: usize,
+    pub left: Option<Box<Node<K, V>>>,
+    pub right: Option<Box<Node<K, V>>>,
+}
+
+impl<K, V> Node<K, V>
+where
+    K: Ord,
+{
+    /// creates a single red `Entry` with key `key` and value `value`
+    #[inline]
+    pub fn new(key: K, value: V) -> Node<K, V> {
+        Node {
+            entry: Entry::new(key, value),
+            color: Color::Red,
+            size: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    /// returns an optional reference to the `value` with key `key`
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entry(key).map(|entry| &entry.value)
+    }
+
+    /// returns an optional mutable reference to the `value` with key `key`
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entry_mut(key).map(|entry| &mut entry.value)
+    }
+
+    /// returns a reference to the `Entry` with key `key`
+    pub fn entry<Q>(&self, key: &Q) -> Option<&Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match key.cmp(self.key().borrow()) {
+            Ordering::Less => self.left.as_ref()?.entry(key),
+            Ordering::Greater => self.right.as_ref()?.entry(key),
+            Ordering::Equal => Some(&self.entry),
+        }
+    }
+
+    /// returns a mutable reference to the `Entry` with key `key`
+    pub fn entry_mut<Q>(&mut self, key: &Q) -> Option<&mut Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match key.cmp(self.key().borrow()) {
+            Ordering::Less => self.left.as_mut()?.entry_mut(key),
+            Ordering::Greater => self.right.as_mut()?.entry_mut(key),
+            Ordering::Equal => Some(&mut self.entry),
+        }
+    }
+
+    /// returns a reference to the key
+    pub fn key(&self) -> &K {
+        self.entry.key()
+    }
+
+    /// returns the color of the node
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// returns `true` if the subtree rooted at `self` contains `key`
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entry(key).is_some()
+    }
+
+    /// sets the value of the entry with key `key` to `value`
+    /// in the subtree rooted at `link`, then repairs it
+    /// if `key` already exists, the value is overwritten
+    /// otherwise, a new entry is added
+    /// returns the rank of the entry if a *new* one is added
+    /// (not overwritten), else returns `None`
+    ///
+    /// the root of the subtree may be left red
+    pub fn insert(
+        link: &mut Option<Box<Node<K, V>>>,
+        key: K,
+        value: V,
+    ) -> Option<usize> {
+        Node::put(link, key, value, true)
+    }
+
+    /// inserts a new entry with key and value `key`, `value`
+    /// in the subtree rooted at `link`, then repairs it
+    /// if an entry with `key` already exists, does not overwrite old value
+    /// returns the rank of the entry if a new one is created
+    /// else returns `None`
+    ///
+    /// the root of the subtree may be left red
+    pub fn try_insert(
+        link: &mut Option<Box<Node<K, V>>>,
+        key: K,
+        value: V,
+    ) -> Option<usize> {
+        Node::put(link, key, value, false)
+    }
+
+    /// does the work of `insert` and `try_insert`
+    /// the rank is counted on the way back up, and the repairs never
+    /// change it, since rotations keep the entries in order
+    fn put(
+        link: &mut Option<Box<Node<K, V>>>,
+        key: K,
+        value: V,
+        overwrite: bool,
+    ) -> Option<usize> {
+        let Some(node) = link else {
+            *link = Some(Box::new(Node::new(key, value)));
+            return Some(0);
+        };
+        let rank = match key.cmp(node.key()) {
+            Ordering::Less => Node::put(&mut node.left, key, value, overwrite),
+            Ordering::Greater => {
+                let smaller = Node::size_of(&node.left) + 1;
+                Node::put(&mut node.right, key, value, overwrite)
+                    .map(|rank| smaller + rank)
+            }
+            Ordering::Equal => {
+                if overwrite {
+                    node.entry.value = value;
+                }
+                return None;
+            }
+        };
+        if rank.is_some() {
+            node.size += 1;
+            Node::fix_insert(link);
+        }
+        rank
+    }
+
+    /// removes the entry with key `key` from the subtree rooted at `link`,
+    /// then repairs it
+    /// returns the removed entry, or `None` if no such entry exists
+    ///
+    /// the root of the subtree may be left red
+    pub fn remove<Q>(
+        link: &mut Option<Box<Node<K, V>>>,
+        key: &Q,
+    ) -> Option<Entry<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut locate = |node: &Node<K, V>| key.cmp(node.key().borrow());
+        Node::delete(link, &mut locate, &mut false)
+    }
+
+    /// removes the entry with `index` smaller entries from the subtree
+    /// rooted at `link`, then repairs it
+    /// returns the removed entry, or `None` if `index` is out of bounds
+    ///
+    /// the root of the subtree may be left red
+    pub fn remove_nth(
+        link: &mut Option<Box<Node<K, V>>>,
+        mut index: usize,
+    ) -> Option<Entry<K, V>> {
+        let mut locate = |node: &Node<K, V>| {
+            let left = Node::size_of(&node.left);
+            let ordering = index.cmp(&left);
+            if ordering == Ordering::Greater {
+                index -= left + 1;
+            }
+            ordering
+        };
+        Node::delete(link, &mut locate, &mut false)
+    }
+
+    /// removes an entry from the subtree rooted at `link`
+    /// `locate` tells for each node on the way whether the entry is in
+    /// its left subtree, is its own, or is in its right subtree
+    /// sets `shorter` if the subtree lost a black node on every path
+    fn delete<F>(
+        link: &mut Option<Box<Node<K, V>>>,
+        locate: &mut F,
+        shorter: &mut bool,
+    ) -> Option<Entry<K, V>>
+    where
+        F: FnMut(&Node<K, V>) -> Ordering,
+    {
+        let node = link.as_mut()?;
+        match locate(node) {
+            Ordering::Less => {
+                let removed = Node::delete(&mut node.left, locate, shorter);
+                if removed.is_some() {
+                    node.size -= 1;
+                }
+                if *shorter {
+                    *shorter = Node::fix_left(link);
+                }
+                removed
+            }
+            Ordering::Greater => {
+                let removed = Node::delete(&mut node.right, locate, shorter);
+                if removed.is_some() {
+                    node.size -= 1;
+                }
+                if *shorter {
+                    *shorter = Node::fix_right(link);
+                }
+                removed
+            }
+            Ordering::Equal => Node::delete_root(link, shorter),
+        }
+    }
+
+    /// removes the root of the subtree rooted at `link` and returns its entry
+    /// sets `shorter` if the subtree lost a black node on every path
+    ///
+    /// a node with two children swaps entries with its in-order successor,
+    /// which is then removed instead
+    fn delete_root(
+        link: &mut Option<Box<Node<K, V>>>,
+        shorter: &mut bool,
+    ) -> Option<Entry<K, V>> {
+        let node = link.as_mut()?;
+        if node.left.is_some() && node.right.is_some() {
+            let mut successor = Node::take_smallest(&mut node.right, shorter)?;
+            core::mem::swap(&mut node.entry, &mut successor.entry);
+            node.size -= 1;
+            if *shorter {
+                *shorter = Node::fix_right(link);
+            }
+            return Some(successor.entry);
+        }
+        let mut node = link.take()?;
+        // a single child is always red, so painting it black makes up
+        // for the removed node
+        *link = node.left.take().or(node.right.take());
+        match link {
+            Some(child) => child.color = Color::Black,
+            None => *shorter = node.color == Color::Black,
+        }
+        Some(node.entry)
+    }
+
+    /// detaches the node with the smallest key from the subtree rooted at
+    /// `link`, putting its right child in its place and repairing the tree
+    /// sets `shorter` if the subtree lost a black node on every path
+    fn take_smallest(
+        link: &mut Option<Box<Node<K, V>>>,
+        shorter: &mut bool,
+    ) -> Option<Box<Node<K, V>>> {
+        match link {
+            Some(node) if node.left.is_some() => {
+                let smallest = Node::take_smallest(&mut node.left, shorter);
+                node.size -= 1;
+                if *shorter {
+                    *shorter = Node::fix_left(link);
+                }
+                smallest
+            }
+            _ => {
+                let mut node = link.take()?;
+                *link = node.right.take();
+                match link {
+                    Some(child) => child.color = Color::Black,
+                    None => *shorter = node.color == Color::Black,
+                }
+                Some(node)
+            }
+        }
+    }
+
+    /// returns a reference to the smallest entry
+    pub fn smallest(&self) -> &Entry<K, V> {
+        match self.left {
+            Some(ref node) => node.smallest(),
+            None => &self.entry,
+        }
+    }
+
+    /// returns a mutable reference to the smallest entry
+    pub fn smallest_mut(&mut self) -> &mut Entry<K, V> {
+        match self.left {
+            Some(ref mut node) => node.smallest_mut(),
+            None => &mut self.entry,
+        }
+    }
+
+    /// returns a reference to the largest entry
+    pub fn largest(&self) -> &Entry<K, V> {
+        match self.right {
+            Some(ref node) => node.largest(),
+            None => &self.entry,
+        }
+    }
+
+    /// returns a mutable reference to the largest entry
+    pub fn largest_mut(&mut self) -> &mut Entry<K, V> {
+        match self.right {
+            Some(ref mut node) => node.largest_mut(),
+            None => &mut self.entry,
+        }
+    }
+
+    /// builds a tree out of the first `len` entries of `entries`,
+    /// which must be sorted by key
+    ///
+    /// the tree is as balanced as possible, so only its deepest level,
+    /// which may be incomplete, has to be red
+    pub fn from_sorted(
+        len: usize,
+        entries: &mut impl Iterator<Item = (K, V)>,
+    ) -> Option<Box<Node<K, V>>> {
+        let red_depth = len.checked_ilog2()?;
+        let mut root = Node::build(len, entries, 0, red_depth)?;
+        root.color = Color::Black;
+        Some(root)
+    }
+
+    fn build(
+        len: usize,
+        entries: &mut impl Iterator<Item = (K, V)>,
+        depth: u32,
+        red_depth: u32,
+    ) -> Option<Box<Node<K, V>>> {
+        if len == 0 {
+            return None;
+        }
+        let left = Node::build(len / 2, entries, depth + 1, red_depth);
+        let (key, value) = entries.next()?;
+        let right =
+            Node::build(len - len / 2 - 1, entries, depth + 1, red_depth);
+        Some(Box::new(Node {
+            entry: Entry::new(key, value),
+            size: len,
+            color: match depth == red_depth {
+                true => Color::Red,
+                false => Color::Black,
+            },
+            left,
+            right,
+        }))
+    }
+
+    /// returns the number of entries in the subtree rooted at `self`
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// returns the number of entries in the subtree rooted at `link`
+    pub fn size_of(link: &Option<Box<Node<K, V>>>) -> usize {
+        link.as_ref().map_or(0, |node| node.size)
+    }
+
+    /// returns the entry with `index` smaller entries
+    /// in the subtree rooted at `self`
+    pub fn nth(&self, index: usize) -> Option<&Entry<K, V>> {
+        let left = Node::size_of(&self.left);
+        match index.cmp(&left) {
+            Ordering::Less => self.left.as_ref()?.nth(index),
+            Ordering::Equal => Some(&self.entry),
+            Ordering::Greater => self.right.as_ref()?.nth(index - left - 1),
+        }
+    }
+
+    /// returns a mutable reference to the entry with `index` smaller entries
+    /// in the subtree rooted at `self`
+    pub fn nth_mut(&mut self, index: usize) -> Option<&mut Entry<K, V>> {
+        let left = Node::size_of(&self.left);
+        match index.cmp(&left) {
+            Ordering::Less => self.left.as_mut()?.nth_mut(index),
+            Ordering::Equal => Some(&mut self.entry),
+            Ordering::Greater => self.right.as_mut()?.nth_mut(index - left - 1),
+        }
+    }
+
+    /// returns the number of entries in the subtree rooted at `self`
+    /// with keys smaller than `key`
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let left = Node::size_of(&self.left);
+        match key.cmp(self.key().borrow()) {
+            Ordering::Less => {
+                self.left.as_ref().map_or(0, |node| node.rank(key))
+            }
+            Ordering::Equal => left,
+            Ordering::Greater => {
+                left + 1 + self.right.as_ref().map_or(0, |node| node.rank(key))
+            }
+        }
+    }
+
+    /// returns the number of entries in the subtree rooted at `self`
+    /// with keys smaller than `key`, along with the entry with key `key`,
+    /// if there is one
+    pub fn search<Q>(&mut self, key: &Q) -> (usize, Option<&mut Entry<K, V>>)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let left = Node::size_of(&self.left);
+        match key.cmp(self.key().borrow()) {
+            Ordering::Less => match self.left {
+                Some(ref mut node) => node.search(key),
+                None => (0, None),
+            },
+            Ordering::Equal => (left, Some(&mut self.entry)),
+            Ordering::Greater => match self.right {
+                Some(ref mut node) => {
+                    let (rank, entry) = node.search(key);
+                    (left + 1 + rank, entry)
+                }
+                None => (left + 1, None),
+            },
+        }
+    }
+
+    /// returns `true` if `link` holds a red node
+    pub fn is_red(link: &Option<Box<Node<K, V>>>) -> bool {
+        link.as_ref().is_some_and(|node| node.color == Color::Red)
+    }
+
+    /// paints the root of `link` black
+    /// returns `true` if it was red
+    pub fn blacken(link: &mut Option<Box<Node<K, V>>>) -> bool {
+        match link {
+            Some(node) if node.color == Color::Red => {
+                node.color = Color::Black;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// repairs a red child of the root of `link` that has a red child,
+    /// if there is one
+    ///
+    /// with a red sibling, the colors are flipped and the root turns red,
+    /// which may in turn need repairing further up
+    /// otherwise, one or two rotations fix it for good
+    fn fix_insert(link: &mut Option<Box<Node<K, V>>>) {
+        let Some(mut node) = link.take() else {
+            return;
+        };
+        let left = node.left.as_ref().is_some_and(|left| {
+            left.color == Color::Red
+                && (Node::is_red(&left.left) || Node::is_red(&left.right))
+        });
+        let right = node.right.as_ref().is_some_and(|right| {
+            right.color == Color::Red
+                && (Node::is_red(&right.left) || Node::is_red(&right.right))
+        });
+        if (left || right)
+            && Node::is_red(&node.left)
+            && Node::is_red(&node.right)
+        {
+            Node::blacken(&mut node.left);
+            Node::blacken(&mut node.right);
+            node.color = Color::Red;
+        } else if left {
+            if let Some(left) = node.left.take() {
+                node.left = Some(match Node::is_red(&left.right) {
+                    true => Node::rotate_left(left),
+                    false => left,
+                });
+            }
+            node = Node::rotate_right(node);
+            node.color = Color::Black;
+            if let Some(ref mut right) = node.right {
+                right.color = Color::Red;
+            }
+        } else if right {
+            if let Some(right) = node.right.take() {
+                node.right = Some(match Node::is_red(&right.left) {
+                    true => Node::rotate_right(right),
+                    false => right,
+                });
+            }
+            node = Node::rotate_left(node);
+            node.color = Color::Black;
+            if let Some(ref mut left) = node.left {
+                left.color = Color::Red;
+            }
+        }
+        *link = Some(node);
+    }
+
+    /// repairs the root of `link` after its left subtree lost a black node
+    /// on every path
+    /// returns `true` if the whole subtree is still one black node short
+    fn fix_left(link: &mut Option<Box<Node<K, V>>>) -> bool {
+        let Some(mut node) = link.take() else {
+            return false;
+        };
+        if Node::blacken(&mut node.left) {
+            *link = Some(node);
+            return false;
+        }
+        if Node::is_red(&node.right) {
+            // rotate the red sibling up, so that the short side
+            // gets a black sibling and a red parent
+            node = Node::rotate_left(node);
+            node.color = Color::Black;
+            if let Some(ref mut left) = node.left {
+                left.color = Color::Red;
+            }
+            Node::fix_left(&mut node.left);
+            *link = Some(node);
+            return false;
+        }
+        let Some(mut sibling) = node.right.take() else {
+            *link = Some(node);
+            return false;
+        };
+        if !Node::is_red(&sibling.left) && !Node::is_red(&sibling.right) {
+            sibling.color = Color::Red;
+            node.right = Some(sibling);
+            let shorter = node.color == Color::Black;
+            node.color = Color::Black;
+            *link = Some(node);
+            return shorter;
+        }
+        if !Node::is_red(&sibling.right) {
+            sibling = Node::rotate_right(sibling);
+            sibling.color = Color::Black;
+            if let Some(ref mut right) = sibling.right {
+                right.color = Color::Red;
+            }
+        }
+        node.right = Some(sibling);
+        let color = node.color;
+        node = Node::rotate_left(node);
+        node.color = color;
+        Node::blacken(&mut node.left);
+        Node::blacken(&mut node.right);
+        *link = Some(node);
+        false
+    }
+
+    /// repairs the root of `link` after its right subtree lost a black node
+    /// on every path
+    /// returns `true` if the whole subtree is still one black node short
+    fn fix_right(link: &mut Option<Box<Node<K, V>>>) -> bool {
+        let Some(mut node) = link.take() else {
+            return false;
+        };
+        if Node::blacken(&mut node.right) {
+            *link = Some(node);
+            return false;
+        }
+        if Node::is_red(&node.left) {
+            // rotate the red sibling up, so that the short side
+            // gets a black sibling and a red parent
+            node = Node::rotate_right(node);
+            node.color = Color::Black;
+            if let Some(ref mut right) = node.right {
+                right.color = Color::Red;
+            }
+            Node::fix_right(&mut node.right);
+            *link = Some(node);
+            return false;
+        }
+        let Some(mut sibling) = node.left.take() else {
+            *link = Some(node);
+            return false;
+        };
+        if !Node::is_red(&sibling.left) && !Node::is_red(&sibling.right) {
+            sibling.color = Color::Red;
+            node.left = Some(sibling);
+            let shorter = node.color == Color::Black;
+            node.color = Color::Black;
+            *link = Some(node);
+            return shorter;
+        }
+        if !Node::is_red(&sibling.left) {
+            sibling = Node::rotate_left(sibling);
+            sibling.color = Color::Black;
+            if let Some(ref mut left) = sibling.left {
+                left.color = Color::Red;
+            }
+        }
+        node.left = Some(sibling);
+        let color = node.color;
+        node = Node::rotate_right(node);
+        node.color = color;
+        Node::blacken(&mut node.left);
+        Node::blacken(&mut node.right);
+        *link = Some(node);
+        false
+    }
+
+    /// makes the left child of `node` the root of its subtree
+    fn rotate_right(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let Some(mut left) = node.left.take() else {
+            return node;
+        };
+        node.left = left.right.take();
+        node.update_size();
+        left.right = Some(node);
+        left.update_size();
+        left
+    }
+
+    /// makes the right child of `node` the root of its subtree
+    fn rotate_left(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+        let Some(mut right) = node.right.take() else {
+            return node;
+        };
+        node.right = right.left.take();
+        node.update_size();
+        right.left = Some(node);
+        right.update_size();
+        right
+    }
+
+    /// recalculates `self.size` from the sizes of its children
+    fn update_size(&mut self) {
+        self.size = 1 + Node::size_of(&self.left) + Node::size_of(&self.right);
+    }
+}
+
+impl<'a, K, V> Expand for &'a Node<K, V>
+where
+    K: Ord,
+{
+    type Entry = (&'a K, &'a V);
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let (left, entry, right) = self.split();
+        [
+            left.map(Step::Node),
+            Some(Step::Entry(entry)),
+            right.map(Step::Node),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl<K, V> Split for &Node<K, V>
+where
+    K: Ord,
+{
+    type Key = K;
+
+    fn split(self) -> (Option<Self>, Self::Entry, Option<Self>) {
+        (
+            self.left.as_deref(),
+            self.entry.split(),
+            self.right.as_deref(),
+        )
+    }
+
+    fn key(entry: &Self::Entry) -> &K {
+        entry.0
+    }
+}
+
+impl<'a, K, V> Expand for &'a mut Node<K, V>
+where
+    K: Ord,
+{
+    type Entry = (&'a K, &'a mut V);
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let (left, entry, right) = self.split();
+        [
+            left.map(Step::Node),
+            Some(Step::Entry(entry)),
+            right.map(Step::Node),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl<K, V> Split for &mut Node<K, V>
+where
+    K: Ord,
+{
+    type Key = K;
+
+    fn split(self) -> (Option<Self>, Self::Entry, Option<Self>) {
+        (
+            self.left.as_deref_mut(),
+            self.entry.split_mut(),
+            self.right.as_deref_mut(),
+        )
+    }
+
+    fn key(entry: &Self::Entry) -> &K {
+        entry.0
+    }
+}
+
+impl<K, V> Expand for Box<Node<K, V>>
+where
+    K: Ord,
+{
+    type Entry = (K, V);
+
+    fn expand(
+        self,
+    ) -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>> {
+        let Node {
+            entry, left, right, ..
+        } = *self;
+        [
+            left.map(Step::Node),
+            Some(Step::Entry(entry.into_pair())),
+            right.map(Step::Node),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
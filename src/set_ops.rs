@@ -0,0 +1,217 @@
+//! lazy set operations over two iterators of sorted, distinct keys
+//! each step peeks at the next key of both sides and advances the smaller,
+//! so every operation runs in linear time and never allocates
+use core::cmp::{self, Ordering};
+use core::iter::Peekable;
+
+/// an iterator over the keys in either of two sets, in order
+pub struct Union<I>
+where
+    I: Iterator,
+{
+    left: Peekable<I>,
+    right: Peekable<I>,
+}
+
+/// an iterator over the keys in both of two sets, in order
+pub struct Intersection<I>
+where
+    I: Iterator,
+{
+    left: Peekable<I>,
+    right: Peekable<I>,
+}
+
+/// an iterator over the keys in the first of two sets but not the second,
+/// in order
+pub struct Difference<I>
+where
+    I: Iterator,
+{
+    left: Peekable<I>,
+    right: Peekable<I>,
+}
+
+/// an iterator over the keys in exactly one of two sets, in order
+pub struct SymmetricDifference<I>
+where
+    I: Iterator,
+{
+    left: Peekable<I>,
+    right: Peekable<I>,
+}
+
+impl<I> Union<I>
+where
+    I: Iterator,
+{
+    pub fn new(left: I, right: I) -> Union<I> {
+        Union {
+            left: left.peekable(),
+            right: right.peekable(),
+        }
+    }
+}
+
+impl<I> Iterator for Union<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ordering = match (self.left.peek(), self.right.peek()) {
+            (Some(left), Some(right)) => left.cmp(right),
+            (Some(_), None) => Ordering::Less,
+            (None, _) => Ordering::Greater,
+        };
+        match ordering {
+            Ordering::Less => self.left.next(),
+            Ordering::Greater => self.right.next(),
+            Ordering::Equal => {
+                self.right.next();
+                self.left.next()
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (left_min, left_max) = self.left.size_hint();
+        let (right_min, right_max) = self.right.size_hint();
+        let max = left_max.zip(right_max).and_then(|(l, r)| l.checked_add(r));
+        (cmp::max(left_min, right_min), max)
+    }
+}
+
+impl<I> Intersection<I>
+where
+    I: Iterator,
+{
+    pub fn new(left: I, right: I) -> Intersection<I> {
+        Intersection {
+            left: left.peekable(),
+            right: right.peekable(),
+        }
+    }
+}
+
+impl<I> Iterator for Intersection<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let ordering = match (self.left.peek(), self.right.peek()) {
+                (Some(left), Some(right)) => left.cmp(right),
+                _ => return None,
+            };
+            match ordering {
+                Ordering::Less => self.left.next(),
+                Ordering::Greater => self.right.next(),
+                Ordering::Equal => {
+                    self.right.next();
+                    return self.left.next();
+                }
+            };
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let max = match (self.left.size_hint().1, self.right.size_hint().1) {
+            (Some(left), Some(right)) => Some(cmp::min(left, right)),
+            (left, right) => left.or(right),
+        };
+        (0, max)
+    }
+}
+
+impl<I> Difference<I>
+where
+    I: Iterator,
+{
+    pub fn new(left: I, right: I) -> Difference<I> {
+        Difference {
+            left: left.peekable(),
+            right: right.peekable(),
+        }
+    }
+}
+
+impl<I> Iterator for Difference<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let ordering = match (self.left.peek(), self.right.peek()) {
+                (Some(left), Some(right)) => left.cmp(right),
+                (Some(_), None) => Ordering::Less,
+                (None, _) => return None,
+            };
+            match ordering {
+                Ordering::Less => return self.left.next(),
+                Ordering::Greater => self.right.next(),
+                Ordering::Equal => {
+                    self.left.next();
+                    self.right.next()
+                }
+            };
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.left.size_hint().1)
+    }
+}
+
+impl<I> SymmetricDifference<I>
+where
+    I: Iterator,
+{
+    pub fn new(left: I, right: I) -> SymmetricDifference<I> {
+        SymmetricDifference {
+            left: left.peekable(),
+            right: right.peekable(),
+        }
+    }
+}
+
+impl<I> Iterator for SymmetricDifference<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let ordering = match (self.left.peek(), self.right.peek()) {
+                (Some(left), Some(right)) => left.cmp(right),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => return None,
+            };
+            match ordering {
+                Ordering::Less => return self.left.next(),
+                Ordering::Greater => return self.right.next(),
+                Ordering::Equal => {
+                    self.left.next();
+                    self.right.next();
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.left.size_hint().1;
+        let right = self.right.size_hint().1;
+        (0, left.zip(right).and_then(|(l, r)| l.checked_add(r)))
+    }
+}
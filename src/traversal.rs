@@ -0,0 +1,213 @@
+//! a double-ended, in-order walk over the nodes of a tree
+//!
+//! instead of recursing, pending subtrees are kept on an explicit deque:
+//! the front of the deque holds what is left of the walk from the smallest
+//! entry, and the back holds what is left of the walk from the largest one
+use crate::alloc::{Allocator, Global};
+use crate::deque::Deque;
+use core::borrow::Borrow;
+use core::ops::{Bound, RangeBounds};
+
+/// one pending piece of a traversal
+pub enum Step<N, E> {
+    /// a subtree that has not been expanded yet
+    Node(N),
+    /// a single entry, ready to be yielded
+    Entry(E),
+}
+
+/// a handle to a node (`&Node`, `&mut Node` or `Box<Node>`) that can be
+/// split into its children and entries
+pub trait Expand: Sized {
+    type Entry;
+
+    /// returns the children and entries directly owned by `self`, in order
+    fn expand(self)
+        -> impl DoubleEndedIterator<Item = Step<Self, Self::Entry>>;
+}
+
+/// a handle to a node of a binary tree
+pub trait Split: Expand {
+    type Key;
+
+    /// splits `self` into its left child, its entry and its right child
+    fn split(self) -> (Option<Self>, Self::Entry, Option<Self>);
+
+    /// returns the key of an entry yielded by `split`
+    fn key(entry: &Self::Entry) -> &Self::Key;
+}
+
+/// the pending steps are kept in memory from `A`
+pub struct Traversal<N, A = Global>
+where
+    N: Expand,
+    A: Allocator,
+{
+    steps: Deque<Step<N, N::Entry>, A>,
+}
+
+impl<N, A> Traversal<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+    /// creates a traversal over every entry below `root`,
+    /// keeping its pending steps in memory from `alloc`
+    pub fn new_in(root: Option<N>, alloc: A) -> Traversal<N, A> {
+        let mut steps = Deque::new_in(alloc);
+        if let Some(root) = root {
+            steps.push_back(Step::Node(root));
+        }
+        Traversal { steps }
+    }
+
+    /// drops every pending step, ending the traversal
+    pub fn clear(&mut self) {
+        self.steps.clear();
+    }
+}
+
+impl<N, A> Traversal<N, A>
+where
+    N: Split,
+    A: Allocator,
+{
+    /// creates a traversal over the entries below `root`
+    /// whose keys lie within `range`,
+    /// keeping its pending steps in memory from `alloc`
+    ///
+    /// subtrees entirely outside of `range` are never visited
+    pub fn range_in<Q, R>(
+        root: Option<N>,
+        range: &R,
+        alloc: A,
+    ) -> Traversal<N, A>
+    where
+        N::Key: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let below = |key: &Q| match range.start_bound() {
+            Bound::Included(start) => key < start,
+            Bound::Excluded(start) => key <= start,
+            Bound::Unbounded => false,
+        };
+        let above = |key: &Q| match range.end_bound() {
+            Bound::Included(end) => key > end,
+            Bound::Excluded(end) => key >= end,
+            Bound::Unbounded => false,
+        };
+
+        let mut steps = Deque::new_in(alloc);
+        let mut node = root;
+        // find the highest node within `range`
+        // everything in range lies below it
+        while let Some(current) = node {
+            let (left, entry, right) = current.split();
+            let key = N::key(&entry).borrow();
+            if below(key) {
+                node = right;
+            } else if above(key) {
+                node = left;
+            } else {
+                steps.push_back(Step::Entry(entry));
+                Self::prune_start(left, below, &mut steps);
+                Self::prune_end(right, above, &mut steps);
+                break;
+            }
+        }
+        Traversal { steps }
+    }
+
+    /// pushes the parts of `node` that are not `below` the range
+    /// to the front of `steps`
+    ///
+    /// the keys of `node` must all be within the end bound
+    fn prune_start<Q>(
+        mut node: Option<N>,
+        below: impl Fn(&Q) -> bool,
+        steps: &mut Deque<Step<N, N::Entry>, A>,
+    ) where
+        N::Key: Borrow<Q>,
+        Q: ?Sized,
+    {
+        while let Some(current) = node {
+            let (left, entry, right) = current.split();
+            if below(N::key(&entry).borrow()) {
+                node = right;
+            } else {
+                if let Some(right) = right {
+                    steps.push_front(Step::Node(right));
+                }
+                steps.push_front(Step::Entry(entry));
+                node = left;
+            }
+        }
+    }
+
+    /// pushes the parts of `node` that are not `above` the range
+    /// to the back of `steps`
+    ///
+    /// the keys of `node` must all be within the start bound
+    fn prune_end<Q>(
+        mut node: Option<N>,
+        above: impl Fn(&Q) -> bool,
+        steps: &mut Deque<Step<N, N::Entry>, A>,
+    ) where
+        N::Key: Borrow<Q>,
+        Q: ?Sized,
+    {
+        while let Some(current) = node {
+            let (left, entry, right) = current.split();
+            if above(N::key(&entry).borrow()) {
+                node = left;
+            } else {
+                if let Some(left) = left {
+                    steps.push_back(Step::Node(left));
+                }
+                steps.push_back(Step::Entry(entry));
+                node = right;
+            }
+        }
+    }
+}
+
+impl<N, A> Iterator for Traversal<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+    type Item = N::Entry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.steps.pop_front()? {
+                Step::Node(node) => {
+                    for step in node.expand().rev() {
+                        self.steps.push_front(step);
+                    }
+                }
+                Step::Entry(entry) => return Some(entry),
+            }
+        }
+    }
+}
+
+impl<N, A> DoubleEndedIterator for Traversal<N, A>
+where
+    N: Expand,
+    A: Allocator,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.steps.pop_back()? {
+                Step::Node(node) => {
+                    for step in node.expand() {
+                        self.steps.push_back(step);
+                    }
+                }
+                Step::Entry(entry) => return Some(entry),
+            }
+        }
+    }
+}
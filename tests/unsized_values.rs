@@ -0,0 +1,67 @@
+//! trait objects of standard library traits can be stored in a `BsTreeMap`
+//! from outside the crate, where the orphan rules would keep them from
+//! implementing `Value` themselves
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+use terees::binary_search_tree::map::BsTreeMap;
+
+#[test]
+fn closures() {
+    let mut handlers: BsTreeMap<&str, dyn Fn(u32) -> u32> = BsTreeMap::new();
+    handlers.insert_boxed("double", Box::new(|x| x * 2));
+    handlers.insert_boxed("square", Box::new(|x| x * x));
+    assert_eq!(handlers.get("double").map(|f| f(5)), Some(10));
+    assert!(handlers.values().map(|f| f(3)).eq([6, 9]));
+
+    let mut total = 0;
+    let mut counters: BsTreeMap<u32, dyn FnMut(u32) + Send> = BsTreeMap::new();
+    counters.insert_boxed(0, Box::new(|x| total += x));
+    if let Some(count) = counters.get_mut(&0) {
+        count(4);
+        count(5);
+    }
+    drop(counters);
+    assert_eq!(total, 9);
+
+    let mut tasks: BsTreeMap<u32, dyn FnOnce() -> String> = BsTreeMap::new();
+    tasks.insert_boxed(1, Box::new(|| String::from("done")));
+    let task = tasks.remove(&1).map(|entry| entry.into_boxed_pair().1);
+    assert_eq!(task.map(|task| task()), Some(String::from("done")));
+}
+
+#[derive(Debug)]
+struct Failure;
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("failure")
+    }
+}
+
+impl Error for Failure {}
+
+#[test]
+fn other_trait_objects() {
+    let mut anything: BsTreeMap<u32, dyn Any + Send + Sync> = BsTreeMap::new();
+    anything.insert_boxed(1, Box::new(1_u8));
+    anything.insert_boxed(2, Box::new("two"));
+    assert_eq!(
+        anything.get(&1).and_then(|value| value.downcast_ref()),
+        Some(&1_u8)
+    );
+    assert!(anything.get(&2).is_some_and(|value| value.is::<&str>()));
+
+    let mut errors: BsTreeMap<u32, dyn Error + Send + Sync> = BsTreeMap::new();
+    errors.insert_boxed(0, Box::new(Failure));
+    errors.insert_boxed(1, "custom".into());
+    assert!(errors
+        .values()
+        .map(|error| error.to_string())
+        .eq(["failure", "custom"]));
+
+    let mut shown: BsTreeMap<u32, dyn fmt::Display> = BsTreeMap::new();
+    shown.insert_boxed(0, Box::new(7));
+    shown.insert_boxed(1, Box::new('x'));
+    assert!(shown.values().map(|value| value.to_string()).eq(["7", "x"]));
+}